@@ -1,17 +1,30 @@
 use anyhow::Result;
+use chrono::{DateTime, Days, Duration, NaiveDate, NaiveTime, Utc};
 use clap::Parser;
+use clap::ValueEnum;
 use code_common::CliConfigOverrides;
+use code_core::account_slots::slot_account_emails;
 use code_core::config::{Config, ConfigOverrides};
 use code_core::global_usage_tracker::{
+    format_cost,
     scan_global_usage,
+    usage_by_account,
+    AccountUsage,
+    DEFAULT_COST_PRECISION,
     GlobalUsageScanOptions,
     GlobalUsageSnapshot,
     ModelBucket,
+    ProviderRates,
+    SessionUsage,
     UsageBucket,
     UsageTotals,
+    WindowUsage,
 };
 use code_protocol::num_format::format_with_separators;
+use serde_json::json;
 use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
@@ -27,11 +40,152 @@ pub struct UsageCommand {
     #[clap(long = "workers", value_name = "N")]
     pub workers: Option<usize>,
 
+    /// Skip a session source by label (e.g. `.code/slot/work`) or directory prefix; repeatable
+    #[clap(long = "exclude-source", value_name = "LABEL_OR_PATH")]
+    pub exclude_source: Vec<String>,
+
     /// Print per-session totals after the aggregate summary
     #[clap(long)]
     pub verbose: bool,
+
+    /// With --verbose, list only sessions with at least N total tokens (totals still include all)
+    #[clap(long = "min-tokens", value_name = "N", requires = "verbose")]
+    pub min_tokens: Option<u64>,
+
+    /// Render hourly usage as an ASCII bar chart instead of text rows
+    #[clap(long)]
+    pub histogram: bool,
+
+    /// Label buckets relative to the scan time (e.g. "2h ago–1h ago")
+    #[clap(long = "relative-time")]
+    pub relative_time: bool,
+
+    /// List the N sessions with the most recent activity instead of historical aggregates
+    #[clap(long = "tail", value_name = "N")]
+    pub tail: Option<usize>,
+
+    /// Annotate each time bucket with the model that used the most tokens in it
+    #[clap(long = "bucket-models", conflicts_with = "summary_only")]
+    pub bucket_models: bool,
+
+    /// Fold events into bucket totals while parsing instead of keeping them in memory
+    #[clap(long = "low-memory")]
+    pub low_memory: bool,
+
+    /// Group totals by the account email of each source's slot
+    #[clap(long = "accounts", conflicts_with = "tail")]
+    pub accounts: bool,
+
+    /// Print only totals, model groups and sources, skipping time bucketing
+    #[clap(
+        long = "summary-only",
+        conflicts_with_all = ["verbose", "histogram", "relative_time", "tail", "export_buckets"]
+    )]
+    pub summary_only: bool,
+
+    /// Print a single line of today's and the last 30 days' usage (for shell prompts)
+    #[clap(
+        long = "oneline",
+        conflicts_with_all = ["verbose", "histogram", "relative_time", "tail", "summary_only", "accounts"]
+    )]
+    pub oneline: bool,
+
+    /// Stream the report as newline-delimited JSON, one object per section entry
+    #[clap(
+        long = "jsonl",
+        conflicts_with_all = ["verbose", "histogram", "relative_time", "tail", "oneline", "accounts"]
+    )]
+    pub jsonl: bool,
+
+    /// Compare totals of two trailing windows, e.g. `7d:30d` (units: h, d, w)
+    #[clap(
+        long = "compare",
+        value_name = "A:B",
+        value_parser = parse_compare_windows,
+        conflicts_with_all = ["summary_only", "oneline"]
+    )]
+    pub compare: Option<(Duration, Duration)>,
+
+    /// Add a timeline grouped by this period over the full scan
+    #[clap(
+        long = "group-by",
+        value_enum,
+        value_name = "PERIOD",
+        conflicts_with_all = ["summary_only", "oneline"]
+    )]
+    pub group_by: Option<GroupBy>,
+
+    /// Number of periods in the --group-by timeline (default: 30)
+    #[clap(long = "days", value_name = "N", requires = "group_by")]
+    pub days: Option<usize>,
+
+    /// Keep at most N timeline events older than 7 days, sampling the rest (older buckets become estimates)
+    #[clap(long = "max-timeline-events", value_name = "N", conflicts_with = "low_memory")]
+    pub max_timeline_events: Option<usize>,
+
+    /// Count only usage on or after this UTC date (YYYY-MM-DD) in totals
+    #[clap(long = "since", value_name = "DATE", value_parser = parse_date)]
+    pub since: Option<NaiveDate>,
+
+    /// Count only usage on or before this UTC date (YYYY-MM-DD) in totals
+    #[clap(long = "until", value_name = "DATE", value_parser = parse_date)]
+    pub until: Option<NaiveDate>,
+
+    /// Price cache-write input tokens at this many USD per million (default: 1.25× the input rate)
+    #[clap(long = "cache-write-rate", value_name = "USD_PER_M")]
+    pub cache_write_rate: Option<f64>,
+
+    /// Price models matching a pattern from a JSON/TOML file of `pattern = { non_cached, cached, output }` USD-per-million rates
+    #[clap(long = "provider-rates", value_name = "PATH")]
+    pub provider_rates: Option<PathBuf>,
+
+    /// Write every usage bucket (all granularities) as JSON lines to PATH
+    #[clap(long = "export-buckets", value_name = "PATH")]
+    pub export_buckets: Option<PathBuf>,
+
+    /// Warn when the estimated cost of the scanned usage exceeds this many USD
+    #[clap(long = "budget", value_name = "USD")]
+    pub budget: Option<f64>,
+
+    /// Flag a source whose estimated cost exceeds USD (e.g. `.code/slot/work=5`); repeatable
+    #[clap(long = "source-cost-alert", value_name = "LABEL=USD", value_parser = parse_source_cost_alert)]
+    pub source_cost_alert: Vec<(String, f64)>,
+
+    /// Exit non-zero when --budget is exceeded
+    #[clap(long = "budget-strict", requires = "budget")]
+    pub budget_strict: bool,
+
+    /// Decimal places for costs; tiny nonzero costs still show one significant figure
+    #[clap(long = "cost-precision", value_name = "N", default_value_t = DEFAULT_COST_PRECISION)]
+    pub cost_precision: usize,
+
+    /// Exit non-zero when sessions are missing totals or log lines are malformed
+    #[clap(long = "fail-on-errors")]
+    pub fail_on_errors: bool,
+
+    /// Number of data-quality errors tolerated before exiting non-zero (implies --fail-on-errors)
+    #[clap(long = "max-errors", value_name = "N")]
+    pub max_errors: Option<usize>,
 }
 
+/// Period for the `--group-by` timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    Day,
+}
+
+/// Periods in the `--group-by` timeline when `--days` is not given.
+const DEFAULT_GROUP_DAYS: usize = 30;
+
+/// Exit code used when `--fail-on-errors` trips on a scan with data-quality issues.
+const DATA_QUALITY_EXIT_CODE: i32 = 2;
+
+/// Exit code used when `--budget-strict` trips on a scan over budget.
+const BUDGET_EXCEEDED_EXIT_CODE: i32 = 3;
+
+/// Width in columns of the longest bar drawn by `--histogram`.
+const HISTOGRAM_WIDTH: usize = 40;
+
 impl UsageCommand {
     pub fn run(mut self) -> Result<()> {
         let config = load_config_or_exit(self.config_overrides.take());
@@ -42,11 +196,371 @@ impl UsageCommand {
         if let Some(workers) = self.workers.take() {
             options = options.with_max_workers(workers);
         }
-        options = options.with_record_sessions(self.verbose);
+        if let Some(rate) = self.cache_write_rate {
+            options = options.with_cache_write_rate(rate);
+        }
+        if let Some(path) = self.provider_rates.take() {
+            options = options.with_provider_rates(ProviderRates::load(&path)?);
+        }
+        if let Some((first, second)) = self.compare {
+            options = options.with_custom_windows(vec![first, second]);
+        }
+        if let Some(max_events) = self.max_timeline_events {
+            options = options.with_max_timeline_events(max_events);
+        }
+        if self.group_by == Some(GroupBy::Day) {
+            options = options.with_day_buckets(self.days.unwrap_or(DEFAULT_GROUP_DAYS));
+        }
+        options = options
+            .with_date_window(
+                self.since.map(start_of_day),
+                self.until.map(|date| start_of_day(date + Days::new(1))),
+            )
+            .with_record_sessions(self.verbose || self.tail.is_some())
+            .with_summary_only(self.summary_only)
+            .with_bucket_model_breakdown(self.bucket_models)
+            .with_low_memory(self.low_memory)
+            .with_excluded_sources(std::mem::take(&mut self.exclude_source))
+            .with_source_cost_alert(std::mem::take(&mut self.source_cost_alert).into_iter().collect());
+
+        let exit_code = self.scan_and_report(options)?;
+        if exit_code != 0 {
+            std::process::exit(exit_code);
+        }
+        Ok(())
+    }
 
+    fn scan_and_report(&self, options: GlobalUsageScanOptions) -> Result<i32> {
+        let code_home = options.code_home.clone();
         let snapshot = scan_global_usage(options)?;
-        print_text_summary(&snapshot, self.verbose);
+        if let Some(path) = &self.export_buckets {
+            let mut out = BufWriter::new(File::create(path)?);
+            let written = write_bucket_export(&snapshot, &mut out)?;
+            out.flush()?;
+            eprintln!("Exported {written} bucket(s) to {}", path.display());
+        }
+        let precision = self.cost_precision;
+        if self.jsonl {
+            let mut out = BufWriter::new(std::io::stdout().lock());
+            write_jsonl_report(&snapshot, &mut out)?;
+            out.flush()?;
+        } else {
+            match self.tail {
+                Some(count) => print_tail_sessions(&snapshot, count, precision),
+                None if self.oneline => println!("{}", oneline_summary(&snapshot, precision)),
+                None if self.summary_only => print_summary_only(&snapshot, precision),
+                None => print_text_summary(
+                    &snapshot,
+                    self.verbose,
+                    self.min_tokens.unwrap_or(0),
+                    self.histogram,
+                    self.relative_time,
+                    precision,
+                ),
+            }
+            if self.accounts {
+                let emails = slot_account_emails(&code_home)?;
+                print_account_cards(&usage_by_account(&snapshot.source_usage, &emails), precision);
+            }
+            if let [first, second] = snapshot.custom_windows.as_slice() {
+                print_window_comparison(first, second, precision);
+            }
+        }
+        let over_budget = match self
+            .budget
+            .and_then(|budget| budget_warning(&snapshot, budget, precision))
+        {
+            // Keep stdout parseable when streaming JSON lines.
+            Some(warning) if self.jsonl => {
+                eprintln!("{warning}");
+                true
+            }
+            Some(warning) => {
+                println!("\n{warning}");
+                true
+            }
+            None => false,
+        };
+        let exit_code = data_quality_exit_code(&snapshot, self.fail_on_errors, self.max_errors);
+        if exit_code != 0 {
+            eprintln!(
+                "Data-quality check failed: {} error(s) (missing totals: {}, malformed lines: {})",
+                data_quality_errors(&snapshot),
+                snapshot.sessions_missing_totals,
+                snapshot.malformed_lines
+            );
+            return Ok(exit_code);
+        }
+        if over_budget && self.budget_strict {
+            return Ok(BUDGET_EXCEEDED_EXIT_CODE);
+        }
+        Ok(0)
+    }
+}
+
+/// Writes one JSON object per bucket, across every granularity, returning the
+/// number of lines written.
+fn write_bucket_export<W: Write>(snapshot: &GlobalUsageSnapshot, out: &mut W) -> Result<usize> {
+    let granularities: [(&str, &[UsageBucket]); 6] = [
+        ("hourly", &snapshot.hourly_buckets),
+        ("twelve_hour", &snapshot.twelve_hour_buckets),
+        ("daily", &snapshot.daily_buckets),
+        ("weekly", &snapshot.weekly_buckets),
+        ("monthly", &snapshot.monthly_buckets),
+        ("group_day", &snapshot.day_buckets),
+    ];
+    let mut written = 0;
+    for (granularity, buckets) in granularities {
+        for bucket in buckets {
+            let record = json!({
+                "granularity": granularity,
+                "start": bucket.start.to_rfc3339(),
+                "end": bucket.end.to_rfc3339(),
+                "total_tokens": bucket.totals.total_tokens,
+                "cost_usd": bucket.totals.cost_usd,
+            });
+            writeln!(out, "{record}")?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// Streams the report as newline-delimited JSON: a `totals` line, then one
+/// line per trailing window, bucket, model and source, returning the number of
+/// lines written. Each line is a standalone object tagged by `type`.
+fn write_jsonl_report<W: Write>(snapshot: &GlobalUsageSnapshot, out: &mut W) -> Result<usize> {
+    let mut written = 0;
+    let mut emit = |record: serde_json::Value| -> Result<()> {
+        writeln!(out, "{record}")?;
+        written += 1;
         Ok(())
+    };
+
+    emit(json!({
+        "type": "totals",
+        "generated_at": snapshot.generated_at.to_rfc3339(),
+        "sessions_processed": snapshot.sessions_processed,
+        "sessions_missing_totals": snapshot.sessions_missing_totals,
+        "sessions_zero_usage": snapshot.sessions_zero_usage,
+        "malformed_lines": snapshot.malformed_lines,
+        "default_source_label": snapshot.default_source_label,
+        "timeline_sampled": snapshot.timeline_sampled,
+        "totals": totals_json(&snapshot.totals),
+    }))?;
+
+    let trailing = &snapshot.trailing;
+    let windows: [(&str, &UsageTotals); 6] = [
+        ("1h", &trailing.last_hour),
+        ("12h", &trailing.last_twelve_hours),
+        ("1d", &trailing.last_day),
+        ("7d", &trailing.last_seven_days),
+        ("30d", &trailing.last_thirty_days),
+        ("365d", &trailing.last_year),
+    ];
+    for (window, totals) in windows {
+        emit(json!({ "type": "trailing", "window": window, "totals": totals_json(totals) }))?;
+    }
+    for custom in &snapshot.custom_windows {
+        emit(json!({
+            "type": "trailing",
+            "window": window_label(custom.duration),
+            "totals": totals_json(&custom.totals),
+        }))?;
+    }
+
+    let granularities: [(&str, &[UsageBucket]); 6] = [
+        ("hourly", &snapshot.hourly_buckets),
+        ("twelve_hour", &snapshot.twelve_hour_buckets),
+        ("daily", &snapshot.daily_buckets),
+        ("weekly", &snapshot.weekly_buckets),
+        ("monthly", &snapshot.monthly_buckets),
+        ("group_day", &snapshot.day_buckets),
+    ];
+    for (granularity, buckets) in granularities {
+        for bucket in buckets {
+            emit(json!({
+                "type": "bucket",
+                "granularity": granularity,
+                "start": bucket.start.to_rfc3339(),
+                "end": bucket.end.to_rfc3339(),
+                "totals": totals_json(&bucket.totals),
+            }))?;
+        }
+    }
+
+    for entry in &snapshot.model_usage {
+        emit(json!({
+            "type": "model",
+            "model": entry.bucket.as_str(),
+            "group": entry.bucket.display_group(),
+            "totals": totals_json(&entry.totals),
+        }))?;
+    }
+
+    for entry in &snapshot.source_usage {
+        emit(json!({
+            "type": "source",
+            "label": entry.label,
+            "directory": entry.directory.display().to_string(),
+            "totals": totals_json(&entry.totals),
+        }))?;
+    }
+
+    Ok(written)
+}
+
+fn totals_json(totals: &UsageTotals) -> serde_json::Value {
+    json!({
+        "non_cached_input_tokens": totals.non_cached_input_tokens,
+        "cached_input_tokens": totals.cached_input_tokens,
+        "cache_write_tokens": totals.cache_write_tokens,
+        "output_tokens": totals.output_tokens,
+        "reasoning_output_tokens": totals.reasoning_output_tokens,
+        "total_tokens": totals.total_tokens,
+        "cost_usd": totals.cost_usd,
+    })
+}
+
+fn parse_date(value: &str) -> std::result::Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|err| format!("expected YYYY-MM-DD, got `{value}`: {err}"))
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    date.and_time(NaiveTime::MIN).and_utc()
+}
+
+fn parse_compare_windows(value: &str) -> std::result::Result<(Duration, Duration), String> {
+    let (first, second) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected two windows like 7d:30d, got {value:?}"))?;
+    Ok((parse_window(first)?, parse_window(second)?))
+}
+
+fn parse_window(value: &str) -> std::result::Result<Duration, String> {
+    let value = value.trim();
+    let split = value.len().saturating_sub(1);
+    let (count, unit) = value.split_at(split);
+    let count: i64 = count
+        .parse()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| format!("invalid window {value:?}: expected a positive count such as 7d"))?;
+    match unit {
+        "h" => Ok(Duration::hours(count)),
+        "d" => Ok(Duration::days(count)),
+        "w" => Ok(Duration::weeks(count)),
+        _ => Err(format!("invalid window {value:?}: unit must be h, d or w")),
+    }
+}
+
+fn window_label(duration: Duration) -> String {
+    if duration.num_hours() % 24 == 0 {
+        format!("{}d", duration.num_days())
+    } else {
+        format!("{}h", duration.num_hours())
+    }
+}
+
+/// Change from a baseline window to a comparison window; percentages are
+/// `None` when the baseline is zero.
+#[derive(Debug, Clone, PartialEq)]
+struct WindowDelta {
+    tokens: i128,
+    tokens_percent: Option<f64>,
+    cost_usd: f64,
+    cost_percent: Option<f64>,
+}
+
+fn window_delta(base: &UsageTotals, other: &UsageTotals) -> WindowDelta {
+    let percent = |base: f64, delta: f64| (base != 0.0).then(|| delta / base * 100.0);
+    let tokens = i128::from(other.total_tokens) - i128::from(base.total_tokens);
+    let cost_usd = other.cost_usd - base.cost_usd;
+    WindowDelta {
+        tokens,
+        tokens_percent: percent(base.total_tokens as f64, tokens as f64),
+        cost_usd,
+        cost_percent: percent(base.cost_usd, cost_usd),
+    }
+}
+
+fn print_window_comparison(base: &WindowUsage, other: &WindowUsage, precision: usize) {
+    let delta = window_delta(&base.totals, &other.totals);
+    let fmt_percent = |percent: Option<f64>| match percent {
+        Some(percent) => format!("{percent:+.1}%"),
+        None => "n/a".to_string(),
+    };
+    let sign = |negative: bool| if negative { "-" } else { "+" };
+    println!(
+        "\nWindow comparison ({} → {}):",
+        window_label(base.duration),
+        window_label(other.duration)
+    );
+    println!(
+        "  Tokens : {} → {} ({}{}, {})",
+        fmt_tokens(base.totals.total_tokens),
+        fmt_tokens(other.totals.total_tokens),
+        sign(delta.tokens < 0),
+        fmt_tokens(u64::try_from(delta.tokens.unsigned_abs()).unwrap_or(u64::MAX)),
+        fmt_percent(delta.tokens_percent)
+    );
+    println!(
+        "  Cost   : {} → {} ({}{}, {})",
+        format_cost(base.totals.cost_usd, precision),
+        format_cost(other.totals.cost_usd, precision),
+        sign(delta.cost_usd < 0.0),
+        format_cost(delta.cost_usd.abs(), precision),
+        fmt_percent(delta.cost_percent)
+    );
+}
+
+fn parse_source_cost_alert(value: &str) -> std::result::Result<(String, f64), String> {
+    let (label, cap) = value
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected LABEL=USD, got {value:?}"))?;
+    let cap: f64 = cap
+        .trim()
+        .parse()
+        .map_err(|err| format!("invalid cost cap {cap:?}: {err}"))?;
+    if label.trim().is_empty() || !cap.is_finite() || cap < 0.0 {
+        return Err(format!("expected LABEL=USD with a non-negative cap, got {value:?}"));
+    }
+    Ok((label.trim().to_string(), cap))
+}
+
+/// Warning line for `--budget`, or `None` while the scanned cost is within it.
+fn budget_warning(snapshot: &GlobalUsageSnapshot, budget: f64, precision: usize) -> Option<String> {
+    let cost = snapshot.totals.cost_usd;
+    let overage = cost - budget;
+    (overage > 0.0).then(|| {
+        format!(
+            "⚠ BUDGET EXCEEDED: estimated cost {} is over the {} budget by {}",
+            format_cost(cost, precision),
+            format_cost(budget, precision),
+            format_cost(overage, precision)
+        )
+    })
+}
+
+fn data_quality_errors(snapshot: &GlobalUsageSnapshot) -> usize {
+    snapshot
+        .sessions_missing_totals
+        .saturating_add(snapshot.malformed_lines)
+}
+
+fn data_quality_exit_code(
+    snapshot: &GlobalUsageSnapshot,
+    fail_on_errors: bool,
+    max_errors: Option<usize>,
+) -> i32 {
+    if !fail_on_errors && max_errors.is_none() {
+        return 0;
+    }
+    if data_quality_errors(snapshot) > max_errors.unwrap_or(0) {
+        DATA_QUALITY_EXIT_CODE
+    } else {
+        0
     }
 }
 
@@ -68,13 +582,107 @@ fn load_config_or_exit(overrides: CliConfigOverrides) -> Config {
     }
 }
 
-fn print_text_summary(snapshot: &GlobalUsageSnapshot, verbose: bool) {
+fn print_text_summary(
+    snapshot: &GlobalUsageSnapshot,
+    verbose: bool,
+    min_tokens: u64,
+    histogram: bool,
+    relative_time: bool,
+    precision: usize,
+) {
+    print_totals(snapshot, precision);
+
+    println!("\nRecent usage windows:");
+    print_trailing_line("Last 1 hour", &snapshot.trailing.last_hour);
+    print_trailing_line("Last 12 hours", &snapshot.trailing.last_twelve_hours);
+    print_trailing_line("Last day", &snapshot.trailing.last_day);
+    print_trailing_line("Last 7 days", &snapshot.trailing.last_seven_days);
+    print_trailing_line("Last 30 days", &snapshot.trailing.last_thirty_days);
+    print_trailing_line("Last year", &snapshot.trailing.last_year);
+
+    print_model_groups(snapshot, precision);
+    print_source_cards(snapshot, precision);
+    let relative_to = relative_time.then_some(snapshot.generated_at);
+    let section = |label: &str, buckets: &[UsageBucket]| {
+        print_bucket_section(label, buckets, relative_to, precision)
+    };
+    if histogram {
+        print_bucket_histogram("Hourly usage (last 12 hours)", &snapshot.hourly_buckets, relative_to);
+    } else {
+        section("Hourly usage (last 12 hours)", &snapshot.hourly_buckets);
+    }
+    section("12-hour usage (last 7 days)", &snapshot.twelve_hour_buckets);
+    section("Daily usage (last 7 days)", &snapshot.daily_buckets);
+    section("Weekly usage (last 8 weeks)", &snapshot.weekly_buckets);
+    section("Monthly usage (last 6 months)", &snapshot.monthly_buckets);
+    if !snapshot.day_buckets.is_empty() {
+        let title = format!("Daily usage (last {} days)", snapshot.day_buckets.len());
+        section(&title, &snapshot.day_buckets);
+    }
+
+    if let Some(session) = &snapshot.largest_session {
+        println!(
+            "\nLargest session: {} · {} tokens ({})",
+            session.session_id,
+            fmt_tokens(session.totals.total_tokens),
+            session.model_bucket.as_str()
+        );
+    }
+
+    let sessions = sessions_at_least(snapshot, min_tokens);
+    if verbose && !sessions.is_empty() {
+        println!("\nPer-session totals:");
+        for session in &sessions {
+            let split = session.cost_split;
+            println!(
+                "- {} [{}]: non-cached={} cached={} output={} total={} cost={} (output={} reasoning={})",
+                session.session_id,
+                session.model_bucket.as_str(),
+                fmt_tokens(session.totals.non_cached_input_tokens),
+                fmt_tokens(session.totals.cached_input_tokens),
+                fmt_tokens(
+                    session.totals.output_tokens + session.totals.reasoning_output_tokens
+                ),
+                fmt_tokens(session.totals.total_tokens),
+                format_cost(session.totals.cost_usd, precision),
+                format_cost(split.output_cost, precision),
+                format_cost(split.reasoning_cost, precision)
+            );
+        }
+    }
+    let hidden = snapshot.per_session.len() - sessions.len();
+    if verbose && hidden > 0 {
+        println!("({hidden} session(s) under {} tokens hidden)", fmt_tokens(min_tokens));
+    }
+
+    print_scan_footer(snapshot);
+}
+
+/// `--summary-only` output: totals, model groups and sources, without the
+/// time buckets that were never computed.
+fn print_summary_only(snapshot: &GlobalUsageSnapshot, precision: usize) {
+    print_totals(snapshot, precision);
+    print_model_groups(snapshot, precision);
+    print_source_cards(snapshot, precision);
+    print_scan_footer(snapshot);
+}
+
+fn print_totals(snapshot: &GlobalUsageSnapshot, precision: usize) {
     let generated_at = snapshot.generated_at.format("%Y-%m-%d %H:%M:%S UTC");
     println!("Global token usage as of {generated_at}");
     println!(
-        "Sessions processed: {}  ·  missing totals: {}",
-        snapshot.sessions_processed, snapshot.sessions_missing_totals
+        "Sessions processed: {}  ·  missing totals: {}  ·  malformed lines: {}",
+        snapshot.sessions_processed, snapshot.sessions_missing_totals, snapshot.malformed_lines
     );
+    if snapshot.sessions_zero_usage > 0 {
+        println!("Sessions with zero usage: {}", snapshot.sessions_zero_usage);
+    }
+    if snapshot.future_timestamp_events > 0 {
+        println!(
+            "Ignored {} event(s) timestamped in the future",
+            snapshot.future_timestamp_events
+        );
+    }
 
     println!("\nTotals:");
     println!(
@@ -85,6 +693,12 @@ fn print_text_summary(snapshot: &GlobalUsageSnapshot, verbose: bool) {
         "  Cached input     : {} tokens",
         fmt_tokens(snapshot.totals.cached_input_tokens)
     );
+    if snapshot.totals.cache_write_tokens > 0 {
+        println!(
+            "  Cache writes     : {} tokens",
+            fmt_tokens(snapshot.totals.cache_write_tokens)
+        );
+    }
     println!(
         "  Output           : {} tokens",
         fmt_tokens(snapshot.totals.output_tokens)
@@ -98,52 +712,83 @@ fn print_text_summary(snapshot: &GlobalUsageSnapshot, verbose: bool) {
         fmt_tokens(snapshot.totals.total_tokens)
     );
     println!(
-        "  Estimated cost   : ${:.4}",
-        snapshot.totals.cost_usd
+        "  Estimated cost   : {}",
+        format_cost(snapshot.totals.cost_usd, precision)
     );
+    if snapshot.all_time_totals.total_tokens != snapshot.totals.total_tokens {
+        println!(
+            "  Lifetime         : {} tokens · {}",
+            fmt_tokens(snapshot.all_time_totals.total_tokens),
+            format_cost(snapshot.all_time_totals.cost_usd, precision)
+        );
+    }
+}
 
-    println!("\nRecent usage windows:");
-    print_trailing_line("Last 1 hour", &snapshot.trailing.last_hour);
-    print_trailing_line("Last 12 hours", &snapshot.trailing.last_twelve_hours);
-    print_trailing_line("Last day", &snapshot.trailing.last_day);
-    print_trailing_line("Last 7 days", &snapshot.trailing.last_seven_days);
-    print_trailing_line("Last 30 days", &snapshot.trailing.last_thirty_days);
-    print_trailing_line("Last year", &snapshot.trailing.last_year);
-
-    print_model_groups(snapshot);
-    print_source_cards(snapshot);
-    print_bucket_section("Hourly usage (last 12 hours)", &snapshot.hourly_buckets);
-    print_bucket_section("12-hour usage (last 7 days)", &snapshot.twelve_hour_buckets);
-    print_bucket_section("Daily usage (last 7 days)", &snapshot.daily_buckets);
-    print_bucket_section("Weekly usage (last 8 weeks)", &snapshot.weekly_buckets);
-    print_bucket_section("Monthly usage (last 6 months)", &snapshot.monthly_buckets);
+fn print_scan_footer(snapshot: &GlobalUsageSnapshot) {
+    println!(
+        "\nScanned {} session(s) in {} ms",
+        snapshot.sessions_processed,
+        snapshot.scan_duration_ms
+    );
+    if snapshot.timeline_sampled {
+        println!("Buckets older than 7 days were sampled (--max-timeline-events); treat them as estimates");
+    }
+}
 
-    if let Some(session) = &snapshot.largest_session {
+fn print_tail_sessions(snapshot: &GlobalUsageSnapshot, count: usize, precision: usize) {
+    let sessions = tail_sessions(snapshot, count);
+    println!("Most recently active sessions ({} of {}):", sessions.len(), snapshot.per_session.len());
+    for session in sessions {
+        let last_event = session
+            .last_event_at
+            .map(|ts| ts.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| "—".to_string());
         println!(
-            "\nLargest session: {} · {} tokens ({})",
+            "- {} [{}] last event {}: total={} cost={}",
             session.session_id,
+            session.model_bucket.as_str(),
+            last_event,
             fmt_tokens(session.totals.total_tokens),
-            session.model_bucket.as_str()
+            format_cost(session.totals.cost_usd, precision)
         );
     }
+}
 
-    if verbose && !snapshot.per_session.is_empty() {
-        println!("\nPer-session totals:");
-        for session in &snapshot.per_session {
-            println!(
-                "- {} [{}]: non-cached={} cached={} output={} total={} cost=${:.4}",
-                session.session_id,
-                session.model_bucket.as_str(),
-                fmt_tokens(session.totals.non_cached_input_tokens),
-                fmt_tokens(session.totals.cached_input_tokens),
-                fmt_tokens(
-                    session.totals.output_tokens + session.totals.reasoning_output_tokens
-                ),
-                fmt_tokens(session.totals.total_tokens),
-                session.totals.cost_usd
-            );
-        }
-    }
+/// Sessions shown by `--verbose`, dropping those below `--min-tokens`.
+fn sessions_at_least(snapshot: &GlobalUsageSnapshot, min_tokens: u64) -> Vec<&SessionUsage> {
+    snapshot
+        .per_session
+        .iter()
+        .filter(|session| session.totals.total_tokens >= min_tokens)
+        .collect()
+}
+
+/// Sessions ordered by most recent event first; sessions without timestamps sort last.
+fn tail_sessions(snapshot: &GlobalUsageSnapshot, count: usize) -> Vec<&SessionUsage> {
+    let mut sessions: Vec<&SessionUsage> = snapshot.per_session.iter().collect();
+    sessions.sort_by(|a, b| {
+        b.last_event_at
+            .cmp(&a.last_event_at)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    sessions.truncate(count);
+    sessions
+}
+
+/// Compact `today: … · 30d: …` line built from the trailing day and 30-day windows.
+fn oneline_summary(snapshot: &GlobalUsageSnapshot, precision: usize) -> String {
+    let window = |totals: &UsageTotals| {
+        format!(
+            "{} tok {}",
+            fmt_tokens_compact(totals.total_tokens),
+            format_cost(totals.cost_usd, precision)
+        )
+    };
+    format!(
+        "today: {} · 30d: {}",
+        window(&snapshot.trailing.last_day),
+        window(&snapshot.trailing.last_thirty_days)
+    )
 }
 
 fn print_trailing_line(label: &str, totals: &UsageTotals) {
@@ -160,7 +805,7 @@ fn print_trailing_line(label: &str, totals: &UsageTotals) {
     );
 }
 
-fn print_model_groups(snapshot: &GlobalUsageSnapshot) {
+fn print_model_groups(snapshot: &GlobalUsageSnapshot, precision: usize) {
     println!("\nPer-model totals and cost estimates:");
     if snapshot.model_usage.is_empty() {
         println!("  (no sessions)");
@@ -172,36 +817,36 @@ fn print_model_groups(snapshot: &GlobalUsageSnapshot) {
         map.insert(entry.bucket, entry.totals.clone());
     }
 
-    for (group, buckets) in MODEL_DISPLAY_GROUPS.iter() {
+    for group in ModelBucket::DISPLAY_GROUPS {
+        let members: Vec<(&ModelBucket, &UsageTotals)> = map
+            .iter()
+            .filter(|(bucket, _)| bucket.display_group() == group)
+            .collect();
         let mut group_totals = UsageTotals::default();
-        for bucket in *buckets {
-            if let Some(value) = map.get(bucket) {
-                accumulate_usage_totals(&mut group_totals, value);
-            }
+        for (_, value) in &members {
+            accumulate_usage_totals(&mut group_totals, value);
         }
         if group_totals.total_tokens == 0 {
             continue;
         }
         println!("- {group}:");
         println!(
-            "    tokens={} · cost=${:.4}",
+            "    tokens={} · cost={}",
             fmt_tokens(group_totals.total_tokens),
-            group_totals.cost_usd
+            format_cost(group_totals.cost_usd, precision)
         );
-        for bucket in *buckets {
-            if let Some(value) = map.get(bucket) {
-                println!(
-                    "      {:<18} tokens={} cost=${:.4}",
-                    bucket.as_str(),
-                    fmt_tokens(value.total_tokens),
-                    value.cost_usd
-                );
-            }
+        for (bucket, value) in members {
+            println!(
+                "      {:<18} tokens={} cost={}",
+                bucket.as_str(),
+                fmt_tokens(value.total_tokens),
+                format_cost(value.cost_usd, precision)
+            );
         }
     }
 }
 
-fn print_source_cards(snapshot: &GlobalUsageSnapshot) {
+fn print_source_cards(snapshot: &GlobalUsageSnapshot, precision: usize) {
     println!("\nTop sources:");
     if snapshot.source_usage.is_empty() {
         println!("  (no sessions)");
@@ -209,10 +854,47 @@ fn print_source_cards(snapshot: &GlobalUsageSnapshot) {
     }
     for entry in &snapshot.source_usage {
         println!(
-            "  {:<24} {:>12} tokens   ${:.4}",
+            "  {:<24} {:>12} tokens   {}",
             entry.label,
             fmt_tokens(entry.totals.total_tokens),
-            entry.totals.cost_usd
+            format_cost(entry.totals.cost_usd, precision)
+        );
+    }
+    if let Some(label) = &snapshot.default_source_label {
+        println!("  Default slot credentials: {label}");
+    }
+    for line in source_cost_alert_lines(snapshot, precision) {
+        println!("{line}");
+    }
+}
+
+fn source_cost_alert_lines(snapshot: &GlobalUsageSnapshot, precision: usize) -> Vec<String> {
+    snapshot
+        .source_cost_alerts
+        .iter()
+        .map(|alert| {
+            format!(
+                "  ⚠ {} cost {}, over its {} cap",
+                alert.label,
+                format_cost(alert.cost_usd, precision),
+                format_cost(alert.cap_usd, precision)
+            )
+        })
+        .collect()
+}
+
+fn print_account_cards(accounts: &[AccountUsage], precision: usize) {
+    println!("\nBy account:");
+    if accounts.is_empty() {
+        println!("  (no sessions)");
+        return;
+    }
+    for entry in accounts {
+        println!(
+            "  {:<32} {:>12} tokens   {}",
+            entry.account,
+            fmt_tokens(entry.totals.total_tokens),
+            format_cost(entry.totals.cost_usd, precision)
         );
     }
 }
@@ -224,6 +906,9 @@ fn accumulate_usage_totals(dst: &mut UsageTotals, src: &UsageTotals) {
     dst.cached_input_tokens = dst
         .cached_input_tokens
         .saturating_add(src.cached_input_tokens);
+    dst.cache_write_tokens = dst
+        .cache_write_tokens
+        .saturating_add(src.cache_write_tokens);
     dst.output_tokens = dst.output_tokens.saturating_add(src.output_tokens);
     dst.reasoning_output_tokens = dst
         .reasoning_output_tokens
@@ -232,26 +917,81 @@ fn accumulate_usage_totals(dst: &mut UsageTotals, src: &UsageTotals) {
     dst.cost_usd += src.cost_usd;
 }
 
-fn print_bucket_section(label: &str, buckets: &[UsageBucket]) {
+fn bucket_window_label(bucket: &UsageBucket, relative_to: Option<DateTime<Utc>>) -> String {
+    match relative_to {
+        Some(now) => bucket.relative_label(now),
+        None => format!(
+            "{}-{}",
+            bucket.start.format("%m-%d %H:%M"),
+            bucket.end.format("%H:%M")
+        ),
+    }
+}
+
+fn print_bucket_section(
+    label: &str,
+    buckets: &[UsageBucket],
+    relative_to: Option<DateTime<Utc>>,
+    precision: usize,
+) {
     if buckets.is_empty() {
         return;
     }
     println!("\n{label}:");
     for bucket in buckets {
-        let window = format!(
-            "{}-{}",
-            bucket.start.format("%m-%d %H:%M"),
-            bucket.end.format("%H:%M")
-        );
+        let window = bucket_window_label(bucket, relative_to);
         println!(
-            "  {}  {} tokens (cost ${:.4})",
+            "  {}  {} tokens (cost {}){}",
             window,
             fmt_tokens(bucket.totals.total_tokens),
-            bucket.totals.cost_usd
+            format_cost(bucket.totals.cost_usd, precision),
+            top_model_note(bucket)
+        );
+    }
+}
+
+fn print_bucket_histogram(label: &str, buckets: &[UsageBucket], relative_to: Option<DateTime<Utc>>) {
+    if buckets.is_empty() {
+        return;
+    }
+    let totals: Vec<u64> = buckets.iter().map(|bucket| bucket.totals.total_tokens).collect();
+    let widths = histogram_bar_widths(&totals, HISTOGRAM_WIDTH);
+    println!("\n{label}:");
+    for (bucket, width) in buckets.iter().zip(widths) {
+        let window = bucket_window_label(bucket, relative_to);
+        let bar = "█".repeat(width);
+        println!(
+            "  {window}  │{bar:<HISTOGRAM_WIDTH$}│ {}{}",
+            fmt_tokens(bucket.totals.total_tokens),
+            top_model_note(bucket)
         );
     }
 }
 
+/// `  · top: <model>` suffix for buckets scanned with `--bucket-models`.
+fn top_model_note(bucket: &UsageBucket) -> String {
+    match bucket.top_model() {
+        Some((model, _)) => format!("  · top: {}", model.as_str()),
+        None => String::new(),
+    }
+}
+
+/// Scale each total to a bar width relative to the largest total. Non-zero
+/// totals always get at least one column so small buckets stay visible.
+fn histogram_bar_widths(totals: &[u64], width: usize) -> Vec<usize> {
+    let max = totals.iter().copied().max().unwrap_or(0);
+    totals
+        .iter()
+        .map(|&total| {
+            if max == 0 || total == 0 {
+                return 0;
+            }
+            let scaled = (total as f64 / max as f64 * width as f64).round() as usize;
+            scaled.clamp(1, width)
+        })
+        .collect()
+}
+
 fn fmt_tokens(value: u64) -> String {
     const SCALES: &[(u64, &str)] = &[(1_000_000_000_000, "T"), (1_000_000_000, "B"), (1_000_000, "M"), (1_000, "K")];
     for (scale, suffix) in SCALES {
@@ -262,29 +1002,18 @@ fn fmt_tokens(value: u64) -> String {
     format_with_separators(value)
 }
 
-const MODEL_DISPLAY_GROUPS: &[(&str, &[ModelBucket])] = &[
-    (
-        "gpt-5-codex",
-        &[
-            ModelBucket::Gpt5Codex,
-            ModelBucket::Gpt51Codex,
-            ModelBucket::CodeGpt5Codex,
-            ModelBucket::ChatGpt51Codex,
-        ],
-    ),
-    ("gpt-5", &[ModelBucket::Gpt5, ModelBucket::Gpt51]),
-    (
-        "gpt-5-codex-mini",
-        &[
-            ModelBucket::Gpt5Mini,
-            ModelBucket::Gpt51CodexMini,
-            ModelBucket::CodeGpt5CodexMini,
-            ModelBucket::CodeGpt5Mini,
-            ModelBucket::ChatGpt51CodexMini,
-        ],
-    ),
-    ("other", &[ModelBucket::Other]),
-];
+/// Like `fmt_tokens` but with at most one decimal and no trailing `.0`, e.g. `1.2M` or `45M`.
+fn fmt_tokens_compact(value: u64) -> String {
+    const SCALES: &[(u64, &str)] = &[(1_000_000_000_000, "T"), (1_000_000_000, "B"), (1_000_000, "M"), (1_000, "K")];
+    for (scale, suffix) in SCALES {
+        if value >= *scale {
+            let scaled = format!("{:.1}", value as f64 / *scale as f64);
+            let scaled = scaled.strip_suffix(".0").unwrap_or(&scaled);
+            return format!("{scaled}{suffix}");
+        }
+    }
+    value.to_string()
+}
 
 trait TakeOverrides {
     fn take(&mut self) -> CliConfigOverrides;
@@ -295,3 +1024,320 @@ impl TakeOverrides for CliConfigOverrides {
         std::mem::take(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn parse_command(args: &[&str]) -> UsageCommand {
+        let mut argv = vec!["usage"];
+        argv.extend_from_slice(args);
+        UsageCommand::try_parse_from(argv).expect("parse usage args")
+    }
+
+    fn corrupt_sessions_dir() -> TempDir {
+        let temp = TempDir::new().expect("tempdir");
+        fs::write(
+            temp.path().join("sess-corrupt.jsonl"),
+            r#"{"type":"session_meta","payload":{"id":"sess-corrupt","model":"gpt-5"}}
+{"type":"event_msg","timestamp":"2025-11-19T00:00:00Z","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":10,"total_tokens":10}}}}
+{not json
+"#,
+        )
+        .expect("write corrupt log");
+        fs::write(
+            temp.path().join("sess-empty.jsonl"),
+            r#"{"type":"session_meta","payload":{"id":"sess-empty","model":"gpt-5"}}
+"#,
+        )
+        .expect("write empty log");
+        temp
+    }
+
+    fn costly_sessions_dir() -> TempDir {
+        let temp = TempDir::new().expect("tempdir");
+        // 2M non-cached gpt-5 input tokens is $2.50.
+        fs::write(
+            temp.path().join("sess-costly.jsonl"),
+            r#"{"type":"session_meta","payload":{"id":"sess-costly","model":"gpt-5"}}
+{"type":"event_msg","timestamp":"2025-11-19T00:00:00Z","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":2000000,"total_tokens":2000000}}}}
+"#,
+        )
+        .expect("write costly log");
+        temp
+    }
+
+    fn scan_options(dir: &TempDir) -> GlobalUsageScanOptions {
+        GlobalUsageScanOptions::new(dir.path().to_path_buf())
+            .with_sessions_override(dir.path().to_path_buf())
+    }
+
+    #[test]
+    fn tail_orders_sessions_by_last_event() {
+        let dir = TempDir::new().expect("tempdir");
+        for (id, last) in [
+            ("sess-old", "2025-11-18T08:00:00Z"),
+            ("sess-newest", "2025-11-19T12:00:00Z"),
+            ("sess-mid", "2025-11-19T06:00:00Z"),
+        ] {
+            fs::write(
+                dir.path().join(format!("{id}.jsonl")),
+                format!(
+                    r#"{{"type":"session_meta","payload":{{"id":"{id}","model":"gpt-5"}}}}
+{{"type":"event_msg","timestamp":"2025-11-18T00:00:00Z","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":10,"total_tokens":10}}}}}}}}
+{{"type":"event_msg","timestamp":"{last}","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":20,"total_tokens":20}}}}}}}}
+"#
+                ),
+            )
+            .expect("write log");
+        }
+
+        let snapshot = scan_global_usage(scan_options(&dir).with_record_sessions(true)).expect("scan");
+        let ids: Vec<&str> = tail_sessions(&snapshot, 2)
+            .iter()
+            .map(|session| session.session_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["sess-newest", "sess-mid"]);
+        assert_eq!(tail_sessions(&snapshot, 10).len(), 3);
+    }
+
+    #[test]
+    fn date_flags_parse_to_an_inclusive_day_window() {
+        let command = parse_command(&["--since", "2025-11-01", "--until", "2025-11-19"]);
+        assert_eq!(command.since, NaiveDate::from_ymd_opt(2025, 11, 1));
+        assert_eq!(command.until, NaiveDate::from_ymd_opt(2025, 11, 19));
+        assert_eq!(start_of_day(command.since.unwrap()).to_rfc3339(), "2025-11-01T00:00:00+00:00");
+        assert!(UsageCommand::try_parse_from(["usage", "--since", "11/01/2025"]).is_err());
+    }
+
+    #[test]
+    fn min_tokens_hides_small_sessions_but_keeps_totals() {
+        let dir = TempDir::new().expect("tempdir");
+        for (id, tokens) in [("sess-big", 50_000), ("sess-tiny", 12), ("sess-large", 8_000)] {
+            fs::write(
+                dir.path().join(format!("{id}.jsonl")),
+                format!(
+                    r#"{{"type":"session_meta","payload":{{"id":"{id}","model":"gpt-5"}}}}
+{{"type":"event_msg","timestamp":"2025-11-19T00:00:00Z","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":{tokens},"total_tokens":{tokens}}}}}}}}}
+"#
+                ),
+            )
+            .expect("write log");
+        }
+        let command = parse_command(&["--verbose", "--min-tokens", "1000"]);
+        assert_eq!(command.min_tokens, Some(1_000));
+
+        let snapshot = scan_global_usage(scan_options(&dir).with_record_sessions(true)).expect("scan");
+        let mut ids: Vec<&str> = sessions_at_least(&snapshot, 1_000)
+            .iter()
+            .map(|session| session.session_id.as_str())
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec!["sess-big", "sess-large"]);
+        assert_eq!(sessions_at_least(&snapshot, 0).len(), 3);
+        assert_eq!(snapshot.totals.total_tokens, 58_012);
+    }
+
+    #[test]
+    fn oneline_summary_formats_day_and_thirty_day_windows() {
+        let mut snapshot = GlobalUsageSnapshot::default();
+        snapshot.trailing.last_day = UsageTotals {
+            total_tokens: 1_234_567,
+            cost_usd: 3.4,
+            ..UsageTotals::default()
+        };
+        snapshot.trailing.last_thirty_days = UsageTotals {
+            total_tokens: 45_000_000,
+            cost_usd: 120.0,
+            ..UsageTotals::default()
+        };
+        assert_eq!(oneline_summary(&snapshot, 2), "today: 1.2M tok $3.40 · 30d: 45M tok $120.00");
+
+        assert_eq!(
+            oneline_summary(&GlobalUsageSnapshot::default(), 2),
+            "today: 0 tok $0.00 · 30d: 0 tok $0.00"
+        );
+        assert!(UsageCommand::try_parse_from(["usage", "--oneline", "--verbose"]).is_err());
+    }
+
+    #[test]
+    fn compare_reports_window_deltas_and_percentages() {
+        let dir = TempDir::new().expect("tempdir");
+        let at = |days: i64| (Utc::now() - Duration::days(days)).to_rfc3339();
+        // 3,000 tokens 20 days ago and 1,000 more 2 days ago: 7d sees 1,000, 30d sees 4,000.
+        fs::write(
+            dir.path().join("sess-trend.jsonl"),
+            format!(
+                r#"{{"type":"session_meta","payload":{{"id":"sess-trend","model":"gpt-5"}}}}
+{{"type":"event_msg","timestamp":"{}","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":3000,"total_tokens":3000}}}}}}}}
+{{"type":"event_msg","timestamp":"{}","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":4000,"total_tokens":4000}}}}}}}}
+"#,
+                at(20),
+                at(2)
+            ),
+        )
+        .expect("write log");
+
+        let command = parse_command(&["--compare", "7d:30d"]);
+        let (first, second) = command.compare.expect("compare windows");
+        assert_eq!((first, second), (Duration::days(7), Duration::days(30)));
+
+        let snapshot =
+            scan_global_usage(scan_options(&dir).with_custom_windows(vec![first, second])).expect("scan");
+        let [week, month] = snapshot.custom_windows.as_slice() else {
+            panic!("expected two windows: {:?}", snapshot.custom_windows);
+        };
+        assert_eq!(week.totals.total_tokens, 1_000);
+        assert_eq!(month.totals.total_tokens, 4_000);
+
+        let delta = window_delta(&week.totals, &month.totals);
+        assert_eq!(delta.tokens, 3_000);
+        assert_eq!(delta.tokens_percent, Some(300.0));
+        assert!((delta.cost_usd - 0.00375).abs() < 1e-9, "{delta:?}");
+        assert!((delta.cost_percent.expect("cost percent") - 300.0).abs() < 1e-6);
+
+        let shrink = window_delta(&month.totals, &week.totals);
+        assert_eq!(shrink.tokens, -3_000);
+        assert_eq!(shrink.tokens_percent, Some(-75.0));
+
+        let empty = window_delta(&UsageTotals::default(), &week.totals);
+        assert_eq!(empty.tokens, 1_000);
+        assert_eq!(empty.tokens_percent, None);
+        assert_eq!(empty.cost_percent, None);
+
+        assert!(UsageCommand::try_parse_from(["usage", "--compare", "7d"]).is_err());
+        assert!(UsageCommand::try_parse_from(["usage", "--compare", "7x:30d"]).is_err());
+        assert_eq!(window_label(Duration::hours(12)), "12h");
+        assert_eq!(window_label(Duration::weeks(2)), "14d");
+    }
+
+    #[test]
+    fn group_by_day_flags_request_day_buckets() {
+        let command = parse_command(&["--group-by", "day", "--days", "90"]);
+        assert_eq!(command.group_by, Some(GroupBy::Day));
+        assert_eq!(command.days, Some(90));
+        assert!(UsageCommand::try_parse_from(["usage", "--days", "90"]).is_err());
+        assert!(UsageCommand::try_parse_from(["usage", "--group-by", "week"]).is_err());
+
+        let dir = costly_sessions_dir();
+        let snapshot = scan_global_usage(scan_options(&dir).with_day_buckets(90)).expect("scan");
+        assert_eq!(snapshot.day_buckets.len(), 90);
+        let mut out = Vec::new();
+        let written = write_bucket_export(&snapshot, &mut out).expect("export");
+        let text = String::from_utf8(out).expect("utf8");
+        assert_eq!(text.matches("\"group_day\"").count(), 90);
+        assert!(written > 90);
+    }
+
+    #[test]
+    fn bucket_export_emits_one_line_per_bucket() {
+        let dir = corrupt_sessions_dir();
+        let snapshot = scan_global_usage(scan_options(&dir)).expect("scan");
+        let expected = snapshot.hourly_buckets.len()
+            + snapshot.twelve_hour_buckets.len()
+            + snapshot.daily_buckets.len()
+            + snapshot.weekly_buckets.len()
+            + snapshot.monthly_buckets.len();
+        assert!(expected > 0);
+
+        let mut out = Vec::new();
+        let written = write_bucket_export(&snapshot, &mut out).expect("export");
+        assert_eq!(written, expected);
+
+        let text = String::from_utf8(out).expect("utf8");
+        let records: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json line"))
+            .collect();
+        assert_eq!(records.len(), expected);
+        for record in &records {
+            for field in ["granularity", "start", "end", "total_tokens", "cost_usd"] {
+                assert!(record.get(field).is_some(), "missing {field} in {record}");
+            }
+        }
+    }
+
+    #[test]
+    fn jsonl_report_emits_independent_typed_lines() {
+        let dir = costly_sessions_dir();
+        let snapshot = scan_global_usage(scan_options(&dir)).expect("scan");
+
+        let mut out = Vec::new();
+        let written = write_jsonl_report(&snapshot, &mut out).expect("jsonl");
+        let text = String::from_utf8(out).expect("utf8");
+        assert_eq!(text.lines().count(), written);
+
+        let mut types = BTreeMap::new();
+        for line in text.lines() {
+            let record: serde_json::Value = serde_json::from_str(line).expect("json line");
+            assert!(record["totals"]["total_tokens"].is_u64(), "missing totals in {record}");
+            let kind = record["type"].as_str().expect("type").to_string();
+            *types.entry(kind).or_insert(0usize) += 1;
+        }
+        assert_eq!(types.get("totals"), Some(&1));
+        assert_eq!(types.get("trailing"), Some(&6));
+        assert_eq!(types.get("model"), Some(&snapshot.model_usage.len()));
+        assert_eq!(types.get("source"), Some(&snapshot.source_usage.len()));
+        assert!(types.get("bucket").is_some_and(|count| *count > 0));
+        assert_eq!(types.len(), 5);
+        assert!(text.lines().next().is_some_and(|line| line.contains("\"type\":\"totals\"")));
+    }
+
+    #[test]
+    fn histogram_bar_widths_scale_to_largest_bucket() {
+        assert_eq!(
+            histogram_bar_widths(&[0, 50, 100, 25, 1], 20),
+            vec![0, 10, 20, 5, 1]
+        );
+        assert_eq!(histogram_bar_widths(&[0, 0, 0], 20), vec![0, 0, 0]);
+        assert!(histogram_bar_widths(&[], 20).is_empty());
+    }
+
+    #[test]
+    fn fail_on_errors_returns_non_zero_for_corrupt_fixture() {
+        let dir = corrupt_sessions_dir();
+        let command = parse_command(&["--fail-on-errors"]);
+        let code = command.scan_and_report(scan_options(&dir)).expect("scan");
+        assert_eq!(code, DATA_QUALITY_EXIT_CODE);
+    }
+
+    #[test]
+    fn corrupt_fixture_exits_zero_without_flag_or_within_threshold() {
+        let dir = corrupt_sessions_dir();
+        let command = parse_command(&[]);
+        assert_eq!(command.scan_and_report(scan_options(&dir)).expect("scan"), 0);
+
+        let command = parse_command(&["--max-errors", "2"]);
+        assert_eq!(command.scan_and_report(scan_options(&dir)).expect("scan"), 0);
+
+        let command = parse_command(&["--max-errors", "1"]);
+        assert_eq!(
+            command.scan_and_report(scan_options(&dir)).expect("scan"),
+            DATA_QUALITY_EXIT_CODE
+        );
+    }
+
+    #[test]
+    fn budget_overrun_warns_and_fails_in_strict_mode() {
+        let dir = costly_sessions_dir();
+        let snapshot = scan_global_usage(scan_options(&dir)).expect("scan");
+        let warning = budget_warning(&snapshot, 1.0, 2).expect("over budget");
+        assert!(warning.contains("$2.50"), "{warning}");
+        assert!(warning.contains("by $1.50"), "{warning}");
+        assert_eq!(budget_warning(&snapshot, 5.0, 2), None);
+
+        let command = parse_command(&["--budget", "1.00"]);
+        assert_eq!(command.scan_and_report(scan_options(&dir)).expect("scan"), 0);
+
+        let command = parse_command(&["--budget", "1.00", "--budget-strict"]);
+        assert_eq!(
+            command.scan_and_report(scan_options(&dir)).expect("scan"),
+            BUDGET_EXCEEDED_EXIT_CODE
+        );
+
+        let command = parse_command(&["--budget", "5.00", "--budget-strict"]);
+        assert_eq!(command.scan_and_report(scan_options(&dir)).expect("scan"), 0);
+    }
+}
@@ -0,0 +1,18 @@
+//! Reads one JSON request per line from stdin and prints the bridge's JSON
+//! response, for debugging requests without the JVM round trip.
+
+use std::io::{self, BufRead, Write};
+
+fn main() -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(out, "{}", codex_core_jni::execute_json(&line))?;
+        out.flush()?;
+    }
+    Ok(())
+}
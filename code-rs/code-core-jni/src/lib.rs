@@ -1,6 +1,8 @@
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
@@ -12,10 +14,15 @@ use code_auto_drive_core::{
 };
 use code_core::agent_defaults::model_guide_markdown_with_custom;
 use code_core::coalesce_snapshot_records;
+use code_core::coalesce_snapshot_records_aggressive;
 use code_core::config::{Config, ConfigOverrides};
 use code_core::debug_logger::DebugLogger;
 use code_core::fork_history_from_response_items;
-use code_core::models::{ContentItem, ResponseItem};
+use code_core::model_family::{find_family_for_model, ModelFamily};
+use code_core::models::{
+    ContentItem, ReasoningItemContent, ReasoningItemReasoningSummary, ResponseItem,
+};
+use code_core::preview_fork_from_response_items;
 use code_core::prune_history_after_dropping_last_user_turns;
 use code_core::retain_api_messages_only;
 use code_core::summarize_snapshot;
@@ -24,7 +31,6 @@ use code_core::AuthManager;
 use code_core::ModelClient;
 use code_core::Prompt;
 use code_core::ResponseEvent;
-use code_core::ResponseStream;
 use code_core::SnapshotRecordPayload;
 use code_core::protocol::TokenUsage;
 use jni::objects::{JClass, JString};
@@ -35,11 +41,19 @@ use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tokio::runtime::Builder as TokioRuntimeBuilder;
+use tokio::sync::Notify;
 use uuid::Uuid;
 
 static CONFIG: Lazy<Mutex<Option<Value>>> = Lazy::new(|| Mutex::new(None));
 static KOTLIN_CONFIG: OnceCell<Arc<Config>> = OnceCell::new();
+static SIMPLE_TURN_CANCELLATIONS: Lazy<Mutex<HashMap<String, Arc<SimpleTurnCancellation>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 const SIMPLE_MODEL_FIXTURE_ENV: &str = "CODE_KOTLIN_SIMPLE_MODEL_FIXTURE";
+/// Originator sent to the provider when the config leaves it blank.
+const FALLBACK_ORIGINATOR: &str = "lightcode-jni";
+/// Largest `execute` request accepted when the initialize config sets no
+/// `max_request_bytes`; larger payloads are rejected before parsing.
+const DEFAULT_MAX_REQUEST_BYTES: usize = 4 * 1024 * 1024;
 
 #[derive(Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -57,6 +71,8 @@ enum ExecuteRequest {
     ConversationFilterPopularCommands(ConversationFilterPopularCommandsRequest),
     AutoCoordinatorPlanningSeed(PlannerSeedRequest),
     SimpleModelTurn(SimpleModelTurnRequest),
+    CancelSimpleTurn { turn_id: String },
+    ValidateConfig { config: Value },
 }
 
 impl From<PhaseInput> for AutoRunPhase {
@@ -95,6 +111,12 @@ struct AutoDriveCountdownTickRequest {
     countdown_id: u64,
     decision_seq: u64,
     seconds_left: u8,
+    /// Host controller's current countdown id; defaults to the tick's id.
+    #[serde(default)]
+    controller_countdown_id: Option<u64>,
+    /// Host controller's current decision seq; defaults to the tick's seq.
+    #[serde(default)]
+    controller_decision_seq: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -125,6 +147,8 @@ struct ConversationFilterHistoryRequest {
 #[derive(Debug, Deserialize)]
 struct ConversationCoalesceSnapshotRequest {
     records: Vec<SnapshotRecordPayload>,
+    #[serde(default)]
+    aggressive: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -136,6 +160,9 @@ struct ConversationSnapshotSummaryRequest {
 struct ConversationForkHistoryRequest {
     history: Vec<ResponseItem>,
     drop_last_user_turns: u32,
+    /// Report what the fork would do without building the retained history.
+    #[serde(default)]
+    dry_run: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -154,12 +181,117 @@ struct SimpleModelTurnRequest {
     history: Vec<Value>,
     #[serde(rename = "latest_user_prompt")]
     latest_user_prompt: Option<String>,
+    /// Host-chosen id that a later `cancel_simple_turn` request can target.
+    #[serde(default)]
+    turn_id: Option<String>,
+    /// Model to use instead of the configured one.
+    #[serde(default)]
+    model: Option<String>,
+    /// Family for the override `model`; derived from the model name when
+    /// absent. Ignored without `model`.
+    #[serde(default)]
+    model_family: Option<String>,
+    /// Return reasoning and answer text as ordered `segments` instead of
+    /// separate `thinking` and `answer` fields.
+    #[serde(default)]
+    preserve_order: bool,
 }
 
 struct SimpleModelTurnResult {
     thinking: Vec<String>,
+    /// Milliseconds from stream start until each `thinking` chunk began
+    /// arriving; `None` for fixture-loaded results.
+    thinking_offsets_ms: Vec<Option<u64>>,
     answer: String,
+    /// Reasoning and answer text in arrival order; only collected when the
+    /// request sets `preserve_order`.
+    segments: Option<Vec<SimpleTurnSegment>>,
     token_usage: Option<TokenUsage>,
+    cancelled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SimpleTurnSegmentKind {
+    Reasoning,
+    Answer,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct SimpleTurnSegment {
+    kind: SimpleTurnSegmentKind,
+    text: String,
+}
+
+/// Appends `text` to the trailing segment when it has the same kind (and no
+/// break was requested), otherwise starts a new segment.
+fn push_segment(
+    segments: &mut Vec<SimpleTurnSegment>,
+    kind: SimpleTurnSegmentKind,
+    text: &str,
+    force_break: bool,
+) {
+    match segments.last_mut() {
+        Some(last) if last.kind == kind && !force_break => last.text.push_str(text),
+        _ => segments.push(SimpleTurnSegment {
+            kind,
+            text: text.to_string(),
+        }),
+    }
+}
+
+/// Cancellation signal shared between an in-flight simple turn and the
+/// `cancel_simple_turn` request that targets it.
+#[derive(Default)]
+struct SimpleTurnCancellation {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl SimpleTurnCancellation {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        // `notify_one` stores a permit, so a collector that is between polls
+        // still observes the cancellation on its next wait.
+        self.notify.notify_one();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Keeps a turn's cancellation registered for as long as the turn runs.
+struct SimpleTurnRegistration {
+    turn_id: String,
+    cancellation: Arc<SimpleTurnCancellation>,
+}
+
+impl SimpleTurnRegistration {
+    fn register(turn_id: String) -> Self {
+        let cancellation = Arc::new(SimpleTurnCancellation::default());
+        if let Ok(mut registry) = SIMPLE_TURN_CANCELLATIONS.lock() {
+            registry.insert(turn_id.clone(), Arc::clone(&cancellation));
+        }
+        Self {
+            turn_id,
+            cancellation,
+        }
+    }
+}
+
+impl Drop for SimpleTurnRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut registry) = SIMPLE_TURN_CANCELLATIONS.lock() {
+            // Only remove our own entry; a newer turn may have reused the id.
+            if registry
+                .get(&self.turn_id)
+                .is_some_and(|entry| Arc::ptr_eq(entry, &self.cancellation))
+            {
+                registry.remove(&self.turn_id);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -362,11 +494,16 @@ fn shutdown_impl() -> Result<(), String> {
 }
 
 fn execute_impl(env: &mut JNIEnv, request_json: JString) -> Result<jstring, String> {
-    let request_str = get_string(env, request_json)?;
-    let req: ExecuteRequest = serde_json::from_str(&request_str)
-        .map_err(|e| format!("{} in payload {}", e, request_str))?;
-    let response = handle_request(req);
-    let response_str = serde_json::to_string(&response).map_err(|e| e.to_string())?;
+    // Every UTF-16 unit takes at least one UTF-8 byte, so an over-long string is
+    // rejected here without copying it out of the JVM.
+    let limit = max_request_bytes();
+    let units = java_string_units(env, &request_json)?;
+    let response_str = if units > limit {
+        request_too_large(units, limit).to_string()
+    } else {
+        let request_str = get_string(env, request_json)?;
+        try_execute_json(&request_str)?
+    };
     let output = env
         .new_string(response_str)
         .map_err(|e| e.to_string())?
@@ -374,6 +511,90 @@ fn execute_impl(env: &mut JNIEnv, request_json: JString) -> Result<jstring, Stri
     Ok(output)
 }
 
+fn try_execute_json(request_str: &str) -> Result<String, String> {
+    let limit = max_request_bytes();
+    if request_str.len() > limit {
+        return Ok(request_too_large(request_str.len(), limit).to_string());
+    }
+    let req: ExecuteRequest = serde_json::from_str(request_str)
+        .map_err(|e| format!("{} in payload {}", e, request_str))?;
+    let response = handle_request(req);
+    serde_json::to_string(&response).map_err(|e| e.to_string())
+}
+
+/// Request size limit: `max_request_bytes` from the initialize config when it
+/// is a positive integer, otherwise [`DEFAULT_MAX_REQUEST_BYTES`].
+fn max_request_bytes() -> usize {
+    CONFIG
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref()?.get("max_request_bytes")?.as_u64())
+        .and_then(|limit| usize::try_from(limit).ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(DEFAULT_MAX_REQUEST_BYTES)
+}
+
+fn request_too_large(size: usize, limit: usize) -> Value {
+    json!({
+        "status": "error",
+        "code": "request_too_large",
+        "message": format!("request of {size} bytes exceeds the {limit}-byte limit"),
+        "limit_bytes": limit,
+    })
+}
+
+/// Keys an initialize config must carry as non-empty strings.
+const REQUIRED_CONFIG_KEYS: [&str; 2] = ["model", "model_provider"];
+
+/// Checks `config` against the shape `initialize` expects, returning one
+/// message per problem found.
+fn config_errors(config: &Value) -> Vec<String> {
+    let Some(object) = config.as_object() else {
+        return vec!["config must be a JSON object".to_string()];
+    };
+    let mut errors = Vec::new();
+    for key in REQUIRED_CONFIG_KEYS {
+        match object.get(key) {
+            None => errors.push(format!("missing required field `{key}`")),
+            Some(Value::String(value)) if !value.trim().is_empty() => {}
+            Some(Value::String(_)) => errors.push(format!("field `{key}` must not be empty")),
+            Some(_) => errors.push(format!("field `{key}` must be a string")),
+        }
+    }
+    if let Some(limit) = object.get("max_request_bytes")
+        && limit.as_u64().is_none_or(|limit| limit == 0)
+    {
+        errors.push("field `max_request_bytes` must be a positive integer".to_string());
+    }
+    errors
+}
+
+/// Pre-flight for `initialize`: reports whether `config` would be accepted
+/// without storing it.
+fn handle_validate_config(config: &Value) -> Value {
+    let errors = config_errors(config);
+    json!({
+        "status": "ok",
+        "kind": "validate_config",
+        "valid": errors.is_empty(),
+        "errors": errors,
+    })
+}
+
+/// Runs a JSON request through the same dispatch as the JNI `execute` entry
+/// point, without a JVM. Failures come back as the same `status: error`
+/// payload the bridge returns to Kotlin.
+#[cfg(feature = "debug-repl")]
+pub fn execute_json(request: &str) -> String {
+    try_execute_json(request).unwrap_or_else(|err| {
+        json!({
+            "status": "error",
+            "message": err,
+        })
+        .to_string()
+    })
+}
+
 fn handle_request(request: ExecuteRequest) -> Value {
     match request {
         ExecuteRequest::Echo { payload } => json!({
@@ -426,14 +647,22 @@ fn handle_request(request: ExecuteRequest) -> Value {
             handle_planner_seed_request(req)
         }
         ExecuteRequest::SimpleModelTurn(req) => handle_simple_model_turn(req),
+        ExecuteRequest::CancelSimpleTurn { turn_id } => handle_cancel_simple_turn(&turn_id),
+        ExecuteRequest::ValidateConfig { config } => handle_validate_config(&config),
     }
 }
 
 fn handle_auto_drive_countdown_tick(req: AutoDriveCountdownTickRequest) -> Value {
     let mut controller = AutoDriveController::default();
     controller.phase = req.phase.into();
-    controller.countdown_id = req.countdown_id;
-    controller.countdown_decision_seq = req.decision_seq;
+    controller.countdown_id = req.controller_countdown_id.unwrap_or(req.countdown_id);
+    controller.countdown_decision_seq = req.controller_decision_seq.unwrap_or(req.decision_seq);
+
+    let stale = req.countdown_id != controller.countdown_id
+        || req.decision_seq != controller.countdown_decision_seq;
+    let wrong_phase = !controller.phase.is_active()
+        || !controller.phase.awaiting_coordinator_submit()
+        || controller.phase.is_paused_manual();
 
     let effects = controller.handle_countdown_tick(
         req.countdown_id,
@@ -441,12 +670,24 @@ fn handle_auto_drive_countdown_tick(req: AutoDriveCountdownTickRequest) -> Value
         req.seconds_left,
     );
 
-    json!({
+    let mut response = json!({
         "status": "ok",
         "kind": "auto_drive_countdown_tick",
         "effects": effects.iter().map(effect_to_json).collect::<Vec<_>>(),
         "seconds_left": controller.seconds_remaining,
-    })
+        "matched": !stale && !wrong_phase,
+    });
+    let ignored_reason = if stale {
+        Some("stale_countdown")
+    } else if wrong_phase {
+        Some("wrong_phase")
+    } else {
+        None
+    };
+    if let Some(reason) = ignored_reason {
+        response["ignored_reason"] = json!(reason);
+    }
+    response
 }
 
 fn handle_auto_drive_update_continue_mode(req: AutoDriveUpdateContinueModeRequest) -> Value {
@@ -554,13 +795,18 @@ fn handle_conversation_filter_history(req: ConversationFilterHistoryRequest) ->
 }
 
 fn handle_conversation_coalesce_snapshot(req: ConversationCoalesceSnapshotRequest) -> Value {
-    let outcome = coalesce_snapshot_records(req.records);
+    let outcome = if req.aggressive {
+        coalesce_snapshot_records_aggressive(req.records)
+    } else {
+        coalesce_snapshot_records(req.records)
+    };
 
     json!({
         "status": "ok",
         "kind": "conversation_coalesce_snapshot",
         "records": outcome.records,
         "removed_count": outcome.removed_count,
+        "merged_count": outcome.merged_count,
     })
 }
 
@@ -573,10 +819,23 @@ fn handle_conversation_snapshot_summary(req: ConversationSnapshotSummaryRequest)
         "record_count": summary.record_count,
         "assistant_messages": summary.assistant_messages,
         "user_messages": summary.user_messages,
+        "image_items": summary.image_items,
     })
 }
 
 fn handle_conversation_fork_history(req: ConversationForkHistoryRequest) -> Value {
+    if req.dry_run {
+        let preview =
+            preview_fork_from_response_items(&req.history, req.drop_last_user_turns as usize);
+        return json!({
+            "status": "ok",
+            "kind": "conversation_fork_history",
+            "dry_run": true,
+            "would_drop_user_turns": preview.would_drop_user_turns,
+            "would_become_new": preview.would_become_new,
+            "resulting_len": preview.resulting_len,
+        });
+    }
     let outcome = fork_history_from_response_items(req.history, req.drop_last_user_turns as usize);
 
     json!({
@@ -606,7 +865,7 @@ fn handle_planner_seed_request(req: PlannerSeedRequest) -> Value {
                 AutoTurnAgentsTiming::Blocking => "Blocking",
             });
 
-            json!({
+            let mut response = json!({
                 "status": "ok",
                 "kind": "auto_coordinator_planning_seed",
                 "response_json": seed.response_json,
@@ -614,8 +873,14 @@ fn handle_planner_seed_request(req: PlannerSeedRequest) -> Value {
                 "goal_message": seed.goal_message,
                 "status_title": seed.status_title,
                 "status_sent_to_user": seed.status_sent_to_user,
-                "agents_timing": agents_timing,
-            })
+                "has_agents": agents_timing.is_some(),
+            });
+            // Only report timing when agents were requested; a null value then
+            // means "requested but none planned" rather than "not asked".
+            if req.include_agents {
+                response["agents_timing"] = json!(agents_timing);
+            }
+            response
         }
         None => json!({
             "status": "ok",
@@ -626,18 +891,34 @@ fn handle_planner_seed_request(req: PlannerSeedRequest) -> Value {
 }
 
 fn handle_simple_model_turn(req: SimpleModelTurnRequest) -> Value {
-    if let Some(path) = std::env::var_os(SIMPLE_MODEL_FIXTURE_ENV) {
-        let fixture_path = PathBuf::from(path);
-        match load_simple_model_fixture(&fixture_path) {
-            Ok(result) => {
+    if let Err((index, role)) = validate_history_roles(&req.history) {
+        return json!({
+            "status": "error",
+            "kind": "simple_model_turn",
+            "code": "invalid_history_role",
+            "message": format!("history entry {index} has unsupported message role {role:?}"),
+        });
+    }
+
+    let model_override = match req.model.as_deref() {
+        Some(model) => match resolve_model_override(model, req.model_family.as_deref()) {
+            Ok(family) => Some(family),
+            Err(message) => {
                 return json!({
-                    "status": "ok",
+                    "status": "error",
                     "kind": "simple_model_turn",
-                    "thinking": result.thinking,
-                    "answer": result.answer,
-                    "token_usage": result.token_usage,
+                    "code": "unknown_model_family",
+                    "message": message,
                 });
             }
+        },
+        None => None,
+    };
+
+    if let Some(path) = std::env::var_os(SIMPLE_MODEL_FIXTURE_ENV) {
+        let fixture_path = PathBuf::from(path);
+        match load_simple_model_fixture(&fixture_path, req.preserve_order) {
+            Ok(result) => return simple_model_turn_json(result),
             Err(err) => {
                 return json!({
                     "status": "error",
@@ -648,14 +929,8 @@ fn handle_simple_model_turn(req: SimpleModelTurnRequest) -> Value {
         }
     }
 
-    match run_simple_model_turn(req) {
-        Ok(result) => json!({
-            "status": "ok",
-            "kind": "simple_model_turn",
-            "thinking": result.thinking,
-            "answer": result.answer,
-            "token_usage": result.token_usage,
-        }),
+    match run_simple_model_turn(req, model_override) {
+        Ok(result) => simple_model_turn_json(result),
         Err(err) => json!({
             "status": "error",
             "kind": "simple_model_turn",
@@ -664,7 +939,78 @@ fn handle_simple_model_turn(req: SimpleModelTurnRequest) -> Value {
     }
 }
 
-fn run_simple_model_turn(req: SimpleModelTurnRequest) -> Result<SimpleModelTurnResult, String> {
+fn simple_model_turn_json(result: SimpleModelTurnResult) -> Value {
+    let status = if result.cancelled { "cancelled" } else { "ok" };
+    let hints = budget_hints(result.token_usage.as_ref());
+    match result.segments {
+        Some(segments) => json!({
+            "status": status,
+            "kind": "simple_model_turn",
+            "segments": segments,
+            "budget_hints": hints,
+            "token_usage": result.token_usage,
+        }),
+        None => json!({
+            "status": status,
+            "kind": "simple_model_turn",
+            "thinking": result.thinking,
+            "thinking_offsets_ms": result.thinking_offsets_ms,
+            "answer": result.answer,
+            "budget_hints": hints,
+            "token_usage": result.token_usage,
+        }),
+    }
+}
+
+/// Share of output tokens spent on reasoning above which a turn suggests lowering effort.
+const REASONING_SHARE_HINT_THRESHOLD: f64 = 0.8;
+
+/// Reasoning tokens in a single turn above which a turn suggests lowering effort.
+const REASONING_TOKENS_HINT_THRESHOLD: u64 = 32_000;
+
+/// Human-readable nudges derived from a turn's usage, e.g. when reasoning
+/// dominates the output and a lower reasoning effort would be cheaper.
+fn budget_hints(usage: Option<&TokenUsage>) -> Vec<String> {
+    let Some(usage) = usage else {
+        return Vec::new();
+    };
+    let mut hints = Vec::new();
+    let reasoning = usage.reasoning_output_tokens;
+    if usage.output_tokens > 0
+        && reasoning as f64 > usage.output_tokens as f64 * REASONING_SHARE_HINT_THRESHOLD
+    {
+        let percent = (REASONING_SHARE_HINT_THRESHOLD * 100.0).round();
+        hints.push(format!("reasoning tokens exceeded {percent}% of output"));
+    }
+    if reasoning > REASONING_TOKENS_HINT_THRESHOLD {
+        hints.push(format!(
+            "reasoning used {reasoning} tokens (over {REASONING_TOKENS_HINT_THRESHOLD}); consider a lower reasoning effort"
+        ));
+    }
+    hints
+}
+
+fn handle_cancel_simple_turn(turn_id: &str) -> Value {
+    let cancellation = SIMPLE_TURN_CANCELLATIONS
+        .lock()
+        .ok()
+        .and_then(|registry| registry.get(turn_id).cloned());
+    let found = cancellation.is_some();
+    if let Some(cancellation) = cancellation {
+        cancellation.cancel();
+    }
+    json!({
+        "status": "ok",
+        "kind": "cancel_simple_turn",
+        "turn_id": turn_id,
+        "found": found,
+    })
+}
+
+fn run_simple_model_turn(
+    req: SimpleModelTurnRequest,
+    model_override: Option<ModelFamily>,
+) -> Result<SimpleModelTurnResult, String> {
     let config = load_kotlin_config()?;
 
     let prompt_text = req
@@ -672,7 +1018,9 @@ fn run_simple_model_turn(req: SimpleModelTurnRequest) -> Result<SimpleModelTurnR
         .or_else(|| latest_user_prompt_from_history(&req.history))
         .ok_or_else(|| "latest_user_prompt_required".to_string())?;
 
-    let prompt = build_simple_prompt(&config, prompt_text.clone());
+    let prompt = build_simple_prompt(&config, prompt_text.clone(), model_override);
+    let preserve_order = req.preserve_order;
+    let registration = req.turn_id.map(SimpleTurnRegistration::register);
     let runtime = TokioRuntimeBuilder::new_current_thread()
         .enable_all()
         .build()
@@ -681,7 +1029,8 @@ fn run_simple_model_turn(req: SimpleModelTurnRequest) -> Result<SimpleModelTurnR
     runtime.block_on(async move {
         let client = build_model_client(config.clone())?;
         let stream = client.stream(&prompt).await.map_err(|err| err.to_string())?;
-        collect_simple_model_stream(stream).await
+        let cancellation = registration.as_ref().map(|entry| entry.cancellation.as_ref());
+        collect_simple_model_stream(stream, cancellation, preserve_order).await
     })
 }
 
@@ -704,7 +1053,7 @@ fn build_model_client(config: Arc<Config>) -> Result<ModelClient, String> {
     let auth_manager = AuthManager::shared_with_mode_and_originator(
         config.code_home.clone(),
         preferred_auth,
-        config.responses_originator_header.clone(),
+        effective_originator(&config.responses_originator_header),
     );
     let logger = DebugLogger::new(config.debug)
         .or_else(|_| DebugLogger::new(false))
@@ -723,9 +1072,31 @@ fn build_model_client(config: Arc<Config>) -> Result<ModelClient, String> {
     ))
 }
 
+/// The configured originator header, or [`FALLBACK_ORIGINATOR`] when it is
+/// blank, since providers may reject requests without one.
+fn effective_originator(configured: &str) -> String {
+    if configured.trim().is_empty() {
+        tracing::debug!("responses originator header is blank; using {FALLBACK_ORIGINATOR}");
+        return FALLBACK_ORIGINATOR.to_string();
+    }
+    configured.to_string()
+}
+
+/// Resolves the family for a request-supplied model. The configured family
+/// belongs to the configured model, so reusing it for an override can make the
+/// provider reject the request. An explicit `family` wins over the model name.
+fn resolve_model_override(model: &str, family: Option<&str>) -> Result<ModelFamily, String> {
+    let lookup = family.unwrap_or(model);
+    let mut resolved = find_family_for_model(lookup)
+        .ok_or_else(|| format!("cannot resolve a model family for {lookup:?}"))?;
+    resolved.slug = model.to_string();
+    Ok(resolved)
+}
+
 fn build_simple_prompt(
     config: &Arc<Config>,
     latest_user_prompt: String,
+    model_override: Option<ModelFamily>,
 ) -> Prompt {
     let mut prompt = Prompt::default();
     prompt.input = vec![ResponseItem::Message {
@@ -739,13 +1110,39 @@ fn build_simple_prompt(
     prompt.user_instructions = config.user_instructions.clone();
     prompt.base_instructions_override = config.base_instructions.clone();
     prompt.include_additional_instructions = true;
-    prompt.model_override = Some(config.model.clone());
-    prompt.model_family_override = Some(config.model_family.clone());
+    match model_override {
+        Some(family) => {
+            prompt.model_override = Some(family.slug.clone());
+            prompt.model_family_override = Some(family);
+        }
+        None => {
+            prompt.model_override = Some(config.model.clone());
+            prompt.model_family_override = Some(config.model_family.clone());
+        }
+    }
     prompt.model_descriptions = model_guide_markdown_with_custom(&config.agents);
 
     prompt
 }
 
+const SUPPORTED_HISTORY_ROLES: &[&str] = &["user", "assistant", "system", "developer"];
+
+/// Rejects `message` entries whose role the model API would not accept, so
+/// malformed host data is reported instead of silently skipped when looking
+/// for the latest user prompt. Returns the offending entry index and role.
+fn validate_history_roles(history: &[Value]) -> Result<(), (usize, String)> {
+    for (index, entry) in history.iter().enumerate() {
+        if entry.get("type").and_then(Value::as_str) != Some("message") {
+            continue;
+        }
+        let role = entry.get("role").and_then(Value::as_str).unwrap_or_default();
+        if !SUPPORTED_HISTORY_ROLES.contains(&role) {
+            return Err((index, role.to_string()));
+        }
+    }
+    Ok(())
+}
+
 fn latest_user_prompt_from_history(history: &[Value]) -> Option<String> {
     history.iter().rev().find_map(|entry| {
         let obj = entry.as_object()?;
@@ -765,40 +1162,126 @@ fn latest_user_prompt_from_history(history: &[Value]) -> Option<String> {
     })
 }
 
-async fn collect_simple_model_stream(
-    mut stream: ResponseStream,
-) -> Result<SimpleModelTurnResult, String> {
+async fn collect_simple_model_stream<S, E>(
+    mut stream: S,
+    cancellation: Option<&SimpleTurnCancellation>,
+    preserve_order: bool,
+) -> Result<SimpleModelTurnResult, String>
+where
+    S: futures::Stream<Item = Result<ResponseEvent, E>> + Unpin,
+    E: std::fmt::Display,
+{
+    let started = Instant::now();
+    let elapsed_ms = || u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
     let mut thinking_chunks: Vec<String> = Vec::new();
+    let mut thinking_offsets_ms: Vec<Option<u64>> = Vec::new();
     let mut current_thinking = String::new();
+    // Arrival offset of the first delta in `current_thinking`.
+    let mut current_thinking_offset: Option<u64> = None;
+    fn flush_thinking(
+        current: &mut String,
+        offset: &mut Option<u64>,
+        chunks: &mut Vec<String>,
+        offsets: &mut Vec<Option<u64>>,
+    ) {
+        if !current.trim().is_empty() {
+            chunks.push(current.trim().to_string());
+            offsets.push(*offset);
+        }
+        current.clear();
+        *offset = None;
+    }
     let mut answer_chunks: Vec<String> = Vec::new();
+    let mut segments: Vec<SimpleTurnSegment> = Vec::new();
+    // Set when a new reasoning summary part starts, so its text is not merged
+    // into the previous reasoning segment.
+    let mut reasoning_break = false;
 
     let mut token_usage: Option<TokenUsage> = None;
+    let mut cancelled = false;
 
-    while let Some(event) = stream.next().await {
+    loop {
+        if cancellation.is_some_and(SimpleTurnCancellation::is_cancelled) {
+            cancelled = true;
+            break;
+        }
+        let next = tokio::select! {
+            biased;
+            _ = async {
+                match cancellation {
+                    Some(cancellation) => cancellation.notify.notified().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                cancelled = true;
+                break;
+            }
+            next = stream.next() => next,
+        };
+        let Some(event) = next else {
+            break;
+        };
         let event = event.map_err(|err| err.to_string())?;
         match event {
             ResponseEvent::ReasoningSummaryDelta { delta, .. }
             | ResponseEvent::ReasoningContentDelta { delta, .. } => {
+                if preserve_order {
+                    push_segment(&mut segments, SimpleTurnSegmentKind::Reasoning, &delta, reasoning_break);
+                    reasoning_break = false;
+                }
+                if current_thinking_offset.is_none() {
+                    current_thinking_offset = Some(elapsed_ms());
+                }
                 current_thinking.push_str(&delta);
             }
             ResponseEvent::ReasoningSummaryPartAdded => {
-                if !current_thinking.trim().is_empty() {
-                    thinking_chunks.push(current_thinking.trim().to_string());
-                }
-                current_thinking.clear();
+                flush_thinking(
+                    &mut current_thinking,
+                    &mut current_thinking_offset,
+                    &mut thinking_chunks,
+                    &mut thinking_offsets_ms,
+                );
+                reasoning_break = true;
             }
             ResponseEvent::OutputTextDelta { delta, .. } => {
+                if preserve_order {
+                    push_segment(&mut segments, SimpleTurnSegmentKind::Answer, &delta, false);
+                }
                 answer_chunks.push(delta);
             }
-            ResponseEvent::OutputItemDone { item, .. } => {
-                if let ResponseItem::Message { content, .. } = item {
+            ResponseEvent::OutputItemDone { item, .. } => match item {
+                ResponseItem::Message { content, .. } => {
                     for piece in content {
                         if let ContentItem::OutputText { text } = piece {
+                            if preserve_order {
+                                push_segment(&mut segments, SimpleTurnSegmentKind::Answer, &text, false);
+                            }
                             answer_chunks.push(text);
                         }
                     }
                 }
-            }
+                ResponseItem::Reasoning { summary, content, .. } => {
+                    flush_thinking(
+                        &mut current_thinking,
+                        &mut current_thinking_offset,
+                        &mut thinking_chunks,
+                        &mut thinking_offsets_ms,
+                    );
+                    // The final item repeats any text already streamed as deltas.
+                    for text in reasoning_item_texts(summary, content) {
+                        let text = text.trim();
+                        if !text.is_empty() && !thinking_chunks.iter().any(|chunk| chunk == text) {
+                            thinking_chunks.push(text.to_string());
+                            thinking_offsets_ms.push(Some(elapsed_ms()));
+                            if preserve_order {
+                                push_segment(&mut segments, SimpleTurnSegmentKind::Reasoning, text, true);
+                            }
+                        }
+                    }
+                    reasoning_break = true;
+                }
+                _ => {}
+            },
             ResponseEvent::Completed { token_usage: usage, .. } => {
                 token_usage = usage;
                 break;
@@ -807,34 +1290,95 @@ async fn collect_simple_model_stream(
         }
     }
 
-    if !current_thinking.trim().is_empty() {
-        thinking_chunks.push(current_thinking.trim().to_string());
-    }
+    flush_thinking(
+        &mut current_thinking,
+        &mut current_thinking_offset,
+        &mut thinking_chunks,
+        &mut thinking_offsets_ms,
+    );
 
     let answer = answer_chunks.join("").trim().to_string();
-    if answer.is_empty() {
+    if answer.is_empty() && !cancelled {
         return Err("model_returned_empty_answer".to_string());
     }
 
+    let segments = preserve_order.then(|| {
+        segments
+            .into_iter()
+            .filter_map(|segment| {
+                let text = segment.text.trim();
+                (!text.is_empty()).then(|| SimpleTurnSegment {
+                    kind: segment.kind,
+                    text: text.to_string(),
+                })
+            })
+            .collect()
+    });
+
     Ok(SimpleModelTurnResult {
         thinking: thinking_chunks,
+        thinking_offsets_ms,
         answer,
+        segments,
         token_usage,
+        cancelled,
     })
 }
 
-fn load_simple_model_fixture(path: &Path) -> Result<SimpleModelTurnResult, String> {
+fn reasoning_item_texts(
+    summary: Vec<ReasoningItemReasoningSummary>,
+    content: Option<Vec<ReasoningItemContent>>,
+) -> Vec<String> {
+    let summary_texts = summary.into_iter().map(|part| match part {
+        ReasoningItemReasoningSummary::SummaryText { text } => text,
+    });
+    let content_texts = content.unwrap_or_default().into_iter().map(|part| match part {
+        ReasoningItemContent::ReasoningText { text } | ReasoningItemContent::Text { text } => text,
+    });
+    summary_texts.chain(content_texts).collect()
+}
+
+fn load_simple_model_fixture(
+    path: &Path,
+    preserve_order: bool,
+) -> Result<SimpleModelTurnResult, String> {
     let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
     let fixture: SimpleModelTurnFixture = serde_json::from_str(&contents).map_err(|err| err.to_string())?;
-    Ok(SimpleModelTurnResult {
-        thinking: fixture
-            .thinking
-            .into_iter()
-            .filter(|chunk| !chunk.trim().is_empty())
-            .collect(),
+    Ok(simple_model_fixture_result(fixture, preserve_order))
+}
+
+/// Turn result for a canned fixture; fixtures carry no arrival times, so every
+/// thinking offset is `None`. With `preserve_order`, each thinking chunk
+/// becomes a reasoning segment followed by the answer.
+fn simple_model_fixture_result(
+    fixture: SimpleModelTurnFixture,
+    preserve_order: bool,
+) -> SimpleModelTurnResult {
+    let thinking: Vec<String> = fixture
+        .thinking
+        .into_iter()
+        .filter(|chunk| !chunk.trim().is_empty())
+        .collect();
+    let segments = preserve_order.then(|| {
+        let reasoning = thinking.iter().map(|chunk| SimpleTurnSegment {
+            kind: SimpleTurnSegmentKind::Reasoning,
+            text: chunk.trim().to_string(),
+        });
+        let answer = fixture.answer.trim();
+        let answer = (!answer.is_empty()).then(|| SimpleTurnSegment {
+            kind: SimpleTurnSegmentKind::Answer,
+            text: answer.to_string(),
+        });
+        reasoning.chain(answer).collect()
+    });
+    SimpleModelTurnResult {
+        thinking_offsets_ms: vec![None; thinking.len()],
+        thinking,
         answer: fixture.answer,
+        segments,
         token_usage: None,
-    })
+        cancelled: false,
+    }
 }
 
 fn effect_to_json(effect: &AutoControllerEffect) -> Value {
@@ -903,17 +1447,579 @@ fn effect_to_json(effect: &AutoControllerEffect) -> Value {
     }
 }
 
+/// Length of a Java string in UTF-16 code units, read without copying it.
+fn java_string_units(env: &mut JNIEnv, input: &JString) -> Result<usize, String> {
+    let raw_env = env.get_raw();
+    let raw_str = input.as_raw();
+    if raw_env.is_null() || raw_str.is_null() {
+        return Err("null string passed to bridge".to_string());
+    }
+    // SAFETY: `raw_env` is the env for the current native call and `raw_str` is a
+    // live, non-null local reference.
+    let len = unsafe {
+        let get_length = (**raw_env)
+            .GetStringLength
+            .ok_or_else(|| "JNI GetStringLength unavailable".to_string())?;
+        get_length(raw_env, raw_str)
+    };
+    Ok(usize::try_from(len).unwrap_or_default())
+}
+
+/// Read a Java string through its UTF-16 code units so supplementary characters
+/// (surrogate pairs) survive intact instead of going through modified UTF-8.
 fn get_string(env: &mut JNIEnv, input: JString) -> Result<String, String> {
-    env.get_string(&input)
-        .map_err(|e| e.to_string())
-        .map(|s| s.to_string_lossy().into_owned())
+    let raw_env = env.get_raw();
+    let raw_str = input.as_raw();
+    if raw_env.is_null() || raw_str.is_null() {
+        return Err("null string passed to bridge".to_string());
+    }
+    // SAFETY: `raw_env` is the env for the current native call and `raw_str` is a
+    // live, non-null local reference; the buffer is sized to the string length.
+    let units = unsafe {
+        let functions = &**raw_env;
+        let get_length = functions
+            .GetStringLength
+            .ok_or_else(|| "JNI GetStringLength unavailable".to_string())?;
+        let get_region = functions
+            .GetStringRegion
+            .ok_or_else(|| "JNI GetStringRegion unavailable".to_string())?;
+        let len = get_length(raw_env, raw_str);
+        let mut units = vec![0u16; usize::try_from(len).unwrap_or_default()];
+        get_region(raw_env, raw_str, 0, len, units.as_mut_ptr());
+        units
+    };
+    if env.exception_check().map_err(|e| e.to_string())? {
+        let _ = env.exception_clear();
+        return Err("failed to read Java string".to_string());
+    }
+    decode_java_utf16(&units)
+}
+
+fn decode_java_utf16(units: &[u16]) -> Result<String, String> {
+    String::from_utf16(units).map_err(|e| format!("encoding error: {e}"))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{handle_request, ExecuteRequest};
+    use super::{
+        collect_simple_model_stream, decode_java_utf16, effective_originator, handle_request,
+        resolve_model_override, simple_model_fixture_result, simple_model_turn_json,
+        try_execute_json, ExecuteRequest, SimpleModelTurnFixture, SimpleModelTurnResult,
+        SimpleTurnRegistration, CONFIG, DEFAULT_MAX_REQUEST_BYTES, FALLBACK_ORIGINATOR,
+    };
+    use code_core::models::{ContentItem, ReasoningItemReasoningSummary, ResponseItem};
+    use code_core::protocol::TokenUsage;
+    use code_core::ResponseEvent;
     use serde_json::json;
 
+    #[test]
+    fn planner_seed_reports_agent_fields_per_include_flag() {
+        let run = |include_agents: bool| {
+            let request: ExecuteRequest = serde_json::from_value(json!({
+                "type": "auto_coordinator_planning_seed",
+                "goal_text": "ship the release",
+                "include_agents": include_agents,
+            }))
+            .expect("request to parse");
+            handle_request(request)
+        };
+
+        let with_agents = run(true);
+        assert_eq!(with_agents["status"], "ok");
+        assert_eq!(with_agents["has_agents"], true);
+        assert_eq!(with_agents["agents_timing"], "Parallel");
+
+        let without_agents = run(false);
+        assert_eq!(without_agents["has_agents"], false);
+        assert!(without_agents.get("agents_timing").is_none());
+    }
+
+    #[test]
+    fn aggressive_coalesce_merges_neighbors() {
+        let records = json!([
+            { "kind": "user", "stream_id": null, "markdown": "first question" },
+            { "kind": "user", "stream_id": null, "markdown": "follow-up" },
+            { "kind": "assistant", "stream_id": "s1", "markdown": "answer" },
+            { "kind": "assistant", "stream_id": "s1", "markdown": "answer" },
+            { "kind": "system", "stream_id": null, "markdown": "  " },
+            { "kind": "other", "stream_id": null, "markdown": null },
+            { "kind": "assistant", "stream_id": "s2", "markdown": "more" },
+            { "kind": "assistant", "stream_id": "s3", "markdown": "other stream" },
+            { "kind": "system", "stream_id": null, "markdown": "note a" },
+            { "kind": "system", "stream_id": null, "markdown": "note b" },
+        ]);
+        let run = |aggressive: bool| {
+            let request: ExecuteRequest = serde_json::from_value(json!({
+                "type": "conversation_coalesce_snapshot",
+                "records": records.clone(),
+                "aggressive": aggressive,
+            }))
+            .expect("request to parse");
+            handle_request(request)
+        };
+
+        let default = run(false);
+        assert_eq!(default["removed_count"], 1);
+        assert_eq!(default["merged_count"], 0);
+        assert_eq!(default["records"].as_array().unwrap().len(), 9);
+
+        let aggressive = run(true);
+        assert_eq!(aggressive["removed_count"], 1);
+        assert_eq!(aggressive["merged_count"], 2);
+        let merged = aggressive["records"].as_array().unwrap();
+        assert_eq!(merged.len(), 7);
+        assert_eq!(merged[0]["markdown"], "first question\n\nfollow-up");
+        // Assistant records from different streams and system records stay apart.
+        assert_eq!(merged[3]["stream_id"], "s2");
+        assert_eq!(merged[3]["markdown"], "more");
+        assert_eq!(merged[4]["stream_id"], "s3");
+        assert_eq!(merged[5]["markdown"], "note a");
+        assert_eq!(merged[6]["markdown"], "note b");
+    }
+
+    #[test]
+    fn fork_dry_run_matches_real_fork_outcome() {
+        let message = |role: &str, text: &str| {
+            json!({
+                "type": "message",
+                "role": role,
+                "content": [{ "type": "input_text", "text": text }],
+            })
+        };
+        let history = json!([
+            message("user", "first"),
+            message("assistant", "one"),
+            message("user", "second"),
+            message("assistant", "two"),
+            message("user", "third"),
+            message("assistant", "three"),
+        ]);
+        let run = |drop: u32, dry_run: bool| {
+            let request: ExecuteRequest = serde_json::from_value(json!({
+                "type": "conversation_fork_history",
+                "history": history.clone(),
+                "drop_last_user_turns": drop,
+                "dry_run": dry_run,
+            }))
+            .expect("request to parse");
+            handle_request(request)
+        };
+
+        for drop in 0..=4 {
+            let preview = run(drop, true);
+            let fork = run(drop, false);
+            assert_eq!(preview["status"], "ok");
+            assert!(preview.get("history").is_none(), "dry run must not return history");
+            assert_eq!(preview["would_drop_user_turns"], fork["dropped_user_turns"], "drop {drop}");
+            assert_eq!(preview["would_become_new"], fork["became_new"], "drop {drop}");
+            assert_eq!(
+                preview["resulting_len"].as_u64(),
+                fork["history"].as_array().map(|items| items.len() as u64),
+                "drop {drop}"
+            );
+        }
+        assert_eq!(run(2, true)["would_drop_user_turns"], 2);
+        assert_eq!(run(2, true)["resulting_len"], 2);
+    }
+
+    #[test]
+    fn snapshot_summary_counts_image_items() {
+        let request: ExecuteRequest = serde_json::from_value(json!({
+            "type": "conversation_snapshot_summary",
+            "records": [
+                {
+                    "kind": "user",
+                    "stream_id": null,
+                    "markdown": "what is in this screenshot?",
+                    "content": [
+                        { "type": "input_image", "image_url": "data:image/png;base64,AAAA" },
+                        { "type": "input_text", "text": "caption" },
+                    ],
+                },
+                { "kind": "assistant", "stream_id": "s1", "markdown": "a terminal" },
+            ],
+        }))
+        .expect("request to parse");
+
+        let response = handle_request(request);
+        assert_eq!(response["status"], "ok");
+        assert_eq!(response["user_messages"], 1);
+        assert_eq!(response["image_items"], 1);
+    }
+
+    #[cfg(feature = "debug-repl")]
+    #[test]
+    fn execute_json_answers_echo_without_jni() {
+        let request = json!({ "type": "echo", "payload": { "ping": 1 } }).to_string();
+        let response: serde_json::Value =
+            serde_json::from_str(&super::execute_json(&request)).expect("response json");
+        assert_eq!(response, json!({ "status": "ok", "kind": "echo", "payload": { "ping": 1 } }));
+
+        let response: serde_json::Value =
+            serde_json::from_str(&super::execute_json("{\"type\":\"nope\"}")).expect("response json");
+        assert_eq!(response["status"], "error");
+    }
+
+    #[test]
+    fn oversized_request_is_rejected_before_parsing() {
+        let padding = "x".repeat(DEFAULT_MAX_REQUEST_BYTES);
+        let request = json!({ "type": "echo", "payload": padding }).to_string();
+        assert!(request.len() > DEFAULT_MAX_REQUEST_BYTES);
+
+        let response: serde_json::Value =
+            serde_json::from_str(&try_execute_json(&request).expect("structured error"))
+                .expect("response json");
+        assert_eq!(response["status"], "error");
+        assert_eq!(response["code"], "request_too_large");
+        assert_eq!(response["limit_bytes"], DEFAULT_MAX_REQUEST_BYTES);
+    }
+
+    #[test]
+    fn validate_config_reports_missing_required_field() {
+        let request = json!({
+            "type": "validate_config",
+            "config": { "model": "gpt-5.1-codex" },
+        })
+        .to_string();
+        let stored_before = CONFIG.lock().expect("config lock").clone();
+        let response: serde_json::Value =
+            serde_json::from_str(&try_execute_json(&request).expect("execute")).expect("response json");
+
+        assert_eq!(response["status"], "ok");
+        assert_eq!(response["kind"], "validate_config");
+        assert_eq!(response["valid"], false);
+        assert_eq!(response["errors"], json!(["missing required field `model_provider`"]));
+        assert_eq!(*CONFIG.lock().expect("config lock"), stored_before);
+    }
+
+    #[test]
+    fn utf16_request_round_trips_surrogate_pairs_through_execute() {
+        let text = "naïve 👋🏽 𝄞 中文";
+        let request = json!({ "type": "echo", "payload": { "text": text } }).to_string();
+        let units: Vec<u16> = request.encode_utf16().collect();
+        assert!(units.iter().any(|unit| (0xD800..0xDC00).contains(unit)));
+
+        let decoded = decode_java_utf16(&units).expect("valid utf-16");
+        let response: serde_json::Value =
+            serde_json::from_str(&try_execute_json(&decoded).expect("execute")).expect("response json");
+
+        assert_eq!(response["status"], "ok");
+        assert_eq!(response["payload"]["text"], text);
+    }
+
+    #[test]
+    fn unpaired_surrogate_is_an_encoding_error() {
+        let err = decode_java_utf16(&[0x0068, 0xD83D, 0x0069]).expect_err("lone surrogate");
+        assert!(err.starts_with("encoding error"), "{err}");
+    }
+
+    fn reasoning_delta(delta: &str) -> ResponseEvent {
+        ResponseEvent::ReasoningSummaryDelta {
+            delta: delta.to_string(),
+            item_id: Some("rs_1".to_string()),
+            sequence_number: None,
+            output_index: Some(0),
+            summary_index: Some(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn final_reasoning_item_does_not_duplicate_streamed_deltas() {
+        let events: Vec<Result<ResponseEvent, String>> = vec![
+            Ok(reasoning_delta("Checking the ")),
+            Ok(reasoning_delta("workspace layout.")),
+            Ok(ResponseEvent::OutputItemDone {
+                item: ResponseItem::Reasoning {
+                    id: "rs_1".to_string(),
+                    summary: vec![
+                        ReasoningItemReasoningSummary::SummaryText {
+                            text: "Checking the workspace layout.".to_string(),
+                        },
+                        ReasoningItemReasoningSummary::SummaryText {
+                            text: "Drafting the answer.".to_string(),
+                        },
+                    ],
+                    content: None,
+                    encrypted_content: None,
+                },
+                sequence_number: None,
+                output_index: Some(0),
+            }),
+            Ok(ResponseEvent::OutputItemDone {
+                item: ResponseItem::Message {
+                    id: None,
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText {
+                        text: "Done.".to_string(),
+                    }],
+                },
+                sequence_number: None,
+                output_index: Some(1),
+            }),
+            Ok(ResponseEvent::Completed {
+                response_id: "resp_1".to_string(),
+                token_usage: None,
+            }),
+        ];
+
+        let result = collect_simple_model_stream(futures::stream::iter(events), None, false)
+            .await
+            .expect("stream collects");
+
+        assert_eq!(
+            result.thinking,
+            vec![
+                "Checking the workspace layout.".to_string(),
+                "Drafting the answer.".to_string(),
+            ]
+        );
+        assert_eq!(result.answer, "Done.");
+        assert_eq!(result.thinking_offsets_ms.len(), result.thinking.len());
+    }
+
+    #[test]
+    fn fixture_results_report_null_thinking_offsets() {
+        let fixture = SimpleModelTurnFixture {
+            thinking: vec!["plan".to_string(), "  ".to_string(), "check".to_string()],
+            answer: "done".to_string(),
+        };
+        let response = simple_model_turn_json(simple_model_fixture_result(fixture, false));
+
+        assert_eq!(response["status"], "ok");
+        assert_eq!(response["thinking"], json!(["plan", "check"]));
+        assert_eq!(response["thinking_offsets_ms"], json!([null, null]));
+        assert_eq!(response["answer"], "done");
+    }
+
+    #[test]
+    fn preserve_order_fixture_results_return_segments() {
+        let fixture = SimpleModelTurnFixture {
+            thinking: vec!["plan ".to_string(), "check".to_string()],
+            answer: " done ".to_string(),
+        };
+        let response = simple_model_turn_json(simple_model_fixture_result(fixture, true));
+
+        assert_eq!(response["status"], "ok");
+        assert_eq!(
+            response["segments"],
+            json!([
+                { "kind": "reasoning", "text": "plan" },
+                { "kind": "reasoning", "text": "check" },
+                { "kind": "answer", "text": "done" },
+            ])
+        );
+        assert!(response.get("thinking").is_none());
+    }
+
+    #[tokio::test]
+    async fn streamed_thinking_offsets_are_monotonic() {
+        let events: Vec<Result<ResponseEvent, String>> = vec![
+            Ok(reasoning_delta("Reading the ")),
+            Ok(reasoning_delta("config.")),
+            Ok(ResponseEvent::ReasoningSummaryPartAdded),
+            Ok(reasoning_delta("Comparing options.")),
+            Ok(ResponseEvent::ReasoningSummaryPartAdded),
+            Ok(reasoning_delta("Picking one.")),
+            Ok(ResponseEvent::OutputTextDelta {
+                delta: "Use the default.".to_string(),
+                item_id: None,
+                sequence_number: None,
+                output_index: None,
+            }),
+            Ok(ResponseEvent::Completed {
+                response_id: "resp_1".to_string(),
+                token_usage: None,
+            }),
+        ];
+
+        let result = collect_simple_model_stream(futures::stream::iter(events), None, false)
+            .await
+            .expect("stream collects");
+
+        assert_eq!(result.thinking.len(), 3);
+        let offsets: Vec<u64> = result
+            .thinking_offsets_ms
+            .iter()
+            .map(|offset| offset.expect("streamed chunks carry offsets"))
+            .collect();
+        assert_eq!(offsets.len(), result.thinking.len());
+        assert!(offsets.windows(2).all(|pair| pair[0] <= pair[1]), "{offsets:?}");
+
+        let response = simple_model_turn_json(result);
+        let reported = response["thinking_offsets_ms"].as_array().expect("offsets array");
+        assert_eq!(reported.len(), 3);
+        assert!(reported.iter().all(serde_json::Value::is_u64));
+    }
+
+    #[tokio::test]
+    async fn preserve_order_keeps_reasoning_and_answer_interleaved() {
+        let answer_delta = |delta: &str| ResponseEvent::OutputTextDelta {
+            delta: delta.to_string(),
+            item_id: None,
+            sequence_number: None,
+            output_index: Some(1),
+        };
+        let events: Vec<Result<ResponseEvent, String>> = vec![
+            Ok(reasoning_delta("Reading ")),
+            Ok(reasoning_delta("the request.")),
+            Ok(answer_delta("First, ")),
+            Ok(answer_delta("the setup.")),
+            Ok(reasoning_delta("Double-checking paths.")),
+            Ok(answer_delta(" Then the fix.")),
+            Ok(ResponseEvent::Completed {
+                response_id: "resp_1".to_string(),
+                token_usage: None,
+            }),
+        ];
+
+        let result = collect_simple_model_stream(futures::stream::iter(events), None, true)
+            .await
+            .expect("stream collects");
+        let response = simple_model_turn_json(result);
+
+        assert_eq!(
+            response["segments"],
+            json!([
+                {"kind": "reasoning", "text": "Reading the request."},
+                {"kind": "answer", "text": "First, the setup."},
+                {"kind": "reasoning", "text": "Double-checking paths."},
+                {"kind": "answer", "text": "Then the fix."},
+            ])
+        );
+        assert!(response.get("answer").is_none());
+        assert!(response.get("thinking").is_none());
+    }
+
+    #[test]
+    fn blank_originator_falls_back_to_default() {
+        assert_eq!(effective_originator(""), FALLBACK_ORIGINATOR);
+        assert_eq!(effective_originator("   "), FALLBACK_ORIGINATOR);
+        assert_eq!(effective_originator("code_cli_rs"), "code_cli_rs");
+    }
+
+    #[test]
+    fn reasoning_heavy_usage_adds_budget_hint() {
+        let turn = |usage: Option<TokenUsage>| SimpleModelTurnResult {
+            thinking: Vec::new(),
+            thinking_offsets_ms: Vec::new(),
+            answer: "Done.".to_string(),
+            segments: None,
+            token_usage: usage,
+            cancelled: false,
+        };
+        let usage = TokenUsage {
+            input_tokens: 1_200,
+            cached_input_tokens: 0,
+            output_tokens: 5_000,
+            reasoning_output_tokens: 4_600,
+            total_tokens: 6_200,
+        };
+
+        let response = simple_model_turn_json(turn(Some(usage.clone())));
+        assert_eq!(
+            response["budget_hints"],
+            json!(["reasoning tokens exceeded 80% of output"])
+        );
+
+        let balanced = TokenUsage { reasoning_output_tokens: 1_000, ..usage };
+        let response = simple_model_turn_json(turn(Some(balanced)));
+        assert_eq!(response["budget_hints"], json!([]));
+        assert_eq!(simple_model_turn_json(turn(None))["budget_hints"], json!([]));
+    }
+
+    #[test]
+    fn simple_turn_rejects_unsupported_history_role() {
+        let request: ExecuteRequest = serde_json::from_value(json!({
+            "type": "simple_model_turn",
+            "history": [
+                {
+                    "type": "message",
+                    "role": "user",
+                    "content": [{"type": "input_text", "text": "hello"}],
+                },
+                {
+                    "type": "message",
+                    "role": "robot",
+                    "content": [{"type": "output_text", "text": "beep"}],
+                },
+            ],
+            "latest_user_prompt": null,
+        }))
+        .expect("request to parse");
+
+        let response = handle_request(request);
+        assert_eq!(response["status"], json!("error"));
+        assert_eq!(response["kind"], json!("simple_model_turn"));
+        assert_eq!(response["code"], json!("invalid_history_role"));
+        assert!(response["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("robot")));
+    }
+
+    #[test]
+    fn model_override_derives_family_from_model_name() {
+        // Configured as gpt-4.1, but the request asks for a Codex model: the
+        // family must follow the override rather than the config.
+        let family = resolve_model_override("gpt-5.1-codex", None).expect("known family");
+        assert_eq!(family.slug, "gpt-5.1-codex");
+        assert_eq!(family.family, "gpt-5.1-codex");
+        assert!(family.supports_reasoning_summaries);
+
+        let forced = resolve_model_override("proxy-model", Some("gpt-5")).expect("forced family");
+        assert_eq!(forced.slug, "proxy-model");
+        assert_eq!(forced.family, "gpt-5");
+    }
+
+    #[test]
+    fn unresolvable_model_family_is_reported() {
+        let request: ExecuteRequest = serde_json::from_value(json!({
+            "type": "simple_model_turn",
+            "history": [],
+            "latest_user_prompt": "hi",
+            "model": "mystery-model",
+        }))
+        .expect("request to parse");
+
+        let response = handle_request(request);
+        assert_eq!(response["status"], json!("error"));
+        assert_eq!(response["code"], json!("unknown_model_family"));
+    }
+
+    #[tokio::test]
+    async fn cancelling_mid_stream_returns_partial_answer() {
+        let registration = SimpleTurnRegistration::register("turn-cancel".to_string());
+        let (tx, rx) = futures::channel::mpsc::unbounded::<Result<ResponseEvent, String>>();
+        tx.unbounded_send(Ok(reasoning_delta("Looking around.")))
+            .expect("send reasoning");
+        tx.unbounded_send(Ok(ResponseEvent::OutputTextDelta {
+            delta: "Partial ".to_string(),
+            item_id: None,
+            sequence_number: None,
+            output_index: Some(1),
+        }))
+        .expect("send answer delta");
+
+        let (result, cancel_response) = tokio::join!(
+            collect_simple_model_stream(rx, Some(registration.cancellation.as_ref()), false),
+            async {
+                tokio::task::yield_now().await;
+                let request: ExecuteRequest = serde_json::from_value(json!({
+                    "type": "cancel_simple_turn",
+                    "turn_id": "turn-cancel",
+                }))
+                .expect("request to parse");
+                handle_request(request)
+            }
+        );
+        drop(tx);
+
+        assert_eq!(cancel_response["found"], json!(true));
+        let response = simple_model_turn_json(result.expect("cancelled turn still collects"));
+        assert_eq!(response["status"], json!("cancelled"));
+        assert_eq!(response["answer"], json!("Partial"));
+        assert_eq!(response["thinking"], json!(["Looking around."]));
+    }
+
     #[test]
     fn countdown_tick_refreshes_when_time_remaining() {
         let req_json = json!({
@@ -932,6 +2038,8 @@ mod tests {
         assert_eq!(response["seconds_left"], 5);
         assert_eq!(response["effects"].as_array().unwrap().len(), 1);
         assert_eq!(response["effects"][0]["type"], "refresh_ui");
+        assert_eq!(response["matched"], true);
+        assert!(response.get("ignored_reason").is_none());
     }
 
     #[test]
@@ -969,6 +2077,29 @@ mod tests {
 
         assert_eq!(response["status"], "ok");
         assert_eq!(response["effects"].as_array().unwrap().len(), 0);
+        assert_eq!(response["matched"], false);
+        assert_eq!(response["ignored_reason"], "wrong_phase");
+    }
+
+    #[test]
+    fn countdown_tick_reports_stale_countdown() {
+        let req_json = json!({
+            "type": "auto_drive_countdown_tick",
+            "phase": { "name": "awaiting_coordinator", "prompt_ready": true },
+            "countdown_id": 3,
+            "decision_seq": 9,
+            "seconds_left": 2,
+            "controller_countdown_id": 4,
+            "controller_decision_seq": 9
+        });
+        let request: ExecuteRequest = serde_json::from_value(req_json).expect("request to parse");
+
+        let response = handle_request(request);
+
+        assert_eq!(response["status"], "ok");
+        assert_eq!(response["effects"].as_array().unwrap().len(), 0);
+        assert_eq!(response["matched"], false);
+        assert_eq!(response["ignored_reason"], "stale_countdown");
     }
 
     #[test]
@@ -18,6 +18,15 @@ pub(crate) fn resolve_classpath() -> Result<String> {
     Ok(path_to_string(&path))
 }
 
+/// Whether the engine jar can be located, without starting a JVM.
+pub(crate) fn engine_jar_available() -> bool {
+    engine_jar_available_with_overrides(ResolverOverrides::default())
+}
+
+fn engine_jar_available_with_overrides(overrides: ResolverOverrides) -> bool {
+    resolve_with_overrides(overrides).is_ok()
+}
+
 fn resolve_with_overrides(overrides: ResolverOverrides) -> Result<PathBuf> {
     if let Some(path) = env_override_path(&overrides) {
         return Ok(path);
@@ -222,4 +231,25 @@ mod tests {
         assert!(msg.contains(workspace_fallback.to_str().unwrap()));
         assert!(msg.contains(target_fallback.to_str().unwrap()));
     }
+
+    #[test]
+    fn reports_engine_unavailable_when_jar_is_missing() {
+        let root = tempdir().unwrap();
+        let fake_bin = root.path().join("bin/code");
+        std::fs::create_dir_all(fake_bin.parent().unwrap()).unwrap();
+        std::fs::write(&fake_bin, b"binary").unwrap();
+        let missing = root.path().join("missing").join(ENGINE_JAR_NAME);
+
+        let overrides = ResolverOverrides {
+            env_override: Some(missing.to_string_lossy().into_owned()),
+            current_exe_override: Some(fake_bin),
+            workspace_jar_override: Some(missing.clone()),
+            cargo_target_jar_override: Some(missing.clone()),
+        };
+        assert!(!engine_jar_available_with_overrides(overrides.clone()));
+
+        std::fs::create_dir_all(missing.parent().unwrap()).unwrap();
+        std::fs::write(&missing, b"jar").unwrap();
+        assert!(engine_jar_available_with_overrides(overrides));
+    }
 }
@@ -0,0 +1,11 @@
+//! Structured errors callers may want to match on, carried inside `anyhow::Error`.
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HostError {
+    /// `CoreEngineHost` has no static method with this name and signature,
+    /// usually because the engine jar is older or newer than this crate.
+    #[error("CoreEngineHost has no method {method}{signature}; engine version mismatch?")]
+    MethodNotFound { method: String, signature: String },
+}
@@ -1,14 +1,28 @@
 use anyhow::{anyhow, Result};
+use jni::errors::Error as JniError;
 use jni::objects::{JObject, JString, JValue};
-use jni::{InitArgsBuilder, JavaVM};
+use jni::{InitArgsBuilder, JNIEnv, JavaVM};
 use once_cell::sync::OnceCell;
 
 mod classpath;
+mod error;
+mod retry;
+mod submission;
 
-use crate::classpath::resolve_classpath;
+use crate::classpath::{engine_jar_available, resolve_classpath};
+use crate::retry::{retry_attach, AttemptError, ATTACH_ATTEMPTS, ATTACH_BACKOFF};
+
+pub use crate::error::HostError;
+pub use crate::submission::{ControlCommand, Submission};
 
 static JVM: OnceCell<JavaVM> = OnceCell::new();
 
+/// Reports whether the Kotlin engine jar can be found, so callers can skip
+/// engine work up front. Does not create the JVM.
+pub fn is_kotlin_engine_available() -> bool {
+    engine_jar_available()
+}
+
 fn java_vm() -> Result<&'static JavaVM> {
     JVM.get_or_try_init(|| {
         let classpath = resolve_classpath()?;
@@ -23,13 +37,27 @@ fn java_vm() -> Result<&'static JavaVM> {
 
 fn call_static_str(method: &str, signature: &str, args: &[JValue<'_, '_>]) -> Result<String> {
     let vm = java_vm()?;
-    let mut env = vm.attach_current_thread().map_err(|err| anyhow!("attach thread failed: {err}"))?;
+    retry_attach(ATTACH_ATTEMPTS, ATTACH_BACKOFF, || {
+        let mut env = vm
+            .attach_current_thread()
+            .map_err(|err| AttemptError::Attach(anyhow!("attach thread failed: {err}")))?;
+        call_static_str_on(&mut env, method, signature, args).map_err(AttemptError::Call)
+    })
+}
+
+fn call_static_str_on(
+    env: &mut JNIEnv<'_>,
+    method: &str,
+    signature: &str,
+    args: &[JValue<'_, '_>],
+) -> Result<String> {
     let class = env
         .find_class("ai/lightcode/core/engine/CoreEngineHost")
         .map_err(|err| anyhow!("failed to find CoreEngineHost: {err}"))?;
-    let result = env
-        .call_static_method(class, method, signature, args)
-        .map_err(|err| anyhow!("call {method} failed: {err}"))?;
+    let result = match env.call_static_method(class, method, signature, args) {
+        Ok(result) => result,
+        Err(err) => return Err(call_error(env, method, signature, err)),
+    };
     let obj = result.l().map_err(|err| anyhow!("{method} returned non-object: {err}"))?;
     let jstr: JString = JString::from(obj);
     let rust_str: String = env
@@ -39,6 +67,42 @@ fn call_static_str(method: &str, signature: &str, args: &[JValue<'_, '_>]) -> Re
     Ok(rust_str)
 }
 
+/// Maps a failed static call to [`HostError::MethodNotFound`] when the JVM
+/// raised `NoSuchMethodError`, so hosts can detect engine version skew.
+fn call_error(env: &mut JNIEnv<'_>, method: &str, signature: &str, err: JniError) -> anyhow::Error {
+    let missing = match err {
+        JniError::MethodNotFound { .. } => true,
+        JniError::JavaException => take_no_such_method_error(env),
+        _ => false,
+    };
+    if missing {
+        return HostError::MethodNotFound {
+            method: method.to_string(),
+            signature: signature.to_string(),
+        }
+        .into();
+    }
+    anyhow!("call {method} failed: {err}")
+}
+
+/// Clears a pending `NoSuchMethodError` and reports whether there was one.
+/// Any other pending exception is rethrown so the caller still sees it.
+fn take_no_such_method_error(env: &mut JNIEnv<'_>) -> bool {
+    let Ok(throwable) = env.exception_occurred() else {
+        return false;
+    };
+    if throwable.is_null() || env.exception_clear().is_err() {
+        return false;
+    }
+    let missing = env
+        .is_instance_of(&throwable, "java/lang/NoSuchMethodError")
+        .unwrap_or(false);
+    if !missing {
+        let _ = env.throw(throwable);
+    }
+    missing
+}
+
 pub fn start_session(config_json: &str) -> Result<String> {
     let vm = java_vm()?;
     let env = vm.attach_current_thread().map_err(|err| anyhow!("attach thread failed: {err}"))?;
@@ -74,6 +138,14 @@ pub fn submit_turn(session_id: &str, submission_json: &str) -> Result<String> {
     )
 }
 
+/// Typed counterpart of [`submit_turn`]; serializes `submission` so payload
+/// shape errors surface in Rust rather than as `invalid_submission`.
+pub fn submit_turn_typed(session_id: &str, submission: &Submission) -> Result<String> {
+    let json = serde_json::to_string(submission)
+        .map_err(|err| anyhow!("failed to serialize submission: {err}"))?;
+    submit_turn(session_id, &json)
+}
+
 pub fn poll_events(session_id: &str, cursor_json: &str) -> Result<String> {
     let vm = java_vm()?;
     let env = vm.attach_current_thread().map_err(|err| anyhow!("attach thread failed: {err}"))?;
@@ -124,3 +196,31 @@ pub fn run_auto_drive_sequence_raw(submission_json: &str) -> Result<String> {
         &[JValue::Object(&payload_obj)],
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_static_method_is_reported_as_method_not_found() {
+        if !is_kotlin_engine_available() {
+            eprintln!("skipping: Kotlin engine jar not found");
+            return;
+        }
+        let err = match call_static_str("noSuchMethodForTests", "()Ljava/lang/String;", &[]) {
+            Ok(value) => panic!("expected a missing-method error, got {value:?}"),
+            Err(err) => err,
+        };
+        if err.to_string().contains("failed to create JVM") {
+            eprintln!("skipping: {err}");
+            return;
+        }
+        assert_eq!(
+            err.downcast_ref::<HostError>(),
+            Some(&HostError::MethodNotFound {
+                method: "noSuchMethodForTests".to_string(),
+                signature: "()Ljava/lang/String;".to_string(),
+            })
+        );
+    }
+}
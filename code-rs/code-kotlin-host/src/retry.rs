@@ -0,0 +1,83 @@
+//! Bounded retry for transient JVM thread-attach failures.
+
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+
+pub(crate) const ATTACH_ATTEMPTS: usize = 3;
+pub(crate) const ATTACH_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Why a single attach-and-call attempt failed.
+pub(crate) enum AttemptError {
+    /// The thread could not be attached; this can clear up under thread churn.
+    Attach(anyhow::Error),
+    /// The call itself failed (e.g. a Java exception), which would fail again.
+    Call(anyhow::Error),
+}
+
+/// Runs `attempt` up to `attempts` times, backing off linearly between tries.
+/// Only attach failures are retried; the last one is returned if all fail.
+pub(crate) fn retry_attach<T>(
+    attempts: usize,
+    backoff: Duration,
+    mut attempt: impl FnMut() -> Result<T, AttemptError>,
+) -> Result<T> {
+    let mut last_err = None;
+    for n in 0..attempts.max(1) {
+        if n > 0 {
+            thread::sleep(backoff * n as u32);
+        }
+        match attempt() {
+            Ok(value) => return Ok(value),
+            Err(AttemptError::Call(err)) => return Err(err),
+            Err(AttemptError::Attach(err)) => {
+                debug!(attempt = n + 1, "JVM attach failed: {err:#}");
+                last_err = Some(err);
+            }
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow!("attach thread failed")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attach_failure_then_success_is_retried() {
+        let mut calls = 0;
+        let result = retry_attach(3, Duration::ZERO, || {
+            calls += 1;
+            if calls == 1 {
+                Err(AttemptError::Attach(anyhow!("attach thread failed: busy")))
+            } else {
+                Ok("ok")
+            }
+        });
+        assert_eq!(result.expect("retried"), "ok");
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn call_errors_are_not_retried_and_attach_errors_are_bounded() {
+        let mut calls = 0;
+        let err = retry_attach::<()>(3, Duration::ZERO, || {
+            calls += 1;
+            Err(AttemptError::Call(anyhow!("java exception")))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "java exception");
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        let err = retry_attach::<()>(3, Duration::ZERO, || {
+            calls += 1;
+            Err(AttemptError::Attach(anyhow!("attach {calls}")))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "attach 3");
+        assert_eq!(calls, 3);
+    }
+}
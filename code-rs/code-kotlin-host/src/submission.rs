@@ -0,0 +1,57 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// A turn submission accepted by `CoreEngineHost.submitTurn`, mirroring the
+/// Kotlin `SubmissionEnvelope`. Nested engine payloads (controller state,
+/// operations, response items) are passed through as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Submission {
+    AutoDriveSequence {
+        initial_state: Value,
+        operations: Vec<Value>,
+    },
+    ChatTurn {
+        history: Vec<Value>,
+        turn_input: Vec<Value>,
+    },
+    Control {
+        command: ControlCommand,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlCommand {
+    Stop,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn submissions_serialize_to_envelope_shape() {
+        let chat = Submission::ChatTurn {
+            history: vec![json!({"type": "message", "role": "user"})],
+            turn_input: Vec::new(),
+        };
+        assert_eq!(
+            serde_json::to_value(&chat).expect("serialize"),
+            json!({
+                "type": "chat_turn",
+                "history": [{"type": "message", "role": "user"}],
+                "turn_input": [],
+            })
+        );
+
+        let stop = Submission::Control {
+            command: ControlCommand::Stop,
+        };
+        assert_eq!(
+            serde_json::to_value(&stop).expect("serialize"),
+            json!({"type": "control", "command": "stop"})
+        );
+    }
+}
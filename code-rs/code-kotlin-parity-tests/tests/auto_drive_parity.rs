@@ -7,13 +7,17 @@ use code_auto_drive_core::{
     AutoRunPhase,
 };
 use codex_core_jni as _;
-use code_kotlin_host::run_auto_drive_sequence_raw;
+use code_kotlin_host::{is_kotlin_engine_available, run_auto_drive_sequence_raw};
 use serde::Deserialize;
 use serde_json::{json, Value};
 
 #[serial_test::serial]
 #[test]
 fn kotlin_auto_drive_sequence_matches_rust_effects() {
+    if !is_kotlin_engine_available() {
+        eprintln!("skipping Kotlin parity test: Kotlin engine jar not found");
+        return;
+    }
     let payload = sample_payload();
     let request: AutoDriveSequenceEnvelope =
         serde_json::from_value(payload.clone()).expect("fixture payload to deserialize");
@@ -61,8 +65,7 @@ fn sample_payload() -> Value {
 
 fn should_skip(err: &anyhow::Error) -> bool {
     let msg = err.to_string();
-    msg.contains("Kotlin engine jar not found")
-        || msg.contains("failed to create JVM")
+    msg.contains("failed to create JVM")
         || msg.contains("failed to find CoreEngineHost")
         || msg.contains("Java exception was thrown")
 }
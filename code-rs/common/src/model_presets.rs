@@ -14,6 +14,21 @@ pub struct ModelPreset {
     pub model: &'static str,
     /// Reasoning effort to apply for this preset.
     pub effort: Option<ReasoningEffort>,
+    /// Efforts the model accepts. Empty when unknown, in which case every
+    /// effort is treated as supported.
+    pub supported_efforts: &'static [ReasoningEffort],
+}
+
+impl ModelPreset {
+    /// Whether the model accepts this preset's reasoning effort.
+    pub fn supports_effort(&self) -> bool {
+        match self.effort {
+            Some(effort) => {
+                self.supported_efforts.is_empty() || self.supported_efforts.contains(&effort)
+            }
+            None => true,
+        }
+    }
 }
 
 const PRESETS: &[ModelPreset] = &[
@@ -23,6 +38,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Fastest responses with limited reasoning",
         model: "gpt-5.1-codex",
         effort: Some(ReasoningEffort::Low),
+        supported_efforts: &[],
     },
     ModelPreset {
         id: "gpt-5.1-codex-medium",
@@ -30,6 +46,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Dynamically adjusts reasoning based on the task",
         model: "gpt-5.1-codex",
         effort: Some(ReasoningEffort::Medium),
+        supported_efforts: &[],
     },
     ModelPreset {
         id: "gpt-5.1-codex-high",
@@ -37,6 +54,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Maximizes reasoning depth for complex or ambiguous problems",
         model: "gpt-5.1-codex",
         effort: Some(ReasoningEffort::High),
+        supported_efforts: &[],
     },
     ModelPreset {
         id: "gpt-5.1-codex-mini",
@@ -44,6 +62,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Optimized for Code. Cheaper, faster, but less capable.",
         model: "gpt-5.1-codex-mini",
         effort: Some(ReasoningEffort::Medium),
+        supported_efforts: &[],
     },
     ModelPreset {
         id: "gpt-5.1-codex-mini-high",
@@ -51,6 +70,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Maximizes reasoning depth for complex or ambiguous problems",
         model: "gpt-5.1-codex-mini",
         effort: Some(ReasoningEffort::High),
+        supported_efforts: &[],
     },
     ModelPreset {
         id: "gpt-5.1-minimal",
@@ -58,6 +78,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Fastest responses with little reasoning",
         model: "gpt-5.1",
         effort: Some(ReasoningEffort::Minimal),
+        supported_efforts: &[],
     },
     ModelPreset {
         id: "gpt-5.1-low",
@@ -65,6 +86,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Balances speed with some reasoning; useful for straightforward queries and short explanations",
         model: "gpt-5.1",
         effort: Some(ReasoningEffort::Low),
+        supported_efforts: &[],
     },
     ModelPreset {
         id: "gpt-5.1-medium",
@@ -72,6 +94,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Provides a solid balance of reasoning depth and latency for general-purpose tasks",
         model: "gpt-5.1",
         effort: Some(ReasoningEffort::Medium),
+        supported_efforts: &[],
     },
     ModelPreset {
         id: "gpt-5.1-high",
@@ -79,6 +102,7 @@ const PRESETS: &[ModelPreset] = &[
         description: "Maximizes reasoning depth for complex or ambiguous problems",
         model: "gpt-5.1",
         effort: Some(ReasoningEffort::High),
+        supported_efforts: &[],
     },
 ];
 
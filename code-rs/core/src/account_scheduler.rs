@@ -1,12 +1,16 @@
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, TimeDelta, Utc};
 use code_app_server_protocol::AuthMode;
-use tracing::warn;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
-use crate::account_usage::{self, StoredRateLimitSnapshot};
-use crate::auth_accounts::{self, StoredAccount};
+use crate::account_usage::{self, StoredRateLimitSnapshot, StoredUsageSummary};
+use crate::auth_accounts::{self, has_credentials, StoredAccount};
+use crate::global_usage_tracker::{estimate_cost, ModelBucket};
 
 const DEFAULT_PRIORITY_SCORE: f64 = 10_000.0;
 const MIN_TIME_FRACTION: f64 = 0.01;
@@ -30,6 +34,15 @@ pub struct AccountSelection {
     pub snapshot: Option<StoredRateLimitSnapshot>,
 }
 
+/// Why [`AccountScheduler::select_account`] could not pick an account.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionError {
+    /// No account has credentials and is outside its cooldown.
+    NoEligibleAccounts,
+    /// Every otherwise eligible account is over its daily spend cap.
+    AllCapped { account_ids: Vec<String> },
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum SchedulerOutcome {
     Success,
@@ -43,6 +56,8 @@ pub struct AccountScheduler {
     cooldowns: HashMap<String, DateTime<Utc>>,
     weights: HashMap<String, WeightedState>,
     context_bindings: HashMap<String, ContextBinding>,
+    daily_caps: HashMap<String, f64>,
+    plan_weights: HashMap<String, f64>,
 }
 
 impl AccountScheduler {
@@ -52,11 +67,40 @@ impl AccountScheduler {
             cooldowns: HashMap::new(),
             weights: HashMap::new(),
             context_bindings: HashMap::new(),
+            daily_caps: HashMap::new(),
+            plan_weights: HashMap::new(),
         }
     }
 
+    /// Cap the estimated USD spend per account for the current UTC day.
+    /// Accounts over their cap are skipped until the day rolls over.
+    pub fn with_daily_cap(mut self, caps: HashMap<String, f64>) -> Self {
+        self.daily_caps = caps;
+        self
+    }
+
+    /// Scale each account's weight by a multiplier for its plan (e.g. `pro` =>
+    /// 1.5, `plus` => 1.0). Plan names match case-insensitively; accounts whose
+    /// plan is unknown or not listed keep a multiplier of 1.0.
+    pub fn with_plan_weights(mut self, multipliers: HashMap<String, f64>) -> Self {
+        self.plan_weights = multipliers
+            .into_iter()
+            .map(|(plan, multiplier)| (plan.to_ascii_lowercase(), multiplier))
+            .collect();
+        self
+    }
+
     /// Pick the next account using smooth weighted round‑robin.
     pub fn next_account(&mut self, context: Option<&str>, now: DateTime<Utc>) -> Option<AccountSelection> {
+        self.select_account(context, now).ok()
+    }
+
+    /// Like [`Self::next_account`], but reports why no account was chosen.
+    pub fn select_account(
+        &mut self,
+        context: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<AccountSelection, SelectionError> {
         self.prune_expired_cooldowns(now);
         self.prune_stale_contexts(now);
 
@@ -65,40 +109,42 @@ impl AccountScheduler {
             .filter(|ctx| !ctx.is_empty())
             .map(|ctx| ctx.to_string());
 
-        let snapshots = match account_usage::list_rate_limit_snapshots(&self.code_home) {
-            Ok(entries) => entries
-                .into_iter()
-                .map(|entry| (entry.account_id.clone(), entry))
-                .collect::<HashMap<_, _>>(),
-            Err(err) => {
-                warn!("failed to read rate-limit snapshots: {err:#}");
-                HashMap::new()
-            }
-        };
+        let snapshots = self.load_snapshots();
 
         let accounts = match auth_accounts::list_accounts(&self.code_home) {
             Ok(accounts) => accounts,
             Err(err) => {
                 warn!("failed to list accounts: {err:#}");
-                return None;
+                return Err(SelectionError::NoEligibleAccounts);
             }
         };
 
         let mut totals_by_identity: HashMap<String, f64> = HashMap::new();
         let mut slots: Vec<SlotCandidate> = Vec::new();
         let mut identity_by_account: HashMap<String, String> = HashMap::new();
+        let mut capped_accounts: Vec<String> = Vec::new();
 
         for account in accounts.iter() {
-            if !has_credentials(account) || self.is_blocked(&account.id, now) {
+            if !has_credentials(account) {
+                debug!(account_id = %account.id, reason = "missing_credentials", "skipping account");
+                continue;
+            }
+            if self.is_blocked(&account.id, now) {
+                debug!(account_id = %account.id, reason = "cooldown", "skipping account");
+                continue;
+            }
+            if is_exhausted_until_reset(snapshots.get(&account.id), now) {
+                debug!(account_id = %account.id, reason = "exhausted", "skipping account");
+                continue;
+            }
+            if self.is_over_daily_cap(&account.id, now) {
+                debug!(account_id = %account.id, reason = "daily_cap", "skipping account");
+                capped_accounts.push(account.id.clone());
                 continue;
             }
 
             let snapshot = snapshots.get(&account.id).cloned();
-            let weight = snapshot
-                .as_ref()
-                .map(|entry| compute_weight(entry, now))
-                .unwrap_or(DEFAULT_PRIORITY_SCORE)
-                .max(MIN_EFFECTIVE_WEIGHT);
+            let weight = self.account_weight(account, snapshot.as_ref(), now);
 
             let identity = slot_identity(account);
             *totals_by_identity.entry(identity.clone()).or_insert(0.0) += weight;
@@ -125,7 +171,12 @@ impl AccountScheduler {
         let total_weight: f64 = totals_by_identity.values().sum();
 
         if total_weight <= 0.0 {
-            return None;
+            if slots.is_empty() && !capped_accounts.is_empty() {
+                return Err(SelectionError::AllCapped {
+                    account_ids: capped_accounts,
+                });
+            }
+            return Err(SelectionError::NoEligibleAccounts);
         }
 
         let mut forced_account_id: Option<String> = None;
@@ -190,12 +241,15 @@ impl AccountScheduler {
             }
         }
 
-        let chosen_identity = if let Some(identity) = forced_identity {
-            identity
-        } else {
-            best_identity?
+        let chosen_identity = match forced_identity.or(best_identity) {
+            Some(identity) => identity,
+            None => return Err(SelectionError::NoEligibleAccounts),
         };
 
+        let chosen_weight = totals_by_identity
+            .get(&chosen_identity)
+            .copied()
+            .unwrap_or_default();
         if let Some(state) = self.weights.get_mut(&chosen_identity) {
             state.current -= total_weight;
         }
@@ -222,6 +276,15 @@ impl AccountScheduler {
             }
         }
 
+        debug!(
+            account_id = %selection.account_id,
+            identity = %chosen_identity,
+            weight = chosen_weight,
+            total_weight,
+            eligible_slots = slots.len(),
+            "selected account"
+        );
+
         if let Some(ctx_key) = context.as_ref() {
             self.context_bindings.insert(
                 ctx_key.clone(),
@@ -233,7 +296,54 @@ impl AccountScheduler {
             );
         }
 
-        Some(selection)
+        Ok(selection)
+    }
+
+    /// Read-only counterpart to [`Self::next_account`]: lists every account with
+    /// its smooth-WRR priority for the next round and whether it is eligible.
+    /// The priority is the identity's accumulator plus its weight, i.e. the
+    /// value `next_account` would compare; ineligible accounts report `0.0`.
+    /// Neither the accumulators nor the cooldown map are modified.
+    pub fn preview_candidates(&self, now: DateTime<Utc>) -> Vec<(String, f64, bool)> {
+        let snapshots = self.load_snapshots();
+        let accounts = match auth_accounts::list_accounts(&self.code_home) {
+            Ok(accounts) => accounts,
+            Err(err) => {
+                warn!("failed to list accounts: {err:#}");
+                return Vec::new();
+            }
+        };
+
+        let mut totals_by_identity: HashMap<String, f64> = HashMap::new();
+        let mut identity_by_account: HashMap<&str, String> = HashMap::new();
+        for account in accounts.iter() {
+            let eligible = has_credentials(account)
+                && !self.is_blocked(&account.id, now)
+                && !is_exhausted_until_reset(snapshots.get(&account.id), now)
+                && !self.is_over_daily_cap(&account.id, now);
+            if !eligible {
+                continue;
+            }
+            let weight = self.account_weight(account, snapshots.get(&account.id), now);
+            let identity = slot_identity(account);
+            *totals_by_identity.entry(identity.clone()).or_insert(0.0) += weight;
+            identity_by_account.insert(account.id.as_str(), identity);
+        }
+
+        accounts
+            .iter()
+            .map(|account| match identity_by_account.get(account.id.as_str()) {
+                Some(identity) => {
+                    let weight_sum = totals_by_identity.get(identity).copied().unwrap_or_default();
+                    let current = self
+                        .weights
+                        .get(identity)
+                        .map_or(0.0, |state| state.current);
+                    (account.id.clone(), current + weight_sum, true)
+                }
+                None => (account.id.clone(), 0.0, false),
+            })
+            .collect()
     }
 
     pub fn record_outcome(&mut self, account_id: &str, outcome: SchedulerOutcome) {
@@ -251,6 +361,56 @@ impl AccountScheduler {
         }
     }
 
+    /// Persist the smooth-WRR weights and active cooldowns so a later process
+    /// can continue the same rotation via [`Self::load_state`].
+    pub fn save_state(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let state = PersistedSchedulerState {
+            weights: self.weights.clone(),
+            cooldowns: self.cooldowns.clone(),
+        };
+        let json = serde_json::to_string_pretty(&state)?;
+        fs::write(path, json)
+    }
+
+    /// Restore state written by [`Self::save_state`]. A missing file leaves the
+    /// scheduler untouched; cooldowns that already expired are dropped.
+    pub fn load_state(&mut self, path: &Path) -> io::Result<()> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let state: PersistedSchedulerState = serde_json::from_str(&contents)?;
+        self.weights = state.weights;
+        self.cooldowns = state.cooldowns;
+        self.prune_expired_cooldowns(Utc::now());
+        Ok(())
+    }
+
+    fn load_snapshots(&self) -> HashMap<String, StoredRateLimitSnapshot> {
+        load_rate_limit_snapshots(&self.code_home)
+    }
+
+    /// Per-account scheduling weight: quota-derived weight scaled by the plan
+    /// multiplier, floored so every eligible account keeps a nonzero share.
+    fn account_weight(
+        &self,
+        account: &StoredAccount,
+        snapshot: Option<&StoredRateLimitSnapshot>,
+        now: DateTime<Utc>,
+    ) -> f64 {
+        let base = snapshot
+            .map(|entry| compute_weight(entry, now))
+            .unwrap_or(DEFAULT_PRIORITY_SCORE);
+        let multiplier = plan_for_account(account)
+            .and_then(|plan| self.plan_weights.get(&plan.to_ascii_lowercase()).copied())
+            .unwrap_or(1.0);
+        (base * multiplier).max(MIN_EFFECTIVE_WEIGHT)
+    }
+
     fn prune_expired_cooldowns(&mut self, now: DateTime<Utc>) {
         self.cooldowns.retain(|_, until| *until > now);
     }
@@ -267,18 +427,106 @@ impl AccountScheduler {
             .map_or(false, |until| *until > now)
     }
 
+    fn is_over_daily_cap(&self, account_id: &str, now: DateTime<Utc>) -> bool {
+        let Some(cap) = self.daily_caps.get(account_id) else {
+            return false;
+        };
+        match account_usage::load_account_usage(&self.code_home, account_id) {
+            Ok(Some(summary)) => spend_for_day(&summary, now) > *cap,
+            Ok(None) => false,
+            Err(err) => {
+                warn!("failed to read usage for account {account_id}: {err:#}");
+                false
+            }
+        }
+    }
+
     fn drop_context_bindings_for_account(&mut self, account_id: &str) {
         self.context_bindings
             .retain(|_, binding| binding.account_id != account_id);
     }
 }
 
-#[derive(Debug, Clone)]
+/// One row of [`account_status_overview`]: an account joined with its latest
+/// rate-limit snapshot and today's estimated spend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountStatus {
+    pub id: String,
+    pub label: Option<String>,
+    pub mode: AuthMode,
+    pub plan: Option<String>,
+    pub secondary_used_percent: Option<f64>,
+    /// When the secondary (weekly) window next resets.
+    pub resume_at: Option<DateTime<Utc>>,
+    /// When the secondary window would hit 100% at the current pace; see
+    /// [`projected_runout`].
+    pub projected_runout: Option<DateTime<Utc>>,
+    pub today_cost: f64,
+}
+
+/// Lists every known account with its quota and spend for a single dashboard
+/// call. Accounts without a snapshot or usage file report empty fields.
+pub fn account_status_overview(code_home: &Path) -> Vec<AccountStatus> {
+    account_status_overview_at(code_home, Utc::now())
+}
+
+/// [`account_status_overview`] with today's spend computed for the UTC day
+/// containing `now`.
+pub fn account_status_overview_at(code_home: &Path, now: DateTime<Utc>) -> Vec<AccountStatus> {
+    let accounts = match auth_accounts::list_accounts(code_home) {
+        Ok(accounts) => accounts,
+        Err(err) => {
+            warn!("failed to list accounts: {err:#}");
+            return Vec::new();
+        }
+    };
+    let mut snapshots = load_rate_limit_snapshots(code_home);
+
+    accounts
+        .into_iter()
+        .map(|account| {
+            let snapshot = snapshots.remove(&account.id);
+            let today_cost = match account_usage::load_account_usage(code_home, &account.id) {
+                Ok(Some(summary)) => spend_for_day(&summary, now),
+                Ok(None) => 0.0,
+                Err(err) => {
+                    warn!("failed to read usage for account {}: {err:#}", account.id);
+                    0.0
+                }
+            };
+            let plan = plan_for_account(&account)
+                .or_else(|| snapshot.as_ref().and_then(|entry| entry.plan.clone()));
+            AccountStatus {
+                plan,
+                secondary_used_percent: snapshot
+                    .as_ref()
+                    .and_then(|entry| entry.snapshot.as_ref())
+                    .map(|event| event.secondary_used_percent),
+                projected_runout: snapshot.as_ref().and_then(|entry| projected_runout(entry, now)),
+                resume_at: snapshot.and_then(|entry| entry.secondary_next_reset_at),
+                today_cost,
+                id: account.id,
+                label: account.label,
+                mode: account.mode,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct WeightedState {
     weight: f64,
     current: f64,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedSchedulerState {
+    #[serde(default)]
+    weights: HashMap<String, WeightedState>,
+    #[serde(default)]
+    cooldowns: HashMap<String, DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone)]
 struct SlotCandidate {
     selection: AccountSelection,
@@ -293,18 +541,65 @@ struct ContextBinding {
     last_used_at: DateTime<Utc>,
 }
 
-fn has_credentials(account: &StoredAccount) -> bool {
-    match account.mode {
-        AuthMode::ApiKey => account.openai_api_key.is_some(),
-        AuthMode::ChatGPT => account.tokens.is_some(),
+/// Latest rate-limit snapshot per account id; unreadable snapshots count as none.
+fn load_rate_limit_snapshots(code_home: &Path) -> HashMap<String, StoredRateLimitSnapshot> {
+    match account_usage::list_rate_limit_snapshots(code_home) {
+        Ok(entries) => entries
+            .into_iter()
+            .map(|entry| (entry.account_id.clone(), entry))
+            .collect(),
+        Err(err) => {
+            warn!("failed to read rate-limit snapshots: {err:#}");
+            HashMap::new()
+        }
     }
 }
 
 fn plan_for_account(account: &StoredAccount) -> Option<String> {
-    account
-        .tokens
+    account.chatgpt_plan()
+}
+
+/// Estimated spend for the UTC day containing `now`. Per-account usage does not
+/// record the model, so the premium Codex rates are used as a conservative bound.
+fn spend_for_day(summary: &StoredUsageSummary, now: DateTime<Utc>) -> f64 {
+    let day = now.date_naive();
+    let rolled_up = summary
+        .daily_buckets
+        .iter()
+        .filter(|bucket| bucket.period_start.date_naive() == day)
+        .map(|bucket| &bucket.tokens);
+    let recent = summary
+        .hourly_entries
+        .iter()
+        .filter(|entry| entry.timestamp.date_naive() == day)
+        .map(|entry| &entry.tokens);
+
+    rolled_up
+        .chain(recent)
+        .map(|tokens| {
+            estimate_cost(
+                ModelBucket::Gpt51Codex,
+                tokens.input_tokens.saturating_sub(tokens.cached_input_tokens),
+                tokens.cached_input_tokens,
+                tokens
+                    .output_tokens
+                    .saturating_add(tokens.reasoning_output_tokens),
+            )
+        })
+        .sum()
+}
+
+/// An account whose last snapshot shows the secondary window fully used stays
+/// unavailable until that window resets, even without a recorded cooldown.
+fn is_exhausted_until_reset(snapshot: Option<&StoredRateLimitSnapshot>, now: DateTime<Utc>) -> bool {
+    let Some(snapshot) = snapshot else {
+        return false;
+    };
+    let fully_used = snapshot
+        .snapshot
         .as_ref()
-        .and_then(|t| t.id_token.get_chatgpt_plan_type())
+        .is_some_and(|event| event.secondary_used_percent >= 100.0);
+    fully_used && snapshot.secondary_next_reset_at.is_some_and(|reset| reset > now)
 }
 
 fn compute_priority(snapshot: &StoredRateLimitSnapshot, now: DateTime<Utc>) -> Option<f64> {
@@ -323,6 +618,37 @@ fn compute_priority(snapshot: &StoredRateLimitSnapshot, now: DateTime<Utc>) -> O
     Some(remaining_pct / time_fraction)
 }
 
+/// Projects when the secondary window reaches 100% used, assuming usage keeps
+/// the pace implied by the percent used so far over the elapsed part of the
+/// window. A projection past `secondary_next_reset_at` means the account
+/// should not run out this window. `None` without a reset time or any usage,
+/// or when the projection does not fit in a timestamp.
+pub fn projected_runout(snapshot: &StoredRateLimitSnapshot, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let event = snapshot.snapshot.as_ref()?;
+    let reset_at = snapshot.secondary_next_reset_at?;
+    let used_pct = event.secondary_used_percent;
+    if used_pct >= 100.0 {
+        return Some(now);
+    }
+    if used_pct <= 0.0 {
+        return None;
+    }
+    let window_start = reset_at - Duration::minutes(event.secondary_window_minutes as i64);
+    let elapsed_secs = (now - window_start).num_seconds();
+    if elapsed_secs <= 0 {
+        return None;
+    }
+    let pct_per_sec = used_pct / elapsed_secs as f64;
+    let remaining_secs = (100.0 - used_pct) / pct_per_sec;
+    // A tiny percent used projects past what a timestamp can hold; treat
+    // that as no runout rather than overflowing.
+    if !remaining_secs.is_finite() {
+        return None;
+    }
+    let remaining = TimeDelta::try_seconds(remaining_secs.round() as i64)?;
+    now.checked_add_signed(remaining)
+}
+
 pub fn compute_weight(snapshot: &StoredRateLimitSnapshot, now: DateTime<Utc>) -> f64 {
     // Remaining fraction of the secondary window (treat as weekly window surrogate).
     let ratio = compute_priority(snapshot, now).unwrap_or(DEFAULT_PRIORITY_SCORE) / 100.0;
@@ -3,6 +3,8 @@ use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 
+use base64::Engine;
+use chrono::{DateTime, Utc};
 use code_app_server_protocol::AuthMode;
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
@@ -10,7 +12,7 @@ use tracing::warn;
 
 use crate::auth;
 use crate::auth::AuthDotJson;
-use crate::auth_accounts::StoredAccount;
+use crate::auth_accounts::{match_chatgpt_account, normalize_email, StoredAccount};
 use crate::config::resolve_code_path_for_read;
 
 const SLOT_REGISTRY_FILE: &str = "slot_registry.json";
@@ -34,6 +36,17 @@ impl AccountSlot {
     }
 }
 
+/// Credential summary for a slot's `auth.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SlotHealth {
+    pub has_auth_file: bool,
+    pub has_tokens: bool,
+    pub has_api_key: bool,
+    pub email: Option<String>,
+    /// Whether the ChatGPT tokens have expired; `None` when no JWT carries an `exp` claim.
+    pub expired: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SlotRegistryEntry {
     id: String,
@@ -102,7 +115,7 @@ impl SlotRegistryFile {
     fn hydrate_from_filesystem(&mut self, code_home: &Path) -> io::Result<bool> {
         let mut dirty = false;
         let mut known_ids = self.ids();
-        let discovered = scan_slot_dirs(code_home)?;
+        let discovered = scan_slot_dirs(code_home, SlotScan::Metadata)?;
         for slot in discovered {
             if self
                 .slots
@@ -218,7 +231,7 @@ fn default_slot(code_home: &Path) -> AccountSlot {
 pub fn add_slot(code_home: &Path, label: Option<&str>) -> io::Result<AccountSlot> {
     let mut registry = SlotRegistryFile::load(code_home)?;
     let mut existing_ids = registry.ids();
-    let discovered = scan_slot_dirs(code_home)?;
+    let discovered = scan_slot_dirs(code_home, SlotScan::Metadata)?;
     for slot in discovered {
         existing_ids.insert(slot.id);
     }
@@ -248,6 +261,26 @@ pub fn add_slot(code_home: &Path, label: Option<&str>) -> io::Result<AccountSlot
     Ok(AccountSlot::new(unique_id, cleaned_label, dir_path, false))
 }
 
+/// Moves the root `auth.json` (the default slot) into a newly created named slot,
+/// leaving the root without auth so another account can log in there.
+pub fn promote_default_to_slot(code_home: &Path, label: &str) -> io::Result<AccountSlot> {
+    let root_auth = code_home.join("auth.json");
+    if !root_auth.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "default slot has no auth.json to promote",
+        ));
+    }
+
+    let slot = add_slot(code_home, Some(label))?;
+    if let Err(err) = fs::rename(&root_auth, slot.path.join("auth.json")) {
+        let _ = remove_slot(code_home, &slot.id);
+        return Err(err);
+    }
+
+    Ok(AccountSlot::new(slot.id, slot.label, slot.path, false))
+}
+
 /// Removes a slot directory and registry entry. The default slot cannot be removed.
 pub fn remove_slot(code_home: &Path, slot_id: &str) -> io::Result<Option<AccountSlot>> {
     if slot_id == DEFAULT_SLOT_ID {
@@ -269,6 +302,40 @@ pub fn remove_slot(code_home: &Path, slot_id: &str) -> io::Result<Option<Account
     Ok(Some(AccountSlot::new(entry.id, entry.label, path, false)))
 }
 
+/// Logs a slot out by deleting its `auth.json` and `auth_accounts.json` while keeping
+/// the directory and registry entry. For the default slot only the root `auth.json`
+/// is removed, since the root account store is shared. Returns whether any file was removed.
+pub fn clear_slot_auth(code_home: &Path, slot_id: &str) -> io::Result<bool> {
+    let files: &[&str] = if slot_id == DEFAULT_SLOT_ID {
+        &["auth.json"]
+    } else {
+        &["auth.json", "auth_accounts.json"]
+    };
+    let dir = if slot_id == DEFAULT_SLOT_ID {
+        code_home.to_path_buf()
+    } else {
+        // Persist slots discovered only through their auth file, so they stay listed once it is gone.
+        let mut registry = SlotRegistryFile::load(code_home)?;
+        if registry.hydrate_from_filesystem(code_home)? {
+            registry.save(code_home)?;
+        }
+        let entry = registry.entry(slot_id).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("unknown slot {slot_id}"))
+        })?;
+        resolve_entry_path(entry, code_home)
+    };
+
+    let mut removed = false;
+    for name in files {
+        match fs::remove_file(dir.join(name)) {
+            Ok(()) => removed = true,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(removed)
+}
+
 /// Renames a slot by updating its registry label. Returns the updated slot, if found.
 pub fn rename_slot(code_home: &Path, slot_id: &str, new_label: Option<&str>) -> io::Result<Option<AccountSlot>> {
     if slot_id == DEFAULT_SLOT_ID {
@@ -308,6 +375,65 @@ pub fn slot_auth_dir(code_home: &Path, slot_id: &str) -> io::Result<PathBuf> {
     Ok(path)
 }
 
+/// Reads a slot's auth file and reports which credentials it carries.
+pub fn slot_health(code_home: &Path, slot_id: &str) -> io::Result<SlotHealth> {
+    let dir = if slot_id == DEFAULT_SLOT_ID {
+        code_home.to_path_buf()
+    } else {
+        let mut registry = SlotRegistryFile::load(code_home)?;
+        registry.hydrate_from_filesystem(code_home)?;
+        let entry = registry.entry(slot_id).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("unknown slot {slot_id}"))
+        })?;
+        resolve_entry_path(entry, code_home)
+    };
+
+    let auth_path = dir.join("auth.json");
+    if !auth_path.is_file() {
+        return Ok(SlotHealth::default());
+    }
+    let auth_json = match auth::try_read_auth_json(&auth_path) {
+        Ok(auth_json) => auth_json,
+        Err(err) => {
+            warn!(?auth_path, ?err, "failed to read slot auth file");
+            return Ok(SlotHealth { has_auth_file: true, ..SlotHealth::default() });
+        }
+    };
+
+    let has_api_key = auth_json
+        .openai_api_key
+        .as_deref()
+        .is_some_and(|key| !key.trim().is_empty());
+    let tokens = auth_json.tokens.as_ref();
+    let has_tokens = tokens.is_some_and(|tokens| {
+        !tokens.access_token.trim().is_empty() || !tokens.refresh_token.trim().is_empty()
+    });
+    let email = tokens.and_then(|tokens| tokens.id_token.email.clone());
+    let expired = tokens
+        .and_then(|tokens| {
+            jwt_expiry(&tokens.access_token).or_else(|| jwt_expiry(&tokens.id_token.raw_jwt))
+        })
+        .map(|expiry| expiry <= Utc::now());
+
+    Ok(SlotHealth {
+        has_auth_file: true,
+        has_tokens,
+        has_api_key,
+        email,
+        expired,
+    })
+}
+
+fn jwt_expiry(token: &str) -> Option<DateTime<Utc>> {
+    let payload_b64 = token.split('.').nth(1)?;
+    let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload_b64)
+        .ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+    let exp = claims.get("exp")?.as_i64()?;
+    DateTime::from_timestamp(exp, 0)
+}
+
 pub(crate) fn slot_label(components: &[String]) -> String {
     if components.is_empty() {
         return "account".to_string();
@@ -356,20 +482,43 @@ pub(crate) fn ensure_unique_slot_id(base: &str, seen_ids: &mut HashSet<String>)
 struct SlotDir {
     id: String,
     path: PathBuf,
+    /// Email-derived label; `None` for metadata-only scans.
     label: Option<String>,
+    /// Parsed `auth.json`; `None` for metadata-only scans.
     auth: Option<AuthDotJson>,
     components: Vec<String>,
 }
 
-fn scan_slot_dirs(code_home: &Path) -> io::Result<Vec<SlotDir>> {
+/// How much of each slot's `auth.json` a directory scan reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlotScan {
+    /// Detect slots by the presence of `auth.json` without parsing it; enough
+    /// for ids, paths and registry-backed labels.
+    Metadata,
+    /// Parse every `auth.json` for token data and email-derived labels.
+    Full,
+}
+
+fn scan_slot_dirs(code_home: &Path, scan: SlotScan) -> io::Result<Vec<SlotDir>> {
     let mut slots = Vec::new();
     let mut seen_ids = HashSet::new();
     for root in slot_roots(code_home) {
-        scan_slot_root(&root, Vec::new(), 0, &mut seen_ids, &mut slots)?;
+        scan_slot_root(&root, Vec::new(), 0, scan, &mut seen_ids, &mut slots)?;
     }
     Ok(slots)
 }
 
+#[cfg(test)]
+thread_local! {
+    static SLOT_AUTH_READS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn read_slot_auth(auth_path: &Path) -> io::Result<AuthDotJson> {
+    #[cfg(test)]
+    SLOT_AUTH_READS.with(|reads| reads.set(reads.get() + 1));
+    auth::try_read_auth_json(auth_path)
+}
+
 fn slot_roots(code_home: &Path) -> Vec<PathBuf> {
     fn push_root(roots: &mut Vec<PathBuf>, candidate: PathBuf) {
         if candidate.exists() && !roots.iter().any(|root| root == &candidate) {
@@ -410,6 +559,7 @@ fn scan_slot_root(
     root: &Path,
     components: Vec<String>,
     depth: usize,
+    scan: SlotScan,
     seen_ids: &mut HashSet<String>,
     out: &mut Vec<SlotDir>,
 ) -> io::Result<()> {
@@ -437,7 +587,7 @@ fn scan_slot_root(
         }
         let mut next_components = components.clone();
         next_components.push(name.clone());
-        scan_slot_dir(entry.path(), next_components, depth, seen_ids, out)?;
+        scan_slot_dir(entry.path(), next_components, depth, scan, seen_ids, out)?;
     }
 
     Ok(())
@@ -447,6 +597,7 @@ fn scan_slot_dir(
     path: PathBuf,
     components: Vec<String>,
     depth: usize,
+    scan: SlotScan,
     seen_ids: &mut HashSet<String>,
     out: &mut Vec<SlotDir>,
 ) -> io::Result<()> {
@@ -456,20 +607,23 @@ fn scan_slot_dir(
 
     let auth_path = path.join("auth.json");
     if auth_path.is_file() {
-        match auth::try_read_auth_json(&auth_path) {
-            Ok(auth_json) => {
-                let id = ensure_unique_slot_id(&make_slot_id_slug(&components), seen_ids);
-                let label = derive_label_from_auth(&auth_json, &components);
-                out.push(SlotDir {
-                    id,
-                    path,
-                    label: Some(label),
-                    auth: Some(auth_json),
-                    components,
-                });
-            }
-            Err(err) => warn!(?auth_path, ?err, "failed to read slot auth file"),
-        }
+        // Claim the id before parsing so both scan modes number colliding
+        // slugs the same way, even when a Full scan skips an unreadable slot.
+        let id = ensure_unique_slot_id(&make_slot_id_slug(&components), seen_ids);
+        let auth = match scan {
+            SlotScan::Metadata => None,
+            SlotScan::Full => match read_slot_auth(&auth_path) {
+                Ok(auth_json) => Some(auth_json),
+                Err(err) => {
+                    warn!(?auth_path, ?err, "failed to read slot auth file");
+                    return Ok(());
+                }
+            },
+        };
+        let label = auth
+            .as_ref()
+            .map(|auth_json| derive_label_from_auth(auth_json, &components));
+        out.push(SlotDir { id, path, label, auth, components });
         return Ok(());
     }
 
@@ -498,12 +652,15 @@ fn scan_slot_dir(
         let name = entry.file_name().to_string_lossy().into_owned();
         let mut next_components = components.clone();
         next_components.push(name);
-        scan_slot_dir(entry.path(), next_components, depth + 1, seen_ids, out)?;
+        scan_slot_dir(entry.path(), next_components, depth + 1, scan, seen_ids, out)?;
     }
 
     Ok(())
 }
 
+/// Email of the slot's ChatGPT account, otherwise the generic slot label. The
+/// plan is left out because labels can be persisted; displays append it from
+/// the live tokens instead.
 fn derive_label_from_auth(auth_json: &AuthDotJson, components: &[String]) -> String {
     if let Some(tokens) = auth_json.tokens.as_ref() {
         if let Some(email) = tokens.id_token.email.as_deref() {
@@ -524,7 +681,7 @@ pub(crate) fn discover_slot_accounts(code_home: &Path) -> io::Result<Vec<StoredA
     let mut accounts = Vec::new();
     let mut seen_ids = HashSet::new();
 
-    for mut slot in scan_slot_dirs(code_home)? {
+    for mut slot in scan_slot_dirs(code_home, SlotScan::Full)? {
         let Some(auth_json) = slot.auth else {
             continue;
         };
@@ -533,6 +690,7 @@ pub(crate) fn discover_slot_accounts(code_home: &Path) -> io::Result<Vec<StoredA
         }
         let id = slot.id.clone();
         let mut account = stored_account_from_auth(&id, auth_json, slot.label.clone(), slot.components.clone());
+        account.created_at = auth_file_modified_at(&slot.path.join("auth.json"));
         if let Some(label) = overrides.get(&id).and_then(|value| value.clone()) {
             account.label = Some(label);
         }
@@ -555,6 +713,61 @@ pub(crate) fn discover_slot_accounts(code_home: &Path) -> io::Result<Vec<StoredA
     Ok(accounts)
 }
 
+/// Maps each slot directory (and the code home, for the default slot) to the
+/// email of the ChatGPT account stored there. Slots without an email are omitted.
+pub fn slot_account_emails(code_home: &Path) -> io::Result<HashMap<PathBuf, String>> {
+    fn auth_email(auth_json: &AuthDotJson) -> Option<String> {
+        let email = auth_json.tokens.as_ref()?.id_token.email.as_deref()?.trim();
+        (!email.is_empty()).then(|| email.to_string())
+    }
+
+    let mut emails = HashMap::new();
+    for slot in scan_slot_dirs(code_home, SlotScan::Full)? {
+        if let Some(email) = slot.auth.as_ref().and_then(auth_email) {
+            emails.insert(slot.path, email);
+        }
+    }
+    if let Some(email) = auth::load_default_chatgpt_auth(code_home)?
+        .as_ref()
+        .and_then(auth_email)
+    {
+        emails.insert(code_home.to_path_buf(), email);
+    }
+    Ok(emails)
+}
+
+/// Groups slot accounts that hold the same ChatGPT credentials (matching
+/// account id and email). Each entry maps an `account_id:email` identity to
+/// the slot ids sharing it; only identities seen in more than one slot are returned.
+pub fn find_duplicate_accounts(code_home: &Path) -> io::Result<Vec<(String, Vec<String>)>> {
+    let mut groups: Vec<(&StoredAccount, Vec<String>)> = Vec::new();
+    let accounts = discover_slot_accounts(code_home)?;
+    for account in &accounts {
+        let Some(tokens) = account.tokens.as_ref() else {
+            continue;
+        };
+        match groups
+            .iter_mut()
+            .find(|(representative, _)| match_chatgpt_account(representative, tokens))
+        {
+            Some((_, ids)) => ids.push(account.id.clone()),
+            None if account.mode == AuthMode::ChatGPT => groups.push((account, vec![account.id.clone()])),
+            None => {}
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .filter_map(|(representative, ids)| {
+            let tokens = representative.tokens.as_ref()?;
+            let account_id = tokens.account_id.as_deref()?;
+            let email = tokens.id_token.email.as_deref()?;
+            Some((format!("{account_id}:{}", normalize_email(email)), ids))
+        })
+        .collect())
+}
+
 fn stored_account_from_auth(
     id: &str,
     auth_json: AuthDotJson,
@@ -578,10 +791,18 @@ fn stored_account_from_auth(
         tokens,
         last_refresh: auth_json.last_refresh,
         created_at: None,
-        last_used_at: None,
+        last_used_at: auth_json.last_refresh,
     }
 }
 
+/// Slots carry no account metadata of their own, so the auth.json mtime
+/// stands in for when the credentials were stored. Birth time is not used
+/// because it is missing on some filesystems and lost when slots are copied.
+fn auth_file_modified_at(auth_path: &Path) -> Option<DateTime<Utc>> {
+    let modified = fs::metadata(auth_path).and_then(|meta| meta.modified()).ok()?;
+    Some(DateTime::<Utc>::from(modified))
+}
+
 fn load_default_slot_account(code_home: &Path) -> io::Result<Option<StoredAccount>> {
     let Some(auth_json) = auth::load_default_chatgpt_auth(code_home)? else {
         return Ok(None);
@@ -597,6 +818,7 @@ fn load_default_slot_account(code_home: &Path) -> io::Result<Option<StoredAccoun
         vec!["default".to_string()],
     );
     account.label = Some(label);
+    account.created_at = auth_file_modified_at(&auth::get_auth_file(code_home));
     Ok(Some(account))
 }
 
@@ -670,6 +892,86 @@ mod tests {
         assert!(slots.iter().any(|slot| slot.id == created.id));
     }
 
+    #[test]
+    fn list_slots_reads_no_auth_payloads() {
+        let home = tempdir().expect("tempdir");
+        for idx in 0..12 {
+            let slot_dir = home.path().join("slot").join(format!("acct-{idx}"));
+            fs::create_dir_all(&slot_dir).expect("slot dir");
+            let auth = AuthDotJson {
+                openai_api_key: None,
+                tokens: Some(fake_tokens(&format!("acct-{idx}"), &format!("user{idx}@example.com"))),
+                last_refresh: Some(Utc::now()),
+            };
+            write_auth_json(&slot_dir.join("auth.json"), &auth).expect("write auth");
+        }
+        let reads = || SLOT_AUTH_READS.with(std::cell::Cell::get);
+
+        let before = reads();
+        let slots = list_slots(home.path()).expect("list");
+        assert_eq!(reads(), before);
+        assert_eq!(slots.iter().filter(|slot| slot.has_auth_file).count(), 12);
+        assert!(slots.iter().all(|slot| slot.is_default || slot.label.is_none()));
+
+        let accounts = discover_slot_accounts(home.path()).expect("discover");
+        assert_eq!(reads(), before + 12);
+        let labeled = accounts
+            .iter()
+            .filter(|account| account.label.as_deref().is_some_and(|label| label.contains("@example.com")))
+            .count();
+        assert_eq!(labeled, 12);
+    }
+
+    #[test]
+    fn metadata_and_full_scans_assign_the_same_ids() {
+        let home = tempdir().expect("tempdir");
+        // Both directories slug to `slot-slot-a`; one holds an unreadable auth file.
+        let broken_dir = home.path().join("slot_a");
+        fs::create_dir_all(&broken_dir).expect("broken slot dir");
+        fs::write(broken_dir.join("auth.json"), "{not json").expect("write broken auth");
+        let valid_dir = home.path().join("slot-a");
+        fs::create_dir_all(&valid_dir).expect("valid slot dir");
+        let auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(fake_tokens("acct-a", "a@example.com")),
+            last_refresh: Some(Utc::now()),
+        };
+        write_auth_json(&valid_dir.join("auth.json"), &auth).expect("write auth");
+
+        let metadata = scan_slot_dirs(home.path(), SlotScan::Metadata).expect("metadata scan");
+        let full = scan_slot_dirs(home.path(), SlotScan::Full).expect("full scan");
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(full.len(), 1);
+        let metadata_id = metadata
+            .iter()
+            .find(|slot| slot.path == full[0].path)
+            .map(|slot| slot.id.as_str());
+        assert_eq!(metadata_id, Some(full[0].id.as_str()));
+    }
+
+    #[test]
+    fn slot_plan_is_read_from_live_tokens_not_the_label() {
+        let mut tokens = fake_tokens("acct-pro", "slot@example.com");
+        tokens.id_token = crate::token_data::parse_id_token(&tokens.id_token.raw_jwt).expect("parse jwt");
+        let mut auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(tokens),
+            last_refresh: None,
+        };
+        let components = vec!["work".to_string()];
+        assert_eq!(derive_label_from_auth(&auth, &components), "slot@example.com");
+
+        let account = stored_account_from_auth("slot-work", auth.clone(), None, components.clone());
+        assert_eq!(account.label.as_deref(), Some("slot@example.com"));
+        assert_eq!(account.chatgpt_plan().as_deref(), Some("Pro"));
+
+        if let Some(tokens) = auth.tokens.as_mut() {
+            tokens.id_token.chatgpt_plan_type = None;
+        }
+        let account = stored_account_from_auth("slot-work", auth, None, components);
+        assert_eq!(account.chatgpt_plan(), None);
+    }
+
     #[test]
     fn rename_slot_updates_registry() {
         let home = tempdir().expect("tempdir");
@@ -693,6 +995,166 @@ mod tests {
         assert!(!dir.exists());
     }
 
+    #[test]
+    fn clear_slot_auth_keeps_slot_listed_without_auth() {
+        let home = tempdir().expect("tempdir");
+        let created = add_slot(home.path(), Some("Work")).expect("add slot");
+        let auth_path = created.path.join("auth.json");
+        let auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(fake_tokens("acct-clear", "clear@example.com")),
+            last_refresh: Some(Utc::now()),
+        };
+        write_auth_json(&auth_path, &auth).expect("write auth");
+        fs::write(created.path.join("auth_accounts.json"), "{}").expect("write accounts");
+
+        assert!(clear_slot_auth(home.path(), &created.id).expect("clear"));
+        assert!(!auth_path.exists());
+        assert!(!created.path.join("auth_accounts.json").exists());
+        assert!(created.path.is_dir());
+
+        let slots = list_slots(home.path()).expect("list");
+        let slot = slots.iter().find(|slot| slot.id == created.id).expect("slot still listed");
+        assert!(!slot.has_auth_file);
+        assert_eq!(slot.label.as_deref(), Some("Work"));
+
+        assert!(!clear_slot_auth(home.path(), &created.id).expect("clear again"));
+        let err = clear_slot_auth(home.path(), "slot-missing").expect_err("unknown slot");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        write_auth_json(&home.path().join("auth.json"), &auth).expect("write root auth");
+        assert!(clear_slot_auth(home.path(), DEFAULT_SLOT_ID).expect("clear default"));
+        assert!(!home.path().join("auth.json").exists());
+    }
+
+    #[test]
+    fn promote_default_moves_root_auth_into_named_slot() {
+        let home = tempdir().expect("tempdir");
+        let root_auth = home.path().join("auth.json");
+        let auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(fake_tokens("acct-root", "root@example.com")),
+            last_refresh: Some(Utc::now()),
+        };
+        write_auth_json(&root_auth, &auth).expect("write auth");
+
+        let slot = promote_default_to_slot(home.path(), "Personal").expect("promote");
+        assert!(!root_auth.exists());
+        assert!(slot.has_auth_file);
+        assert_eq!(slot.label.as_deref(), Some("Personal"));
+
+        let moved = auth::try_read_auth_json(&slot.path.join("auth.json")).expect("read moved auth");
+        let tokens = moved.tokens.expect("tokens");
+        assert_eq!(tokens.account_id.as_deref(), Some("acct-root"));
+
+        let slots = list_slots(home.path()).expect("list");
+        assert!(slots.iter().any(|listed| listed.id == slot.id && listed.has_auth_file));
+        let default = slots.iter().find(|listed| listed.is_default).expect("default slot");
+        assert!(!default.has_auth_file);
+
+        let err = promote_default_to_slot(home.path(), "Again").expect_err("nothing to promote");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn slot_health_reports_token_and_api_key_slots() {
+        let home = tempdir().expect("tempdir");
+
+        let token_slot = add_slot(home.path(), Some("Tokens".into())).expect("add slot");
+        let mut tokens = fake_tokens("acct-health", "health@example.com");
+        let payload = serde_json::json!({ "exp": Utc::now().timestamp() + 3600 });
+        let payload_b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&payload).expect("payload"));
+        tokens.access_token = format!("e30.{payload_b64}.sig");
+        let auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(tokens),
+            last_refresh: Some(Utc::now()),
+        };
+        write_auth_json(&token_slot.path.join("auth.json"), &auth).expect("write auth");
+
+        let health = slot_health(home.path(), &token_slot.id).expect("health");
+        assert!(health.has_auth_file);
+        assert!(health.has_tokens);
+        assert!(!health.has_api_key);
+        assert_eq!(health.email.as_deref(), Some("health@example.com"));
+        assert_eq!(health.expired, Some(false));
+
+        let key_slot = add_slot(home.path(), Some("Key".into())).expect("add slot");
+        let auth = AuthDotJson {
+            openai_api_key: Some("sk-test".to_string()),
+            tokens: None,
+            last_refresh: None,
+        };
+        write_auth_json(&key_slot.path.join("auth.json"), &auth).expect("write auth");
+
+        let health = slot_health(home.path(), &key_slot.id).expect("health");
+        assert!(health.has_auth_file);
+        assert!(!health.has_tokens);
+        assert!(health.has_api_key);
+        assert_eq!(health.email, None);
+        assert_eq!(health.expired, None);
+    }
+
+    #[test]
+    fn slot_account_emails_maps_slot_dirs_to_emails() {
+        let home = tempdir().expect("tempdir");
+        for (name, email) in [("work", "work@example.com"), ("personal", "me@example.com")] {
+            let slot_dir = home.path().join("slot").join(name);
+            fs::create_dir_all(&slot_dir).expect("slot dir");
+            let auth = AuthDotJson {
+                openai_api_key: None,
+                tokens: Some(fake_tokens(&format!("acct-{name}"), email)),
+                last_refresh: None,
+            };
+            write_auth_json(&slot_dir.join("auth.json"), &auth).expect("write auth");
+        }
+        // The default slot has no auth, so the code home is not mapped.
+        let emails = slot_account_emails(home.path()).expect("emails");
+
+        assert_eq!(emails.len(), 2);
+        assert_eq!(
+            emails.get(&home.path().join("slot").join("work")).map(String::as_str),
+            Some("work@example.com")
+        );
+        assert_eq!(
+            emails.get(&home.path().join("slot").join("personal")).map(String::as_str),
+            Some("me@example.com")
+        );
+    }
+
+    #[test]
+    fn find_duplicate_accounts_reports_shared_credentials() {
+        let home = tempdir().expect("tempdir");
+        let tokens = fake_tokens("acct-dup", "Dup@Example.com");
+        let mut slot_ids = Vec::new();
+        for label in ["First", "Second"] {
+            let slot = add_slot(home.path(), Some(label)).expect("add slot");
+            let auth = AuthDotJson {
+                openai_api_key: None,
+                tokens: Some(tokens.clone()),
+                last_refresh: Some(Utc::now()),
+            };
+            write_auth_json(&slot.path.join("auth.json"), &auth).expect("write auth");
+            slot_ids.push(slot.id);
+        }
+        let unique = add_slot(home.path(), Some("Unique")).expect("add slot");
+        let auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(fake_tokens("acct-unique", "unique@example.com")),
+            last_refresh: Some(Utc::now()),
+        };
+        write_auth_json(&unique.path.join("auth.json"), &auth).expect("write auth");
+
+        let duplicates = find_duplicate_accounts(home.path()).expect("duplicates");
+        assert_eq!(duplicates.len(), 1);
+        let (identity, mut ids) = duplicates.into_iter().next().expect("duplicate group");
+        assert_eq!(identity, "acct-dup:dup@example.com");
+        ids.sort();
+        slot_ids.sort();
+        assert_eq!(ids, slot_ids);
+    }
+
     #[test]
     fn discover_slot_accounts_uses_custom_labels() {
         let home = tempdir().expect("tempdir");
@@ -709,4 +1171,26 @@ mod tests {
         let slot_account = accounts.iter().find(|acc| acc.id == created.id).expect("slot account");
         assert_eq!(slot_account.label.as_deref(), Some("My Slot"));
     }
+
+    #[test]
+    fn slot_accounts_report_auth_file_times() {
+        let home = tempdir().expect("tempdir");
+        let created = add_slot(home.path(), Some("Work".into())).expect("add slot");
+        let auth_path = created.path.join("auth.json");
+        let refreshed_at = Utc::now() - chrono::Duration::hours(3);
+        let auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(fake_tokens("acct-times", "times@example.com")),
+            last_refresh: Some(refreshed_at),
+        };
+        write_auth_json(&auth_path, &auth).expect("write auth");
+        let mtime = fs::metadata(&auth_path)
+            .and_then(|meta| meta.modified())
+            .expect("auth mtime");
+
+        let accounts = discover_slot_accounts(home.path()).expect("discover");
+        let slot_account = accounts.iter().find(|acc| acc.id == created.id).expect("slot account");
+        assert_eq!(slot_account.created_at, Some(DateTime::<Utc>::from(mtime)));
+        assert_eq!(slot_account.last_used_at, Some(refreshed_at));
+    }
 }
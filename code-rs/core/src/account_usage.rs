@@ -692,6 +692,26 @@ pub fn list_rate_limit_snapshots(
     Ok(results)
 }
 
+/// Age of each account's latest rate-limit snapshot at `now`, sorted by
+/// account id. Covers every stored account plus any account with a usage file;
+/// `None` means no snapshot was ever recorded, so the scheduler is working
+/// without limit data for it.
+pub fn snapshot_ages(
+    code_home: &Path,
+    now: DateTime<Utc>,
+) -> std::io::Result<Vec<(String, Option<Duration>)>> {
+    let accounts = crate::auth_accounts::list_accounts(code_home)?;
+    let mut ages: BTreeMap<String, Option<Duration>> =
+        accounts.into_iter().map(|account| (account.id, None)).collect();
+    for snapshot in list_rate_limit_snapshots(code_home)? {
+        let age = snapshot
+            .observed_at
+            .map(|observed_at| (now - observed_at).max(Duration::zero()));
+        ages.insert(snapshot.account_id, age);
+    }
+    Ok(ages.into_iter().collect())
+}
+
 pub fn record_usage_limit_hint(
     code_home: &Path,
     account_id: &str,
@@ -1069,6 +1089,46 @@ mod tests {
         assert_eq!(snapshot.secondary_next_reset_at, Some(expected_reset));
     }
 
+    #[test]
+    fn snapshot_ages_report_fresh_and_missing_snapshots() {
+        let home = TempDir::new().expect("tempdir");
+        let now = Utc::now();
+        let snapshot = RateLimitSnapshotEvent {
+            primary_used_percent: 10.0,
+            secondary_used_percent: 5.0,
+            primary_to_secondary_ratio_percent: 50.0,
+            primary_window_minutes: 300,
+            secondary_window_minutes: 10080,
+            primary_reset_after_seconds: Some(600),
+            secondary_reset_after_seconds: Some(3600),
+            account_id: None,
+        };
+        record_rate_limit_snapshot(
+            home.path(),
+            "acct-fresh",
+            None,
+            &snapshot,
+            now - Duration::minutes(5),
+        )
+        .expect("snapshot recorded");
+        let stored = crate::auth_accounts::upsert_api_key_account(
+            home.path(),
+            "sk-test-missing".to_string(),
+            None,
+            false,
+        )
+        .expect("account stored");
+
+        let ages: BTreeMap<String, Option<Duration>> = snapshot_ages(home.path(), now)
+            .expect("snapshot ages")
+            .into_iter()
+            .collect();
+
+        assert_eq!(ages.len(), 2);
+        assert_eq!(ages.get("acct-fresh"), Some(&Some(Duration::minutes(5))));
+        assert_eq!(ages.get(&stored.id), Some(&None));
+    }
+
     #[test]
     fn token_usage_compacts_old_hourly_entries_into_buckets() {
         let home = TempDir::new().expect("tempdir");
@@ -1,3 +1,4 @@
+use caseless::default_case_fold_str;
 use chrono::{DateTime, Utc};
 use code_app_server_protocol::AuthMode;
 use serde::{Deserialize, Serialize};
@@ -5,6 +6,7 @@ use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use tracing::warn;
+use unicode_normalization::UnicodeNormalization;
 use uuid::Uuid;
 
 use crate::account_slots;
@@ -36,6 +38,17 @@ pub struct StoredAccount {
     pub last_used_at: Option<DateTime<Utc>>,
 }
 
+impl StoredAccount {
+    /// ChatGPT plan named by the account's current id token, e.g. `Pro`.
+    pub fn chatgpt_plan(&self) -> Option<String> {
+        self.tokens
+            .as_ref()?
+            .id_token
+            .get_chatgpt_plan_type()
+            .filter(|plan| !plan.trim().is_empty())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 struct AccountsFile {
     #[serde(default = "default_version")]
@@ -100,8 +113,12 @@ fn write_accounts_file(path: &Path, data: &AccountsFile) -> io::Result<()> {
     Ok(())
 }
 
-fn normalize_email(email: &str) -> String {
-    email.trim().to_ascii_lowercase()
+/// Canonical form for comparing account emails: trimmed, NFC-normalized and
+/// Unicode case folded, so composed/decomposed or differently cased spellings
+/// of the same address (including `ß` and `SS`) compare equal.
+pub(crate) fn normalize_email(email: &str) -> String {
+    let composed: String = email.trim().nfc().collect();
+    default_case_fold_str(&composed).nfc().collect()
 }
 
 fn now() -> DateTime<Utc> {
@@ -112,7 +129,7 @@ fn next_id() -> String {
     Uuid::new_v4().to_string()
 }
 
-fn match_chatgpt_account(existing: &StoredAccount, tokens: &TokenData) -> bool {
+pub(crate) fn match_chatgpt_account(existing: &StoredAccount, tokens: &TokenData) -> bool {
     if existing.mode != AuthMode::ChatGPT {
         return false;
     }
@@ -201,38 +218,85 @@ pub fn list_accounts(code_home: &Path) -> io::Result<Vec<StoredAccount>> {
     let data = read_accounts_file(&path)?;
     let mut accounts = data.accounts;
     match account_slots::discover_slot_accounts(code_home) {
-        Ok(mut slots) => accounts.append(&mut slots),
+        Ok(slots) => {
+            // Slots promoted into the accounts file keep their id but no
+            // credentials; those come from the slot's auth.json, and entries
+            // for slots that are gone or logged out are dropped.
+            accounts.retain(|acc| {
+                !is_slot_account_id(&acc.id) || slots.iter().any(|slot| slot.id == acc.id)
+            });
+            for slot in slots {
+                match accounts.iter_mut().find(|acc| acc.id == slot.id) {
+                    Some(promoted) => apply_slot_credentials(promoted, slot),
+                    None => accounts.push(slot),
+                }
+            }
+        }
         Err(err) => warn!(?err, "failed to load slot-based accounts"),
     }
     Ok(accounts)
 }
 
+fn is_slot_account_id(id: &str) -> bool {
+    id.strip_prefix(account_slots::SLOT_PREFIX)
+        .is_some_and(|rest| rest.starts_with('-'))
+}
+
+/// Fills a promoted slot account from the slot's live `auth.json`, keeping
+/// the accounts file's usage timestamps.
+fn apply_slot_credentials(promoted: &mut StoredAccount, slot: StoredAccount) {
+    promoted.mode = slot.mode;
+    promoted.label = slot.label;
+    promoted.openai_api_key = slot.openai_api_key;
+    promoted.tokens = slot.tokens;
+    promoted.last_refresh = slot.last_refresh;
+}
+
+fn strip_credentials(account: &mut StoredAccount) {
+    account.openai_api_key = None;
+    account.tokens = None;
+}
+
 pub fn get_active_account_id(code_home: &Path) -> io::Result<Option<String>> {
     let path = accounts_file_path(code_home);
     let data = read_accounts_file(&path)?;
     Ok(data.active_account_id)
 }
 
-pub fn find_account(code_home: &Path, account_id: &str) -> io::Result<Option<StoredAccount>> {
-    let path = accounts_file_path(code_home);
-    let data = read_accounts_file(&path)?;
-    if let Some(account) = data
-        .accounts
-        .into_iter()
-        .find(|acc| acc.id == account_id)
-    {
-        return Ok(Some(account));
+/// Resolves the active account id to its stored account. Returns `None` when
+/// no account is active, the id no longer resolves, or the account has been
+/// emptied of its credentials, so callers never pick up a dead account.
+pub fn get_active_account(code_home: &Path) -> io::Result<Option<StoredAccount>> {
+    let Some(active_id) = get_active_account_id(code_home)? else {
+        return Ok(None);
+    };
+    let Some(account) = find_account(code_home, &active_id)? else {
+        return Ok(None);
+    };
+    if !has_credentials(&account) {
+        warn!(account_id = %account.id, "active account has no credentials");
+        return Ok(None);
     }
+    Ok(Some(account))
+}
 
-    match account_slots::discover_slot_accounts(code_home) {
-        Ok(slots) => Ok(slots.into_iter().find(|acc| acc.id == account_id)),
-        Err(err) => {
-            warn!(?err, "failed to load slot-based accounts");
-            Ok(None)
-        }
+/// Whether the account holds usable credentials; a blank API key counts as missing.
+pub(crate) fn has_credentials(account: &StoredAccount) -> bool {
+    match account.mode {
+        AuthMode::ApiKey => account
+            .openai_api_key
+            .as_deref()
+            .is_some_and(|key| !key.trim().is_empty()),
+        AuthMode::ChatGPT => account.tokens.is_some(),
     }
 }
 
+pub fn find_account(code_home: &Path, account_id: &str) -> io::Result<Option<StoredAccount>> {
+    Ok(list_accounts(code_home)?
+        .into_iter()
+        .find(|acc| acc.id == account_id))
+}
+
 pub fn set_active_account_id(
     code_home: &Path,
     account_id: Option<String>,
@@ -257,6 +321,43 @@ pub fn set_active_account_id(
     }
 }
 
+/// Switch the active account, validating the id against file and slot accounts.
+///
+/// Slot-only accounts are promoted into the accounts file so the active id
+/// always refers to a persisted entry. The promoted entry keeps the slot's
+/// id, label and timestamps only; credentials stay in the slot's `auth.json`.
+pub fn activate_account(code_home: &Path, account_id: &str) -> io::Result<StoredAccount> {
+    let path = accounts_file_path(code_home);
+    let mut data = read_accounts_file(&path)?;
+
+    let idx = match data.accounts.iter().position(|acc| acc.id == account_id) {
+        Some(idx) => idx,
+        None => {
+            let slot_account = account_slots::discover_slot_accounts(code_home)?
+                .into_iter()
+                .find(|acc| acc.id == account_id)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("account {account_id} not found"),
+                    )
+                })?;
+            data.accounts.push(slot_account);
+            data.accounts.len() - 1
+        }
+    };
+
+    data.active_account_id = Some(account_id.to_string());
+    let account = &mut data.accounts[idx];
+    if is_slot_account_id(&account.id) {
+        strip_credentials(account);
+    }
+    touch_account(account, true);
+    let activated = account.clone();
+    write_accounts_file(&path, &data)?;
+    Ok(find_account(code_home, account_id)?.unwrap_or(activated))
+}
+
 pub fn remove_account(code_home: &Path, account_id: &str) -> io::Result<Option<StoredAccount>> {
     let path = accounts_file_path(code_home);
     let mut data = read_accounts_file(&path)?;
@@ -279,6 +380,28 @@ pub fn remove_account(code_home: &Path, account_id: &str) -> io::Result<Option<S
     Ok(removed)
 }
 
+/// Set or clear (`None` or blank) the label of an account in the accounts
+/// file. Slot-backed accounts are labelled through the slot registry instead,
+/// so ids not present in the file return `Ok(None)` without writing.
+pub fn rename_account(
+    code_home: &Path,
+    account_id: &str,
+    label: Option<String>,
+) -> io::Result<Option<StoredAccount>> {
+    let path = accounts_file_path(code_home);
+    let mut data = read_accounts_file(&path)?;
+
+    let Some(account) = data.accounts.iter_mut().find(|acc| acc.id == account_id) else {
+        return Ok(None);
+    };
+    account.label = label
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let renamed = account.clone();
+    write_accounts_file(&path, &data)?;
+    Ok(Some(renamed))
+}
+
 pub fn upsert_api_key_account(
     code_home: &Path,
     api_key: String,
@@ -455,6 +578,32 @@ mod tests {
         assert_eq!(accounts[0].id, stored.id);
     }
 
+    #[test]
+    fn upsert_chatgpt_dedupes_unicode_equivalent_emails() {
+        let home = tempdir().expect("tempdir");
+        // Precomposed "é" vs. uppercase "E" followed by a combining acute accent.
+        let composed = make_chatgpt_tokens(Some("acct-1"), Some("jos\u{e9}@example.com"));
+        let decomposed = make_chatgpt_tokens(Some("acct-1"), Some(" JOSE\u{301}@Example.COM"));
+        assert_eq!(
+            normalize_email("jos\u{e9}@example.com"),
+            normalize_email(" JOSE\u{301}@Example.COM")
+        );
+
+        let first = upsert_chatgpt_account(home.path(), composed, Utc::now(), None, true)
+            .expect("insert composed");
+        let second = upsert_chatgpt_account(home.path(), decomposed, Utc::now(), None, false)
+            .expect("insert decomposed");
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(list_accounts(home.path()).expect("list accounts").len(), 1);
+    }
+
+    #[test]
+    fn normalize_email_case_folds_sharp_s() {
+        assert_eq!(normalize_email("Stra\u{df}e@x"), normalize_email("STRASSE@x"));
+        assert_eq!(normalize_email("STRASSE@x"), "strasse@x");
+    }
+
     #[test]
     fn chatgpt_accounts_with_same_email_but_different_ids_are_distinct() {
         let home = tempdir().expect("tempdir");
@@ -487,6 +636,26 @@ mod tests {
         assert_eq!(accounts.len(), 2, "both accounts should remain listed");
     }
 
+    #[test]
+    fn active_account_without_credentials_is_none() {
+        let home = tempdir().expect("tempdir");
+        let stored = upsert_api_key_account(home.path(), "sk-active".to_string(), None, true)
+            .expect("insert api key");
+        let active = get_active_account(home.path()).expect("active account");
+        assert_eq!(active.map(|account| account.id), Some(stored.id.clone()));
+
+        let path = accounts_file_path(home.path());
+        let mut data = read_accounts_file(&path).expect("read accounts");
+        data.accounts[0].openai_api_key = None;
+        write_accounts_file(&path, &data).expect("write accounts");
+
+        assert_eq!(
+            get_active_account_id(home.path()).expect("active id").as_deref(),
+            Some(stored.id.as_str())
+        );
+        assert!(get_active_account(home.path()).expect("active account").is_none());
+    }
+
     #[test]
     fn remove_account_clears_active() {
         let home = tempdir().expect("tempdir");
@@ -510,6 +679,155 @@ mod tests {
         assert!(active_after.is_none());
     }
 
+    #[test]
+    fn rename_account_sets_and_clears_label() {
+        let home = tempdir().expect("tempdir");
+        let stored = upsert_api_key_account(home.path(), "sk-rename".to_string(), None, false)
+            .expect("insert api key");
+
+        let renamed = rename_account(home.path(), &stored.id, Some("  Work key ".to_string()))
+            .expect("rename")
+            .expect("file account");
+        assert_eq!(renamed.label.as_deref(), Some("Work key"));
+        let persisted = find_account(home.path(), &stored.id)
+            .expect("find")
+            .expect("account present");
+        assert_eq!(persisted.label.as_deref(), Some("Work key"));
+
+        rename_account(home.path(), &stored.id, None).expect("clear label");
+        let cleared = find_account(home.path(), &stored.id)
+            .expect("find")
+            .expect("account present");
+        assert!(cleared.label.is_none());
+
+        assert!(rename_account(home.path(), "slot-missing", Some("x".to_string()))
+            .expect("rename missing")
+            .is_none());
+    }
+
+    #[test]
+    fn activate_account_switches_file_account() {
+        let home = tempdir().expect("tempdir");
+        let first = upsert_api_key_account(home.path(), "sk-first".to_string(), None, true)
+            .expect("insert first");
+        let second = upsert_api_key_account(home.path(), "sk-second".to_string(), None, false)
+            .expect("insert second");
+
+        let activated = activate_account(home.path(), &second.id).expect("activate");
+        assert_eq!(activated.id, second.id);
+        assert!(activated.last_used_at.is_some());
+
+        let active = get_active_account_id(home.path()).expect("active id");
+        assert_eq!(active.as_deref(), Some(second.id.as_str()));
+        assert_ne!(active.as_deref(), Some(first.id.as_str()));
+
+        let err = activate_account(home.path(), "missing").expect_err("unknown id");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let active = get_active_account_id(home.path()).expect("active id");
+        assert_eq!(active.as_deref(), Some(second.id.as_str()));
+    }
+
+    #[test]
+    fn activate_account_promotes_slot_account() {
+        let home = tempdir().expect("tempdir");
+        let slot_dir = home.path().join("slot-one");
+        std::fs::create_dir_all(&slot_dir).expect("slot dir");
+        let auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(make_chatgpt_tokens(Some("acct-slot"), Some("slot@example.com"))),
+            last_refresh: Some(Utc::now()),
+        };
+        write_auth_json(&slot_dir.join("auth.json"), &auth).expect("write auth");
+
+        let slot_id = list_accounts(home.path())
+            .expect("list")
+            .into_iter()
+            .find(|acc| acc.id.starts_with("slot-") && acc.id != "slot-default")
+            .expect("missing slot account")
+            .id;
+
+        let activated = activate_account(home.path(), &slot_id).expect("activate slot");
+        assert_eq!(activated.id, slot_id);
+        assert!(activated.created_at.is_some());
+
+        let active = get_active_account_id(home.path()).expect("active id");
+        assert_eq!(active.as_deref(), Some(slot_id.as_str()));
+
+        let stored = read_accounts_file(&accounts_file_path(home.path())).expect("read file");
+        assert!(stored.accounts.iter().any(|acc| acc.id == slot_id));
+
+        let listed = list_accounts(home.path()).expect("list");
+        assert_eq!(listed.iter().filter(|acc| acc.id == slot_id).count(), 1);
+    }
+
+    #[test]
+    fn promoted_slot_entry_holds_no_credentials() {
+        let home = tempdir().expect("tempdir");
+        let slot = account_slots::add_slot(home.path(), Some("work")).expect("add slot");
+        let auth = AuthDotJson {
+            openai_api_key: Some("sk-slot".to_string()),
+            tokens: Some(make_chatgpt_tokens(Some("acct-slot"), Some("slot@example.com"))),
+            last_refresh: Some(Utc::now()),
+        };
+        write_auth_json(&slot.path.join("auth.json"), &auth).expect("write auth");
+
+        let activated = activate_account(home.path(), &slot.id).expect("activate slot");
+        assert!(activated.tokens.is_some());
+
+        let stored = read_accounts_file(&accounts_file_path(home.path())).expect("read file");
+        let promoted = stored
+            .accounts
+            .iter()
+            .find(|acc| acc.id == slot.id)
+            .expect("promoted entry");
+        assert_eq!(promoted.label, activated.label);
+        assert!(promoted.created_at.is_some());
+        assert!(promoted.last_used_at.is_some());
+        assert!(promoted.tokens.is_none());
+        assert!(promoted.openai_api_key.is_none());
+    }
+
+    #[test]
+    fn promoted_slot_account_follows_live_slot_auth() {
+        let home = tempdir().expect("tempdir");
+        let slot = account_slots::add_slot(home.path(), Some("work")).expect("add slot");
+        let mut auth = AuthDotJson {
+            openai_api_key: None,
+            tokens: Some(make_chatgpt_tokens(Some("acct-slot"), Some("slot@example.com"))),
+            last_refresh: Some(Utc::now()),
+        };
+        write_auth_json(&slot.path.join("auth.json"), &auth).expect("write auth");
+        activate_account(home.path(), &slot.id).expect("activate slot");
+
+        if let Some(tokens) = auth.tokens.as_mut() {
+            tokens.access_token = "refreshed".to_string();
+        }
+        write_auth_json(&slot.path.join("auth.json"), &auth).expect("rewrite auth");
+
+        let active = get_active_account(home.path())
+            .expect("active account")
+            .expect("slot account is active");
+        assert_eq!(active.id, slot.id);
+        assert_eq!(
+            active.tokens.as_ref().map(|tokens| tokens.access_token.as_str()),
+            Some("refreshed")
+        );
+        assert!(active.last_used_at.is_some());
+        let listed = list_accounts(home.path()).expect("list");
+        let listed_slot = listed
+            .iter()
+            .find(|acc| acc.id == slot.id)
+            .expect("slot listed once");
+        assert_eq!(
+            listed_slot.tokens.as_ref().map(|tokens| tokens.access_token.as_str()),
+            Some("refreshed")
+        );
+
+        account_slots::remove_slot(home.path(), &slot.id).expect("remove slot");
+        assert!(list_accounts(home.path()).expect("list").iter().all(|acc| acc.id != slot.id));
+        assert!(get_active_account(home.path()).expect("active account").is_none());
+    }
+
     #[test]
     fn list_accounts_includes_slot_directories() {
         let home = tempdir().expect("tempdir");
@@ -729,6 +729,28 @@ mod tests {
     use code_protocol::models::ContentItem;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn filter_cancellable_drops_finished_tasks() {
+        let task = |id: &str, kind: RunningTaskKind, can_cancel: bool| RunningTaskInfo {
+            id: id.to_string(),
+            sub_id: None,
+            kind,
+            label: id.to_string(),
+            command_line: Vec::new(),
+            started_at_ms: 0,
+            can_cancel,
+        };
+        let tasks = vec![
+            task("agent", RunningTaskKind::Agent, true),
+            task("exec-done", RunningTaskKind::ForegroundExec, false),
+            task("bg-live", RunningTaskKind::BackgroundExec, true),
+            task("bg-detached", RunningTaskKind::BackgroundExec, false),
+        ];
+
+        let ids: Vec<String> = filter_cancellable(tasks).into_iter().map(|task| task.id).collect();
+        assert_eq!(ids, vec!["agent".to_string(), "bg-live".to_string()]);
+    }
+
     #[test]
     fn screenshot_dedup_tracks_changes() {
         let mut last = None;
@@ -3599,7 +3621,7 @@ impl Session {
         }
     }
 
-    pub(crate) async fn send_running_tasks_snapshot(&self, sub_id: String) {
+    pub(crate) async fn send_running_tasks_snapshot(&self, sub_id: String, cancellable_only: bool) {
         let tasks = {
             let state = self.state.lock().unwrap();
             let mut tasks = Vec::new();
@@ -3641,7 +3663,11 @@ impl Session {
             }
 
             tasks.sort_by_key(|info| info.started_at_ms);
-            tasks
+            if cancellable_only {
+                filter_cancellable(tasks)
+            } else {
+                tasks
+            }
         };
         let event = self.make_event(&sub_id, EventMsg::RunningTasksSnapshot(RunningTasksSnapshotEvent { tasks }));
         self.send_event(event).await;
@@ -3933,6 +3959,11 @@ fn synthetic_exec_end_payload(cancelled: bool) -> (i32, String) {
     }
 }
 
+/// Keeps only the tasks a client can act on with `Op::TerminateTask`.
+fn filter_cancellable(tasks: Vec<RunningTaskInfo>) -> Vec<RunningTaskInfo> {
+    tasks.into_iter().filter(|task| task.can_cancel).collect()
+}
+
 fn system_time_to_millis(ts: SystemTime) -> u64 {
     match ts.duration_since(std::time::UNIX_EPOCH) {
         Ok(duration) => duration.as_millis().min(u128::from(u64::MAX)) as u64,
@@ -4359,7 +4390,7 @@ async fn submission_loop(
                     let _ = tx_event.send(event).await;
                 }
             }
-            Op::ListRunningTasks => {
+            Op::ListRunningTasks { cancellable_only } => {
                 let sess_arc = match sess.as_ref() {
                     Some(sess) => Arc::clone(sess),
                     None => {
@@ -4368,7 +4399,7 @@ async fn submission_loop(
                     }
                 };
                 sess_arc
-                    .send_running_tasks_snapshot(sub.id.clone())
+                    .send_running_tasks_snapshot(sub.id.clone(), cancellable_only)
                     .await;
             }
             Op::TerminateTask { id, sub_id, kind } => {
@@ -4407,7 +4438,7 @@ async fn submission_loop(
                 sess_arc.send_event(event).await;
 
                 sess_arc
-                    .send_running_tasks_snapshot(sub.id.clone())
+                    .send_running_tasks_snapshot(sub.id.clone(), false)
                     .await;
 
                 if !success {
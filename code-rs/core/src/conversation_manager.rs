@@ -83,6 +83,52 @@ pub fn fork_history_from_response_items(
     }
 }
 
+/// What [`fork_history_from_response_items`] would do, computed without
+/// cloning or truncating the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversationForkPreview {
+    pub would_drop_user_turns: usize,
+    pub would_become_new: bool,
+    pub resulting_len: usize,
+}
+
+pub fn preview_fork_from_response_items(
+    history: &[ResponseItem],
+    drop_last_user_turns: usize,
+) -> ConversationForkPreview {
+    if drop_last_user_turns == 0 {
+        return ConversationForkPreview {
+            would_drop_user_turns: 0,
+            would_become_new: false,
+            resulting_len: history.len(),
+        };
+    }
+
+    let user_positions: Vec<usize> = history
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| matches!(item, ResponseItem::Message { role, .. } if role == "user"))
+        .map(|(idx, _)| idx)
+        .collect();
+    // Mirrors `truncate_after_dropping_last_messages`: too few user turns or a
+    // cut at the very start leaves nothing to fork.
+    let cut_idx = user_positions
+        .len()
+        .checked_sub(drop_last_user_turns)
+        .and_then(|first_dropped| user_positions.get(first_dropped).copied())
+        .unwrap_or(0);
+
+    ConversationForkPreview {
+        would_drop_user_turns: if cut_idx == 0 {
+            user_positions.len()
+        } else {
+            drop_last_user_turns
+        },
+        would_become_new: cut_idx == 0,
+        resulting_len: cut_idx,
+    }
+}
+
 pub fn prune_history_after_dropping_last_user_turns(
     history: Vec<ResponseItem>,
     drop_last_user_turns: usize,
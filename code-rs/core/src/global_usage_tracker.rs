@@ -1,14 +1,20 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::OsStr;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
 use std::thread;
+use std::time::Instant;
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
+use serde::Deserialize;
 use serde_json::Value;
 use tracing::warn;
 use walkdir::WalkDir;
@@ -16,11 +22,23 @@ use walkdir::WalkDir;
 use crate::config::legacy_code_home_dir_for_read;
 
 const SESSIONS_SUBDIR: &str = "sessions";
+/// Events up to this far ahead of the scan clock are treated as clock jitter
+/// and counted at `now`; anything later is dropped from the timeline.
+const FUTURE_TIMESTAMP_TOLERANCE_SECS: i64 = 120;
 const SLOT_DIR_NAME: &str = "slot";
-
-const TOKEN_FIELDS: [&str; 5] = [
+const AUTH_FILE_NAME: &str = "auth.json";
+/// Events newer than this stay exact under `with_max_timeline_events`; it
+/// spans the hourly, 12-hour and daily buckets and the windows up to 7 days.
+const EXACT_TIMELINE_HORIZON_DAYS: i64 = 7;
+/// Fixed seed so sampled scans of the same logs report the same numbers.
+const TIMELINE_SAMPLE_SEED: u64 = 0x5eed_7153;
+/// Account name used for sources whose slot has no resolvable email.
+pub const UNATTRIBUTED_ACCOUNT: &str = "unattributed";
+
+const TOKEN_FIELDS: [&str; 6] = [
     "input_tokens",
     "cached_input_tokens",
+    "cache_creation_input_tokens",
     "output_tokens",
     "reasoning_output_tokens",
     "total_tokens",
@@ -30,6 +48,8 @@ const TOKEN_FIELDS: [&str; 5] = [
 pub struct UsageTotals {
     pub non_cached_input_tokens: u64,
     pub cached_input_tokens: u64,
+    /// Input tokens written to the prompt cache (`cache_creation_input_tokens`).
+    pub cache_write_tokens: u64,
     pub output_tokens: u64,
     pub reasoning_output_tokens: u64,
     pub total_tokens: u64,
@@ -44,6 +64,9 @@ impl UsageTotals {
         self.cached_input_tokens = self
             .cached_input_tokens
             .saturating_add(other.cached_input_tokens);
+        self.cache_write_tokens = self
+            .cache_write_tokens
+            .saturating_add(other.cache_write_tokens);
         self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
         self.reasoning_output_tokens = self
             .reasoning_output_tokens
@@ -51,6 +74,17 @@ impl UsageTotals {
         self.total_tokens = self.total_tokens.saturating_add(other.total_tokens);
         self.cost_usd += other.cost_usd;
     }
+
+    fn scale(&mut self, factor: f64) {
+        let scale = |value: u64| (value as f64 * factor).round() as u64;
+        self.non_cached_input_tokens = scale(self.non_cached_input_tokens);
+        self.cached_input_tokens = scale(self.cached_input_tokens);
+        self.cache_write_tokens = scale(self.cache_write_tokens);
+        self.output_tokens = scale(self.output_tokens);
+        self.reasoning_output_tokens = scale(self.reasoning_output_tokens);
+        self.total_tokens = scale(self.total_tokens);
+        self.cost_usd *= factor;
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -86,6 +120,79 @@ impl ModelBucket {
             ModelBucket::Other => "other",
         }
     }
+
+    /// Display group names, in the order usage renderers list them.
+    pub const DISPLAY_GROUPS: [&'static str; 4] =
+        ["gpt-5-codex", "gpt-5", "gpt-5-codex-mini", "other"];
+
+    /// The entry of [`Self::DISPLAY_GROUPS`] this bucket is rendered under.
+    pub fn display_group(&self) -> &'static str {
+        match self {
+            ModelBucket::Gpt5Codex
+            | ModelBucket::Gpt51Codex
+            | ModelBucket::CodeGpt5Codex
+            | ModelBucket::ChatGpt51Codex => "gpt-5-codex",
+            ModelBucket::Gpt5 | ModelBucket::Gpt51 => "gpt-5",
+            ModelBucket::Gpt5Mini
+            | ModelBucket::Gpt51CodexMini
+            | ModelBucket::CodeGpt5CodexMini
+            | ModelBucket::CodeGpt5Mini
+            | ModelBucket::ChatGpt51CodexMini => "gpt-5-codex-mini",
+            ModelBucket::Other => "other",
+        }
+    }
+}
+
+/// USD per million tokens for models matched by a provider rates file.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ProviderRate {
+    pub non_cached: f64,
+    pub cached: f64,
+    pub output: f64,
+}
+
+/// Cost tables keyed by model-name pattern, overriding the built-in bucket
+/// rates. Patterns match as case-insensitive substrings, like
+/// [`ModelBucket::from_model_name`]; the longest matching pattern wins.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProviderRates {
+    patterns: Vec<(String, ProviderRate)>,
+}
+
+impl ProviderRates {
+    pub fn new(rates: BTreeMap<String, ProviderRate>) -> Self {
+        let mut patterns: Vec<(String, ProviderRate)> = rates
+            .into_iter()
+            .map(|(pattern, rate)| (pattern.to_lowercase(), rate))
+            .collect();
+        patterns.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        Self { patterns }
+    }
+
+    /// Loads a table mapping patterns to `{ non_cached, cached, output }`,
+    /// parsed as JSON for `.json` files and TOML otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let rates: BTreeMap<String, ProviderRate> =
+            if path.extension().and_then(OsStr::to_str) == Some("json") {
+                serde_json::from_str(&raw).with_context(|| format!("parsing {}", path.display()))?
+            } else {
+                toml::from_str(&raw).with_context(|| format!("parsing {}", path.display()))?
+            };
+        Ok(Self::new(rates))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    fn rate_for(&self, model: &str) -> Option<&ProviderRate> {
+        let normalized = model.to_lowercase();
+        self.patterns
+            .iter()
+            .find(|(pattern, _)| normalized.contains(pattern.as_str()))
+            .map(|(_, rate)| rate)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -97,6 +204,31 @@ pub struct ModelUsage {
 #[derive(Debug, Clone)]
 pub struct SourceUsage {
     pub label: String,
+    /// Sessions directory the source was read from.
+    pub directory: PathBuf,
+    pub totals: UsageTotals,
+}
+
+/// A source whose scanned cost went over the cap configured for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceCostAlert {
+    pub label: String,
+    pub cost_usd: f64,
+    pub cap_usd: f64,
+}
+
+/// Usage in a caller-requested trailing window (see `with_custom_windows`).
+#[derive(Debug, Clone)]
+pub struct WindowUsage {
+    pub duration: Duration,
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountUsage {
+    /// Account email (or account id in `GlobalUsageSnapshot::by_account`), or
+    /// [`UNATTRIBUTED_ACCOUNT`].
+    pub account: String,
     pub totals: UsageTotals,
 }
 
@@ -105,6 +237,43 @@ pub struct UsageBucket {
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
     pub totals: UsageTotals,
+    /// Per-model totals for the window; only populated when the scan enables
+    /// `with_bucket_model_breakdown`.
+    pub by_model: Option<BTreeMap<ModelBucket, UsageTotals>>,
+}
+
+impl UsageBucket {
+    /// Model with the most tokens in this window, when a breakdown was collected.
+    pub fn top_model(&self) -> Option<(ModelBucket, &UsageTotals)> {
+        self.by_model
+            .as_ref()?
+            .iter()
+            .filter(|(_, totals)| totals.total_tokens > 0)
+            .max_by_key(|(_, totals)| totals.total_tokens)
+            .map(|(bucket, totals)| (*bucket, totals))
+    }
+
+    /// Window label relative to `now`, e.g. `2h ago–1h ago` or `1h ago–now`.
+    pub fn relative_label(&self, now: DateTime<Utc>) -> String {
+        format!(
+            "{}–{}",
+            format_ago(now - self.start),
+            format_ago(now - self.end)
+        )
+    }
+}
+
+fn format_ago(elapsed: Duration) -> String {
+    let minutes = elapsed.num_minutes();
+    if minutes <= 0 {
+        "now".to_string()
+    } else if minutes < 60 {
+        format!("{minutes}m ago")
+    } else if elapsed.num_hours() < 48 {
+        format!("{}h ago", elapsed.num_hours())
+    } else {
+        format!("{}d ago", elapsed.num_days())
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -122,6 +291,34 @@ pub struct SessionUsage {
     pub session_id: String,
     pub model_bucket: ModelBucket,
     pub totals: UsageTotals,
+    /// Timestamp of the most recent timestamped entry in the session log.
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// True when neither a `.snapshot.json` sidecar nor the log named a model
+    /// and `model_bucket` is the source-based default.
+    pub model_inferred: bool,
+    /// `totals.cost_usd` broken down by token class, priced with the same
+    /// rates as the total.
+    pub cost_split: SessionCostSplit,
+}
+
+/// A session's estimated cost broken down by token class.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SessionCostSplit {
+    pub input_cost: f64,
+    pub output_cost: f64,
+    pub reasoning_cost: f64,
+}
+
+impl SessionCostSplit {
+    pub fn total(&self) -> f64 {
+        self.input_cost + self.output_cost + self.reasoning_cost
+    }
+
+    fn add(&mut self, other: &SessionCostSplit) {
+        self.input_cost += other.input_cost;
+        self.output_cost += other.output_cost;
+        self.reasoning_cost += other.reasoning_cost;
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -129,19 +326,62 @@ pub struct GlobalUsageSnapshot {
     pub generated_at: DateTime<Utc>,
     pub sessions_processed: usize,
     pub sessions_missing_totals: usize,
+    /// Sessions whose token counts were all zero; parsed fine, so not
+    /// counted in `sessions_missing_totals`.
+    pub sessions_zero_usage: usize,
+    pub malformed_lines: usize,
+    /// Token events dropped from buckets and trailing windows because their
+    /// timestamp was too far ahead of the scan clock.
+    pub future_timestamp_events: usize,
+    /// Wall-clock time spent discovering and parsing session logs.
+    pub scan_duration_ms: u64,
+    /// Usage inside the scan's date window (everything when none is set).
     pub totals: UsageTotals,
+    /// Usage across every scanned session, ignoring the date window.
+    pub all_time_totals: UsageTotals,
     pub model_usage: Vec<ModelUsage>,
+    /// Distinct model buckets seen in the scanned sessions, in enum order.
+    pub present_buckets: Vec<ModelBucket>,
     pub source_usage: Vec<SourceUsage>,
+    /// Sources over their `with_source_cost_alert` cap, most expensive first.
+    pub source_cost_alerts: Vec<SourceCostAlert>,
+    /// Source (`.code` or `.codex`) whose home holds the `auth.json` backing
+    /// the default slot. Legacy `.codex` auth wins when both homes have one,
+    /// as for the default slot; `None` with a sessions override or no default auth.
+    pub default_source_label: Option<String>,
+    /// Usage split by the `turn_context` account id in effect at each token
+    /// count, most tokens first; empty when no log carries account ids.
+    pub by_account: Vec<AccountUsage>,
+    /// Usage keyed by the `turn_context` reasoning effort in effect (e.g.
+    /// `high`); empty when no log records an effort.
+    pub effort_usage: BTreeMap<String, UsageTotals>,
     pub trailing: TrailingUsageTotals,
+    /// Totals for each `with_custom_windows` duration, in the order requested.
+    pub custom_windows: Vec<WindowUsage>,
     pub hourly_buckets: Vec<UsageBucket>,
     pub twelve_hour_buckets: Vec<UsageBucket>,
     pub daily_buckets: Vec<UsageBucket>,
     pub weekly_buckets: Vec<UsageBucket>,
     pub monthly_buckets: Vec<UsageBucket>,
+    /// One-day buckets covering `with_day_buckets` days, oldest first; empty
+    /// unless requested.
+    pub day_buckets: Vec<UsageBucket>,
+    /// True when `with_max_timeline_events` was exceeded and events older
+    /// than 7 days were reservoir-sampled. Totals, model groups, sources and
+    /// everything within the last 7 days stay exact; weekly and monthly
+    /// buckets and longer trailing windows are scaled estimates.
+    pub timeline_sampled: bool,
     pub largest_session: Option<SessionUsage>,
     pub per_session: Vec<SessionUsage>,
 }
 
+/// Parsing progress reported while a scan runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanProgress {
+    pub files_total: usize,
+    pub files_done: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct GlobalUsageScanOptions {
     pub code_home: PathBuf,
@@ -149,6 +389,37 @@ pub struct GlobalUsageScanOptions {
     pub legacy_code_home: Option<PathBuf>,
     pub max_workers: Option<usize>,
     pub record_sessions: bool,
+    pub progress: Option<Sender<ScanProgress>>,
+    /// Source labels (e.g. `.code/slot/work`) or directory prefixes to skip.
+    pub excluded_sources: Vec<String>,
+    /// Skip time buckets and trailing windows; only totals, model groups and
+    /// sources are populated.
+    pub summary_only: bool,
+    /// USD per million cache-write input tokens; defaults to 1.25× the
+    /// bucket's non-cached input rate.
+    pub cache_write_rate: Option<f64>,
+    /// Split every time bucket's totals by model.
+    pub bucket_model_breakdown: bool,
+    /// Cost cap in USD per source label; sources over their cap are reported
+    /// in `GlobalUsageSnapshot::source_cost_alerts`.
+    pub source_cost_alerts: HashMap<String, f64>,
+    /// Fold token events into fixed-size bucket and window accumulators while
+    /// parsing instead of retaining them, bounding memory on huge logs.
+    pub low_memory: bool,
+    /// Only token events at or after this instant count toward totals, model
+    /// groups, sources and sessions.
+    pub since: Option<DateTime<Utc>>,
+    /// Only token events before this instant count toward totals.
+    pub until: Option<DateTime<Utc>>,
+    /// Rates that replace the bucket defaults for matching model names.
+    pub provider_rates: ProviderRates,
+    /// Extra trailing windows to total alongside the fixed ones.
+    pub custom_windows: Vec<Duration>,
+    /// Number of one-day buckets to fill in `GlobalUsageSnapshot::day_buckets`.
+    pub day_bucket_count: Option<usize>,
+    /// Most timeline events older than 7 days to keep in memory; past it they
+    /// are reservoir-sampled. Ignored by low-memory scans.
+    pub max_timeline_events: Option<usize>,
 }
 
 impl GlobalUsageScanOptions {
@@ -159,6 +430,19 @@ impl GlobalUsageScanOptions {
             legacy_code_home: legacy_code_home_dir_for_read(),
             max_workers: None,
             record_sessions: false,
+            progress: None,
+            excluded_sources: Vec::new(),
+            summary_only: false,
+            cache_write_rate: None,
+            bucket_model_breakdown: false,
+            source_cost_alerts: HashMap::new(),
+            low_memory: false,
+            since: None,
+            until: None,
+            provider_rates: ProviderRates::default(),
+            custom_windows: Vec::new(),
+            day_bucket_count: None,
+            max_timeline_events: None,
         }
     }
 
@@ -179,6 +463,73 @@ impl GlobalUsageScanOptions {
         self
     }
 
+    pub fn with_progress(mut self, progress: Sender<ScanProgress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_excluded_sources(mut self, excluded: Vec<String>) -> Self {
+        self.excluded_sources = excluded;
+        self
+    }
+
+    pub fn with_summary_only(mut self, summary_only: bool) -> Self {
+        self.summary_only = summary_only;
+        self
+    }
+
+    pub fn with_cache_write_rate(mut self, rate: f64) -> Self {
+        if rate.is_finite() && rate >= 0.0 {
+            self.cache_write_rate = Some(rate);
+        }
+        self
+    }
+
+    pub fn with_bucket_model_breakdown(mut self, enabled: bool) -> Self {
+        self.bucket_model_breakdown = enabled;
+        self
+    }
+
+    pub fn with_source_cost_alert(mut self, caps: HashMap<String, f64>) -> Self {
+        self.source_cost_alerts = caps;
+        self
+    }
+
+    pub fn with_low_memory(mut self, low_memory: bool) -> Self {
+        self.low_memory = low_memory;
+        self
+    }
+
+    pub fn with_date_window(
+        mut self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    pub fn with_provider_rates(mut self, rates: ProviderRates) -> Self {
+        self.provider_rates = rates;
+        self
+    }
+
+    pub fn with_custom_windows(mut self, windows: Vec<Duration>) -> Self {
+        self.custom_windows = windows;
+        self
+    }
+
+    pub fn with_day_buckets(mut self, days: usize) -> Self {
+        self.day_bucket_count = (days > 0).then_some(days);
+        self
+    }
+
+    pub fn with_max_timeline_events(mut self, max_events: usize) -> Self {
+        self.max_timeline_events = (max_events > 0).then_some(max_events);
+        self
+    }
+
     fn effective_worker_count(&self) -> usize {
         if let Some(explicit) = self.max_workers {
             return explicit.max(1);
@@ -199,36 +550,108 @@ pub fn scan_global_usage_at(
     options: GlobalUsageScanOptions,
     now: DateTime<Utc>,
 ) -> Result<GlobalUsageSnapshot> {
+    let started = Instant::now();
     let worker_count = options.effective_worker_count();
-    let mut parser = SessionAggregator::new(now, options.record_sessions);
+    let mut parser = SessionAggregator::new(now, &options);
     parser.scan(&options, worker_count)?;
-    Ok(parser.finish())
+    let mut snapshot = parser.finish();
+    snapshot.default_source_label = default_source_label(&options);
+    snapshot.scan_duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    Ok(snapshot)
+}
+
+/// Groups per-source totals by the account that owns each source. A source is
+/// attributed when its sessions directory sits directly inside a directory
+/// listed in `emails_by_dir` (a slot root or the code home); everything else
+/// is reported under [`UNATTRIBUTED_ACCOUNT`].
+pub fn usage_by_account(
+    sources: &[SourceUsage],
+    emails_by_dir: &HashMap<PathBuf, String>,
+) -> Vec<AccountUsage> {
+    let mut by_account: BTreeMap<String, UsageTotals> = BTreeMap::new();
+    for source in sources {
+        let account = source
+            .directory
+            .parent()
+            .and_then(|dir| emails_by_dir.get(dir))
+            .cloned()
+            .unwrap_or_else(|| UNATTRIBUTED_ACCOUNT.to_string());
+        by_account.entry(account).or_default().add(&source.totals);
+    }
+
+    let mut accounts: Vec<AccountUsage> = by_account
+        .into_iter()
+        .map(|(account, totals)| AccountUsage { account, totals })
+        .collect();
+    accounts.sort_by(|a, b| {
+        b.totals
+            .total_tokens
+            .cmp(&a.totals.total_tokens)
+            .then_with(|| a.account.cmp(&b.account))
+    });
+    accounts
 }
 
 struct SessionAggregator {
     now: DateTime<Utc>,
     record_sessions: bool,
+    summary_only: bool,
+    bucket_model_breakdown: bool,
+    custom_windows: Vec<Duration>,
+    day_bucket_count: Option<usize>,
+    source_cost_caps: HashMap<String, f64>,
+    /// Running bucket/window totals for low-memory scans; `timeline_events`
+    /// stays empty when set.
+    timeline: Option<TimelineAccumulator>,
     totals: UsageTotals,
+    all_time_totals: UsageTotals,
     model_totals: BTreeMap<ModelBucket, UsageTotals>,
     source_totals: BTreeMap<String, UsageTotals>,
+    source_dirs: HashMap<String, PathBuf>,
+    account_totals: BTreeMap<String, UsageTotals>,
+    effort_totals: BTreeMap<String, UsageTotals>,
     timeline_events: Vec<UsageEvent>,
+    /// Sample of events older than the exact horizon when
+    /// `max_timeline_events` is set; recent events stay in `timeline_events`.
+    older_events: Option<TimelineReservoir>,
     sessions_processed: usize,
     sessions_missing_totals: usize,
+    sessions_zero_usage: usize,
+    malformed_lines: usize,
+    future_timestamp_events: usize,
     largest_session: Option<SessionUsage>,
     per_session: Vec<SessionUsage>,
 }
 
 impl SessionAggregator {
-    fn new(now: DateTime<Utc>, record_sessions: bool) -> Self {
+    fn new(now: DateTime<Utc>, options: &GlobalUsageScanOptions) -> Self {
         Self {
             now,
-            record_sessions,
+            record_sessions: options.record_sessions,
+            summary_only: options.summary_only,
+            bucket_model_breakdown: options.bucket_model_breakdown,
+            custom_windows: options.custom_windows.clone(),
+            day_bucket_count: options.day_bucket_count,
+            source_cost_caps: options.source_cost_alerts.clone(),
+            timeline: (options.low_memory && !options.summary_only)
+                .then(|| TimelineAccumulator::new(now, options)),
             totals: UsageTotals::default(),
+            all_time_totals: UsageTotals::default(),
             model_totals: BTreeMap::new(),
             source_totals: BTreeMap::new(),
+            source_dirs: HashMap::new(),
+            account_totals: BTreeMap::new(),
+            effort_totals: BTreeMap::new(),
             timeline_events: Vec::new(),
+            older_events: options
+                .max_timeline_events
+                .filter(|_| !options.low_memory)
+                .map(TimelineReservoir::new),
             sessions_processed: 0,
             sessions_missing_totals: 0,
+            sessions_zero_usage: 0,
+            malformed_lines: 0,
+            future_timestamp_events: 0,
             largest_session: None,
             per_session: Vec::new(),
         }
@@ -237,37 +660,68 @@ impl SessionAggregator {
     fn scan(&mut self, options: &GlobalUsageScanOptions, workers: usize) -> Result<()> {
         let sources = collect_session_sources(options);
         let mut tasks: Vec<(PathBuf, String)> = Vec::new();
+        // Symlinks are followed so linked session dirs are scanned, but walkdir
+        // reports loops as errors (skipped below) and files reachable through
+        // several links are only parsed once.
+        let mut seen_files: HashSet<PathBuf> = HashSet::new();
         for source in sources {
             if !source.directory.exists() {
                 continue;
             }
+            self.source_dirs.insert(source.label.clone(), source.directory.clone());
             for entry in WalkDir::new(&source.directory)
+                .follow_links(true)
                 .into_iter()
                 .filter_map(|e| e.ok())
             {
                 if entry.file_type().is_file()
                     && entry.path().extension().and_then(OsStr::to_str) == Some("jsonl")
                 {
-                    tasks.push((entry.into_path(), source.label.clone()));
+                    let canonical = fs::canonicalize(entry.path())
+                        .unwrap_or_else(|_| entry.path().to_path_buf());
+                    if seen_files.insert(canonical) {
+                        tasks.push((entry.into_path(), source.label.clone()));
+                    }
                 }
             }
         }
 
         tasks.sort_by(|a, b| a.0.cmp(&b.0));
 
-        let results = parse_session_logs(tasks, workers);
+        let settings = ParseSettings {
+            cache_write_rate: options.cache_write_rate,
+            since: options.since,
+            until: options.until,
+            provider_rates: options.provider_rates.clone(),
+            timeline: self.timeline.clone(),
+            summary_only: self.summary_only,
+        };
+        let results = parse_session_logs(tasks, workers, options.progress.as_ref(), &settings);
 
         for (path, label, result) in results {
             match result {
                 Ok(result) => {
+                    self.malformed_lines += result.malformed_lines;
+                    if let Some(all_time) = &result.all_time_totals {
+                        self.all_time_totals.add(all_time);
+                    }
                     if let Some(final_totals) = result.final_totals.clone() {
                         self.sessions_processed += 1;
                         self.consume_session(&label, result.bucket, final_totals.clone());
+                        for (account, totals) in &result.account_totals {
+                            self.account_totals.entry(account.clone()).or_default().add(totals);
+                        }
+                        for (effort, totals) in &result.effort_totals {
+                            self.effort_totals.entry(effort.clone()).or_default().add(totals);
+                        }
                         if self.record_sessions {
                             self.per_session.push(SessionUsage {
                                 session_id: result.session_id.clone(),
                                 model_bucket: result.bucket,
                                 totals: final_totals.clone(),
+                                last_event_at: result.last_event_at,
+                                model_inferred: result.model_inferred,
+                                cost_split: result.cost_split,
                             });
                         }
                         match &self.largest_session {
@@ -277,13 +731,23 @@ impl SessionAggregator {
                                     session_id: result.session_id.clone(),
                                     model_bucket: result.bucket,
                                     totals: final_totals,
+                                    last_event_at: result.last_event_at,
+                                    model_inferred: result.model_inferred,
+                                    cost_split: result.cost_split,
                                 });
                             }
                         }
-                    } else {
-                        self.sessions_missing_totals += 1;
+                    } else if result.all_time_totals.is_none() {
+                        if result.saw_token_counts {
+                            self.sessions_zero_usage += 1;
+                        } else {
+                            self.sessions_missing_totals += 1;
+                        }
+                    }
+                    match result.timeline {
+                        Some(timeline) => self.absorb_folded_timeline(timeline),
+                        None => self.absorb_timeline(result.events),
                     }
-                    self.timeline_events.extend(result.events);
                 }
                 Err(err) => {
                     warn!(?path, "failed to parse session log: {err}");
@@ -294,6 +758,33 @@ impl SessionAggregator {
         Ok(())
     }
 
+    /// Adds a session's events to the timeline, clamping small clock skew to
+    /// `now` and dropping (but counting) events further in the future.
+    fn absorb_timeline(&mut self, events: Vec<UsageEvent>) {
+        for mut event in events {
+            if !clamp_to_scan_clock(&mut event, self.now) {
+                self.future_timestamp_events += 1;
+                continue;
+            }
+            if self.summary_only {
+                continue;
+            }
+            let exact_since = self.now - Duration::days(EXACT_TIMELINE_HORIZON_DAYS);
+            match self.older_events.as_mut() {
+                Some(older) if event.timestamp < exact_since => older.offer(event),
+                _ => self.timeline_events.push(event),
+            }
+        }
+    }
+
+    /// Merges a session already folded by a low-memory parse.
+    fn absorb_folded_timeline(&mut self, timeline: TimelineAccumulator) {
+        self.future_timestamp_events += timeline.future_timestamp_events;
+        if let Some(total) = self.timeline.as_mut() {
+            total.merge(timeline);
+        }
+    }
+
     fn consume_session(&mut self, label: &str, bucket: ModelBucket, totals: UsageTotals) {
         self.totals.add(&totals);
         self.model_totals
@@ -307,6 +798,7 @@ impl SessionAggregator {
     }
 
     fn finish(self) -> GlobalUsageSnapshot {
+        let present_buckets: Vec<ModelBucket> = self.model_totals.keys().copied().collect();
         let mut model_usage: Vec<ModelUsage> = self
             .model_totals
             .into_iter()
@@ -322,7 +814,11 @@ impl SessionAggregator {
         let mut source_usage: Vec<SourceUsage> = self
             .source_totals
             .into_iter()
-            .map(|(label, totals)| SourceUsage { label, totals })
+            .map(|(label, totals)| SourceUsage {
+                directory: self.source_dirs.get(&label).cloned().unwrap_or_default(),
+                label,
+                totals,
+            })
             .collect();
         source_usage.sort_by(|a, b| {
             b.totals
@@ -330,97 +826,364 @@ impl SessionAggregator {
                 .cmp(&a.totals.total_tokens)
                 .then_with(|| a.label.cmp(&b.label))
         });
-
-        let hourly_buckets = compute_time_buckets(
-            &self.timeline_events,
-            12,
-            Duration::hours(1),
-            self.now,
-        );
-        let twelve_hour_buckets = compute_time_buckets(
-            &self.timeline_events,
-            14,
-            Duration::hours(12),
-            self.now,
-        );
-        let daily_buckets = compute_time_buckets(
-            &self.timeline_events,
-            7,
-            Duration::days(1),
-            self.now,
-        );
-        let weekly_buckets = compute_time_buckets(
-            &self.timeline_events,
-            8,
-            Duration::days(7),
-            self.now,
-        );
-        let monthly_buckets = compute_time_buckets(
-            &self.timeline_events,
-            6,
-            Duration::days(30),
-            self.now,
-        );
-
-        let trailing = TrailingUsageTotals {
-            last_hour: compute_rolling_usage(&self.timeline_events, Duration::hours(1), self.now),
-            last_twelve_hours: compute_rolling_usage(
-                &self.timeline_events,
-                Duration::hours(12),
-                self.now,
-            ),
-            last_day: compute_rolling_usage(&self.timeline_events, Duration::days(1), self.now),
-            last_seven_days: compute_rolling_usage(&self.timeline_events, Duration::days(7), self.now),
-            last_thirty_days: compute_rolling_usage(
-                &self.timeline_events,
-                Duration::days(30),
-                self.now,
-            ),
-            last_year: compute_rolling_usage(&self.timeline_events, Duration::days(365), self.now),
+        let mut source_cost_alerts: Vec<SourceCostAlert> = source_usage
+            .iter()
+            .filter_map(|source| {
+                let cap_usd = *self.source_cost_caps.get(&source.label)?;
+                (source.totals.cost_usd > cap_usd).then(|| SourceCostAlert {
+                    label: source.label.clone(),
+                    cost_usd: source.totals.cost_usd,
+                    cap_usd,
+                })
+            })
+            .collect();
+        source_cost_alerts.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+        let has_account_ids = self
+            .account_totals
+            .keys()
+            .any(|account| account != UNATTRIBUTED_ACCOUNT);
+        let mut by_account: Vec<AccountUsage> = if has_account_ids {
+            self.account_totals
+                .into_iter()
+                .map(|(account, totals)| AccountUsage { account, totals })
+                .collect()
+        } else {
+            Vec::new()
         };
+        by_account.sort_by(|a, b| {
+            b.totals
+                .total_tokens
+                .cmp(&a.totals.total_tokens)
+                .then_with(|| a.account.cmp(&b.account))
+        });
 
-        GlobalUsageSnapshot {
+        let mut snapshot = GlobalUsageSnapshot {
             generated_at: self.now,
             sessions_processed: self.sessions_processed,
             sessions_missing_totals: self.sessions_missing_totals,
+            sessions_zero_usage: self.sessions_zero_usage,
+            malformed_lines: self.malformed_lines,
+            future_timestamp_events: self.future_timestamp_events,
+            scan_duration_ms: 0,
             totals: self.totals,
+            all_time_totals: self.all_time_totals,
             model_usage,
+            present_buckets,
             source_usage,
-            trailing,
-            hourly_buckets,
-            twelve_hour_buckets,
-            daily_buckets,
-            weekly_buckets,
-            monthly_buckets,
+            source_cost_alerts,
+            by_account,
+            effort_usage: self.effort_totals,
             largest_session: self.largest_session,
             per_session: self.per_session,
+            ..GlobalUsageSnapshot::default()
+        };
+        if self.summary_only {
+            return snapshot;
+        }
+
+        if let Some(timeline) = self.timeline {
+            timeline.apply_to(&mut snapshot);
+            return snapshot;
+        }
+
+        let mut events = self.timeline_events;
+        if let Some(older) = self.older_events {
+            snapshot.timeline_sampled = older.is_sampled();
+            events.extend(older.into_weighted_events());
+        }
+        let events = &events;
+        let now = self.now;
+        let by_model = self.bucket_model_breakdown;
+        let [hourly, twelve_hour, daily, weekly, monthly] = bucket_layouts()
+            .map(|(count, size)| compute_time_buckets(events, count, size, now, by_model));
+        snapshot.hourly_buckets = hourly;
+        snapshot.twelve_hour_buckets = twelve_hour;
+        snapshot.daily_buckets = daily;
+        snapshot.weekly_buckets = weekly;
+        snapshot.monthly_buckets = monthly;
+        if let Some(days) = self.day_bucket_count {
+            snapshot.day_buckets = compute_time_buckets(events, days, Duration::days(1), now, by_model);
+        }
+        snapshot.trailing = trailing_from_windows(
+            trailing_windows().map(|duration| compute_rolling_usage(events, duration, now)),
+        );
+        snapshot.custom_windows = self
+            .custom_windows
+            .iter()
+            .map(|&duration| WindowUsage {
+                duration,
+                totals: compute_rolling_usage(events, duration, now),
+            })
+            .collect();
+        snapshot
+    }
+}
+
+/// Fixed-capacity uniform sample of timeline events (Algorithm R). Once more
+/// events are offered than fit, each kept event stands for `seen / capacity`
+/// events and is scaled accordingly when the timeline is built.
+struct TimelineReservoir {
+    capacity: usize,
+    seen: usize,
+    events: Vec<UsageEvent>,
+    rng: StdRng,
+}
+
+impl TimelineReservoir {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            events: Vec::new(),
+            rng: StdRng::seed_from_u64(TIMELINE_SAMPLE_SEED),
+        }
+    }
+
+    fn offer(&mut self, event: UsageEvent) {
+        self.seen += 1;
+        if self.events.len() < self.capacity {
+            self.events.push(event);
+            return;
+        }
+        let slot = self.rng.random_range(0..self.seen);
+        if let Some(kept) = self.events.get_mut(slot) {
+            *kept = event;
+        }
+    }
+
+    fn is_sampled(&self) -> bool {
+        self.seen > self.events.len()
+    }
+
+    fn into_weighted_events(mut self) -> Vec<UsageEvent> {
+        if self.is_sampled() {
+            let factor = self.seen as f64 / self.events.len() as f64;
+            for event in &mut self.events {
+                event.deltas.scale(factor);
+            }
+        }
+        self.events
+    }
+}
+
+/// Bucket count and width for the hourly, 12-hour, daily, weekly and monthly
+/// series, in snapshot field order.
+fn bucket_layouts() -> [(usize, Duration); 5] {
+    [
+        (12, Duration::hours(1)),
+        (14, Duration::hours(12)),
+        (7, Duration::days(1)),
+        (8, Duration::days(7)),
+        (6, Duration::days(30)),
+    ]
+}
+
+/// Trailing window lengths in `TrailingUsageTotals` field order.
+fn trailing_windows() -> [Duration; 6] {
+    [
+        Duration::hours(1),
+        Duration::hours(12),
+        Duration::days(1),
+        Duration::days(7),
+        Duration::days(30),
+        Duration::days(365),
+    ]
+}
+
+fn trailing_from_windows(windows: [UsageTotals; 6]) -> TrailingUsageTotals {
+    let [last_hour, last_twelve_hours, last_day, last_seven_days, last_thirty_days, last_year] =
+        windows;
+    TrailingUsageTotals {
+        last_hour,
+        last_twelve_hours,
+        last_day,
+        last_seven_days,
+        last_thirty_days,
+        last_year,
+    }
+}
+
+/// Clamps small clock skew to `now`; returns false for events too far in the
+/// future to count.
+fn clamp_to_scan_clock(event: &mut UsageEvent, now: DateTime<Utc>) -> bool {
+    if event.timestamp > now + Duration::seconds(FUTURE_TIMESTAMP_TOLERANCE_SECS) {
+        return false;
+    }
+    event.timestamp = event.timestamp.min(now);
+    true
+}
+
+/// Fixed-size bucket and trailing-window totals that events are folded into
+/// as they are parsed, so low-memory scans never hold a session's events.
+#[derive(Debug, Clone)]
+struct TimelineAccumulator {
+    now: DateTime<Utc>,
+    buckets: [Vec<UsageBucket>; 5],
+    trailing: [UsageTotals; 6],
+    custom: Vec<WindowUsage>,
+    day_buckets: Vec<UsageBucket>,
+    future_timestamp_events: usize,
+}
+
+impl TimelineAccumulator {
+    fn new(now: DateTime<Utc>, options: &GlobalUsageScanOptions) -> Self {
+        let by_model = options.bucket_model_breakdown;
+        Self {
+            now,
+            buckets: bucket_layouts()
+                .map(|(count, size)| empty_time_buckets(count, size, now, by_model)),
+            trailing: Default::default(),
+            day_buckets: options
+                .day_bucket_count
+                .map(|days| empty_time_buckets(days, Duration::days(1), now, by_model))
+                .unwrap_or_default(),
+            custom: options
+                .custom_windows
+                .iter()
+                .map(|&duration| WindowUsage { duration, totals: UsageTotals::default() })
+                .collect(),
+            future_timestamp_events: 0,
+        }
+    }
+
+    fn absorb(&mut self, mut event: UsageEvent) {
+        if !clamp_to_scan_clock(&mut event, self.now) {
+            self.future_timestamp_events += 1;
+            return;
+        }
+        for ((_, size), buckets) in bucket_layouts().into_iter().zip(self.buckets.iter_mut()) {
+            add_to_time_buckets(buckets, size, &event);
+        }
+        add_to_time_buckets(&mut self.day_buckets, Duration::days(1), &event);
+        for (duration, totals) in trailing_windows().into_iter().zip(self.trailing.iter_mut()) {
+            if in_rolling_window(event.timestamp, duration, self.now) {
+                totals.add(&event.deltas);
+            }
+        }
+        for window in &mut self.custom {
+            if in_rolling_window(event.timestamp, window.duration, self.now) {
+                window.totals.add(&event.deltas);
+            }
+        }
+    }
+
+    fn merge(&mut self, other: TimelineAccumulator) {
+        let series = self.buckets.iter_mut().chain(std::iter::once(&mut self.day_buckets));
+        let other_series = other.buckets.into_iter().chain(std::iter::once(other.day_buckets));
+        for (series, other_series) in series.zip(other_series) {
+            for (bucket, other_bucket) in series.iter_mut().zip(other_series) {
+                bucket.totals.add(&other_bucket.totals);
+                if let (Some(by_model), Some(other_by_model)) =
+                    (bucket.by_model.as_mut(), other_bucket.by_model)
+                {
+                    for (model, totals) in other_by_model {
+                        by_model.entry(model).or_default().add(&totals);
+                    }
+                }
+            }
+        }
+        for (totals, other_totals) in self.trailing.iter_mut().zip(other.trailing) {
+            totals.add(&other_totals);
+        }
+        for (window, other_window) in self.custom.iter_mut().zip(other.custom) {
+            window.totals.add(&other_window.totals);
+        }
+    }
+
+    fn apply_to(self, snapshot: &mut GlobalUsageSnapshot) {
+        let [hourly, twelve_hour, daily, weekly, monthly] = self.buckets;
+        snapshot.hourly_buckets = hourly;
+        snapshot.twelve_hour_buckets = twelve_hour;
+        snapshot.daily_buckets = daily;
+        snapshot.weekly_buckets = weekly;
+        snapshot.monthly_buckets = monthly;
+        snapshot.day_buckets = self.day_buckets;
+        snapshot.trailing = trailing_from_windows(self.trailing);
+        snapshot.custom_windows = self.custom;
+    }
+}
+
+/// Per-file parsing knobs shared by every worker.
+struct ParseSettings {
+    cache_write_rate: Option<f64>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    provider_rates: ProviderRates,
+    /// Empty accumulator cloned for each file when events are folded while
+    /// parsing (low-memory scans).
+    timeline: Option<TimelineAccumulator>,
+    /// Summary-only scans build no timeline, so parsing keeps no events.
+    summary_only: bool,
+}
+
+impl ParseSettings {
+    /// Rates for `model`: provider-file rates when a pattern matches, the
+    /// bucket's built-in rates otherwise, with any configured cache-write rate
+    /// applied on top.
+    fn rates_for(&self, model: &str, bucket: ModelBucket) -> BucketRates {
+        let mut rates = match self.provider_rates.rate_for(model) {
+            Some(rate) => BucketRates::from_provider(rate),
+            None => bucket_rates(bucket),
+        };
+        if let Some(cache_write) = self.cache_write_rate {
+            rates.cache_write = cache_write;
+        }
+        rates
+    }
+
+    /// Whether a token event counts toward windowed totals. Events without a
+    /// timestamp only count when no window is set.
+    fn in_date_window(&self, timestamp: Option<DateTime<Utc>>) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
         }
+        timestamp.is_some_and(|ts| {
+            self.since.is_none_or(|since| ts >= since) && self.until.is_none_or(|until| ts < until)
+        })
     }
 }
 
+#[cfg(test)]
+thread_local! {
+    /// Pool size the last `parse_session_logs` call on this thread asked for.
+    static REQUESTED_POOL_SIZE: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+}
+
 fn parse_session_logs(
     tasks: Vec<(PathBuf, String)>,
     workers: usize,
+    progress: Option<&Sender<ScanProgress>>,
+    settings: &ParseSettings,
 ) -> Vec<(PathBuf, String, Result<SessionParseResult>)> {
+    let files_total = tasks.len();
+    let files_done = AtomicUsize::new(0);
+    if let Some(tx) = progress {
+        let _ = tx.send(ScanProgress {
+            files_total,
+            files_done: 0,
+        });
+    }
+    let parse = |(path, label): (PathBuf, String)| {
+        let result = parse_session_log(&path, &label, settings);
+        if let Some(tx) = progress {
+            let done = files_done.fetch_add(1, Ordering::Relaxed) + 1;
+            let _ = tx.send(ScanProgress {
+                files_total,
+                files_done: done,
+            });
+        }
+        (path, label, result)
+    };
+
+    // No point spinning up more threads than there are files to parse.
+    let workers = workers.min(files_total);
+    #[cfg(test)]
+    REQUESTED_POOL_SIZE.with(|size| size.set(Some(workers)));
     if workers <= 1 {
-        return tasks
-            .into_iter()
-            .map(|(path, label)| {
-                let result = parse_session_log(&path, &label);
-                (path, label, result)
-            })
-            .collect();
+        return tasks.into_iter().map(parse).collect();
     }
 
-    let job = || {
-        tasks
-            .into_par_iter()
-            .map(|(path, label)| {
-                let result = parse_session_log(&path, &label);
-                (path, label, result)
-            })
-            .collect()
-    };
+    let job = || tasks.into_par_iter().map(parse).collect();
 
     match ThreadPoolBuilder::new().num_threads(workers).build() {
         Ok(pool) => pool.install(job),
@@ -434,23 +1197,51 @@ struct SessionSource {
 }
 
 fn collect_session_sources(options: &GlobalUsageScanOptions) -> Vec<SessionSource> {
+    let mut sources = Vec::new();
     if let Some(custom) = &options.sessions_dir_override {
-        return vec![SessionSource {
+        sources.push(SessionSource {
             label: custom.display().to_string(),
             directory: custom.clone(),
-        }];
+        });
+    } else {
+        let code_sessions = options.code_home.join(SESSIONS_SUBDIR);
+        sources.extend(expand_with_slots(".code", &code_sessions));
+
+        if let Some(legacy) = &options.legacy_code_home {
+            let codex_sessions = legacy.join(SESSIONS_SUBDIR);
+            sources.extend(expand_with_slots(".codex", &codex_sessions));
+        }
     }
 
-    let mut sources = Vec::new();
-    let code_sessions = options.code_home.join(SESSIONS_SUBDIR);
-    sources.extend(expand_with_slots(".code", &code_sessions));
+    sources.retain(|source| !is_excluded_source(source, &options.excluded_sources));
+    sources
+}
 
-    if let Some(legacy) = &options.legacy_code_home {
-        let codex_sessions = legacy.join(SESSIONS_SUBDIR);
-        sources.extend(expand_with_slots(".codex", &codex_sessions));
+/// Label of the root source whose home provides the default slot's
+/// `auth.json`. The default slot prefers legacy `.codex` auth, so `.codex`
+/// wins whenever it has one and `.code` is reported only otherwise.
+fn default_source_label(options: &GlobalUsageScanOptions) -> Option<String> {
+    if options.sessions_dir_override.is_some() {
+        return None;
     }
+    let legacy_has_auth = options
+        .legacy_code_home
+        .as_ref()
+        .is_some_and(|legacy| legacy.join(AUTH_FILE_NAME).is_file());
+    if legacy_has_auth {
+        return Some(".codex".to_string());
+    }
+    options
+        .code_home
+        .join(AUTH_FILE_NAME)
+        .is_file()
+        .then(|| ".code".to_string())
+}
 
-    sources
+fn is_excluded_source(source: &SessionSource, excluded: &[String]) -> bool {
+    excluded.iter().any(|pattern| {
+        source.label == *pattern || source.directory.starts_with(Path::new(pattern))
+    })
 }
 
 fn expand_with_slots(label: &str, base_dir: &Path) -> Vec<SessionSource> {
@@ -491,17 +1282,40 @@ fn expand_with_slots(label: &str, base_dir: &Path) -> Vec<SessionSource> {
 #[derive(Debug, Clone)]
 struct UsageEvent {
     timestamp: DateTime<Utc>,
+    bucket: ModelBucket,
     deltas: UsageTotals,
 }
 
 struct SessionParseResult {
     session_id: String,
     bucket: ModelBucket,
+    /// Usage inside the date window; `None` when nothing fell inside it.
     final_totals: Option<UsageTotals>,
+    /// Usage over the whole log; `None` when the session has no token counts.
+    all_time_totals: Option<UsageTotals>,
+    /// Whether any `token_count` event parsed, even if every count was zero.
+    saw_token_counts: bool,
     events: Vec<UsageEvent>,
+    /// Events already folded in; set instead of `events` for low-memory scans.
+    timeline: Option<TimelineAccumulator>,
+    /// Windowed usage keyed by the `turn_context` account id in effect, or
+    /// [`UNATTRIBUTED_ACCOUNT`] before any id is logged.
+    account_totals: BTreeMap<String, UsageTotals>,
+    /// Windowed usage keyed by the `turn_context` reasoning effort in effect;
+    /// usage before any effort is logged is left out.
+    effort_totals: BTreeMap<String, UsageTotals>,
+    malformed_lines: usize,
+    last_event_at: Option<DateTime<Utc>>,
+    model_inferred: bool,
+    /// Split of the windowed cost by token class.
+    cost_split: SessionCostSplit,
 }
 
-fn parse_session_log(path: &Path, source_label: &str) -> Result<SessionParseResult> {
+fn parse_session_log(
+    path: &Path,
+    source_label: &str,
+    settings: &ParseSettings,
+) -> Result<SessionParseResult> {
     let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
     let mut reader = BufReader::new(file);
     let mut buffer = String::new();
@@ -511,14 +1325,20 @@ fn parse_session_log(path: &Path, source_label: &str) -> Result<SessionParseResu
         .and_then(OsStr::to_str)
         .unwrap_or_default()
         .to_string();
+    // Without a sidecar model, token counts logged before the first
+    // `session_meta`/`turn_context` model wait unpriced until one shows up, so
+    // they are not priced against the fallback and the log is read once.
     let mut current_model = load_snapshot_model(path);
-    if current_model.is_none() && source_label.starts_with(".code") {
-        current_model = Some("gpt-5".to_string());
-    }
+    let mut model_inferred = current_model.is_none();
+    let mut unpriced: Vec<UnpricedTokenCount> = Vec::new();
 
     let mut totals_map: HashMap<&'static str, u64> = TOKEN_FIELDS.iter().map(|&f| (f, 0)).collect();
-    let mut events = Vec::new();
-    let mut session_totals = UsageTotals::default();
+    let mut tally = SessionTally::new(settings);
+    let mut current_account: Option<String> = None;
+    let mut current_effort: Option<String> = None;
+    let mut malformed_lines = 0usize;
+    let mut saw_token_counts = false;
+    let mut last_event_at: Option<DateTime<Utc>> = None;
 
     while reader.read_line(&mut buffer)? != 0 {
         let line = buffer.trim();
@@ -531,11 +1351,21 @@ fn parse_session_log(path: &Path, source_label: &str) -> Result<SessionParseResu
             Ok(value) => value,
             Err(err) => {
                 warn!(?path, "invalid json entry: {err}");
+                malformed_lines += 1;
                 buffer.clear();
                 continue;
             }
         };
 
+        let entry_ts = entry
+            .get("timestamp")
+            .and_then(Value::as_str)
+            .and_then(parse_timestamp);
+        if entry_ts.is_some() {
+            last_event_at = last_event_at.max(entry_ts);
+        }
+
+        let mut logged_model: Option<&str> = None;
         match entry.get("type").and_then(Value::as_str) {
             Some("session_meta") => {
                 if let Some(id) = entry
@@ -545,44 +1375,51 @@ fn parse_session_log(path: &Path, source_label: &str) -> Result<SessionParseResu
                 {
                     session_id = id.to_string();
                 }
-                if let Some(model) = entry
+                logged_model = entry
                     .get("payload")
                     .and_then(|p| p.get("model"))
-                    .and_then(Value::as_str)
-                {
-                    current_model = Some(model.to_string());
-                }
+                    .and_then(Value::as_str);
             }
             Some("turn_context") => {
-                if let Some(model) = entry
+                logged_model = entry
                     .get("payload")
                     .and_then(|p| p.get("model"))
-                    .and_then(Value::as_str)
-                {
-                    current_model = Some(model.to_string());
+                    .and_then(Value::as_str);
+                if let Some(account) = turn_context_account_id(entry.get("payload")) {
+                    current_account = Some(account);
+                }
+                if let Some(effort) = turn_context_effort(entry.get("payload")) {
+                    current_effort = Some(effort);
                 }
             }
             Some("event_msg") | Some("event") => {
                 if let Some(payload) = extract_event_payload(&entry) {
                     match payload.kind {
                         "token_count" => {
-                            if let Some(delta) = process_token_count(
-                                payload.info,
-                                entry.get("timestamp").and_then(Value::as_str),
-                                current_model.as_deref().unwrap_or("gpt-5"),
-                                &mut totals_map,
-                                &mut events,
-                            ) {
-                                session_totals.add(&delta);
+                            if let Some(deltas) = token_count_deltas(payload.info, &mut totals_map) {
+                                saw_token_counts = true;
+                                let count = UnpricedTokenCount {
+                                    deltas,
+                                    timestamp: entry_ts,
+                                    account: current_account.clone(),
+                                    effort: current_effort.clone(),
+                                };
+                                match current_model.as_deref() {
+                                    Some(model) => tally.record(count, model, settings),
+                                    None => unpriced.push(count),
+                                }
                             }
                         }
                         "turn_context" => {
-                            if let Some(model) = payload
+                            logged_model = payload
                                 .payload
                                 .and_then(|p| p.get("model"))
-                                .and_then(Value::as_str)
-                            {
-                                current_model = Some(model.to_string());
+                                .and_then(Value::as_str);
+                            if let Some(account) = turn_context_account_id(payload.payload) {
+                                current_account = Some(account);
+                            }
+                            if let Some(effort) = turn_context_effort(payload.payload) {
+                                current_effort = Some(effort);
                             }
                         }
                         _ => {}
@@ -592,28 +1429,142 @@ fn parse_session_log(path: &Path, source_label: &str) -> Result<SessionParseResu
             _ => {}
         }
 
+        if let Some(model) = logged_model {
+            model_inferred = false;
+            for count in unpriced.drain(..) {
+                tally.record(count, model, settings);
+            }
+            current_model = Some(model.to_string());
+        }
+
         buffer.clear();
     }
 
+    if model_inferred && source_label.starts_with(".code") {
+        current_model = Some("gpt-5".to_string());
+    }
+    let fallback_model = current_model.as_deref().unwrap_or("gpt-5");
+    for count in unpriced {
+        tally.record(count, fallback_model, settings);
+    }
+
     let bucket = current_model
         .as_deref()
         .map(ModelBucket::from_model_name)
         .unwrap_or(ModelBucket::Gpt5);
 
-    let final_totals = if session_totals.total_tokens > 0 {
-        Some(session_totals)
-    } else {
-        None
-    };
+    let SessionTally {
+        events,
+        timeline,
+        session_totals,
+        window_totals,
+        account_totals,
+        effort_totals,
+        cost_split,
+    } = tally;
+    let final_totals = (window_totals.total_tokens > 0).then_some(window_totals);
+    let all_time_totals = (session_totals.total_tokens > 0).then_some(session_totals);
 
     Ok(SessionParseResult {
         session_id,
         bucket,
         final_totals,
+        all_time_totals,
+        saw_token_counts,
         events,
+        timeline,
+        account_totals,
+        effort_totals,
+        malformed_lines,
+        last_event_at,
+        model_inferred,
+        cost_split,
     })
 }
 
+/// Token deltas from one `token_count` event, held until the session's model
+/// is known so they can be priced.
+struct UnpricedTokenCount {
+    deltas: UsageTotals,
+    timestamp: Option<DateTime<Utc>>,
+    /// `turn_context` account id and effort in effect when the count was logged.
+    account: Option<String>,
+    effort: Option<String>,
+}
+
+/// Priced usage accumulated while reading one session log.
+struct SessionTally {
+    events: Vec<UsageEvent>,
+    timeline: Option<TimelineAccumulator>,
+    session_totals: UsageTotals,
+    window_totals: UsageTotals,
+    account_totals: BTreeMap<String, UsageTotals>,
+    effort_totals: BTreeMap<String, UsageTotals>,
+    cost_split: SessionCostSplit,
+}
+
+impl SessionTally {
+    fn new(settings: &ParseSettings) -> Self {
+        Self {
+            events: Vec::new(),
+            timeline: settings.timeline.clone(),
+            session_totals: UsageTotals::default(),
+            window_totals: UsageTotals::default(),
+            account_totals: BTreeMap::new(),
+            effort_totals: BTreeMap::new(),
+            cost_split: SessionCostSplit::default(),
+        }
+    }
+
+    fn record(&mut self, count: UnpricedTokenCount, model_name: &str, settings: &ParseSettings) {
+        let mut delta = count.deltas;
+        let (bucket, split) = price_token_deltas(&mut delta, model_name, settings);
+        if let Some(timestamp) = count.timestamp.filter(|_| !settings.summary_only) {
+            let event = UsageEvent {
+                timestamp,
+                bucket,
+                deltas: delta.clone(),
+            };
+            match self.timeline.as_mut() {
+                Some(timeline) => timeline.absorb(event),
+                None => self.events.push(event),
+            }
+        }
+
+        self.session_totals.add(&delta);
+        if settings.in_date_window(count.timestamp) {
+            self.window_totals.add(&delta);
+            self.cost_split.add(&split);
+            let account = count.account.as_deref().unwrap_or(UNATTRIBUTED_ACCOUNT);
+            self.account_totals
+                .entry(account.to_string())
+                .or_default()
+                .add(&delta);
+            if let Some(effort) = count.effort {
+                self.effort_totals.entry(effort).or_default().add(&delta);
+            }
+        }
+    }
+}
+
+/// Non-blank `account_id` carried by a `turn_context` payload.
+fn turn_context_account_id(payload: Option<&Value>) -> Option<String> {
+    let account = payload?.get("account_id")?.as_str()?.trim();
+    (!account.is_empty()).then(|| account.to_string())
+}
+
+/// Lowercased reasoning effort carried by a `turn_context` payload, logged as
+/// `reasoning_effort` or `effort` depending on the writer.
+fn turn_context_effort(payload: Option<&Value>) -> Option<String> {
+    let payload = payload?;
+    let effort = payload
+        .get("reasoning_effort")
+        .or_else(|| payload.get("effort"))?
+        .as_str()?
+        .trim();
+    (!effort.is_empty()).then(|| effort.to_ascii_lowercase())
+}
+
 struct EventPayload<'a> {
     kind: &'a str,
     info: Option<&'a Value>,
@@ -621,35 +1572,31 @@ struct EventPayload<'a> {
 }
 
 fn extract_event_payload<'a>(entry: &'a Value) -> Option<EventPayload<'a>> {
-    if entry.get("type").and_then(Value::as_str) == Some("event") {
-        let payload = entry.get("payload")?.get("msg")?;
-        Some(EventPayload {
-            kind: payload.get("type").and_then(Value::as_str).unwrap_or(""),
-            info: payload.get("info"),
-            payload: Some(payload),
-        })
+    // `event` lines persist a whole `RecordedEvent`, so the message (and its
+    // `info`) sits under `payload.msg`; `event_msg` lines carry it flat.
+    let payload = entry.get("payload")?;
+    let msg = if entry.get("type").and_then(Value::as_str) == Some("event") {
+        payload.get("msg")?
     } else {
-        let payload = entry.get("payload")?;
-        Some(EventPayload {
-            kind: payload.get("type").and_then(Value::as_str).unwrap_or(""),
-            info: payload.get("info"),
-            payload: Some(payload),
-        })
-    }
+        payload
+    };
+    Some(EventPayload {
+        kind: msg.get("type").and_then(Value::as_str).unwrap_or(""),
+        info: msg.get("info"),
+        payload: Some(msg),
+    })
 }
 
-fn process_token_count(
+/// Unpriced token deltas carried by a `token_count` event, relative to the
+/// running counters in `totals_map`.
+fn token_count_deltas(
     info: Option<&Value>,
-    timestamp: Option<&str>,
-    model_name: &str,
     totals_map: &mut HashMap<&'static str, u64>,
-    events: &mut Vec<UsageEvent>,
 ) -> Option<UsageTotals> {
-    let usage = info?.get("total_token_usage")?;
+    let usage = token_usage_fields(info?)?;
 
     let mut deltas = UsageTotals::default();
     let mut delta_input = 0u64;
-    let mut delta_cached = 0u64;
 
     for field in TOKEN_FIELDS {
         if let Some(value) = usage.get(field).and_then(Value::as_u64) {
@@ -658,10 +1605,8 @@ fn process_token_count(
             *prev = value;
             match field {
                 "input_tokens" => delta_input = delta,
-                "cached_input_tokens" => {
-                    delta_cached = delta;
-                    deltas.cached_input_tokens = delta;
-                }
+                "cached_input_tokens" => deltas.cached_input_tokens = delta,
+                "cache_creation_input_tokens" => deltas.cache_write_tokens = delta,
                 "output_tokens" => deltas.output_tokens = delta,
                 "reasoning_output_tokens" => deltas.reasoning_output_tokens = delta,
                 "total_tokens" => deltas.total_tokens = delta,
@@ -670,20 +1615,47 @@ fn process_token_count(
         }
     }
 
-    deltas.non_cached_input_tokens = delta_input.saturating_sub(delta_cached);
+    // Cache reads and cache writes are both reported as part of `input_tokens`.
+    deltas.non_cached_input_tokens = delta_input
+        .saturating_sub(deltas.cached_input_tokens)
+        .saturating_sub(deltas.cache_write_tokens);
+    Some(deltas)
+}
 
+/// Sets `deltas.cost_usd` from the rates for `model_name`, returning the
+/// bucket the model falls in and the cost split by token class.
+fn price_token_deltas(
+    deltas: &mut UsageTotals,
+    model_name: &str,
+    settings: &ParseSettings,
+) -> (ModelBucket, SessionCostSplit) {
     let bucket = ModelBucket::from_model_name(model_name);
-    let billable_output = deltas.output_tokens + deltas.reasoning_output_tokens;
-    deltas.cost_usd = estimate_cost(bucket, deltas.non_cached_input_tokens, deltas.cached_input_tokens, billable_output);
+    let rates = settings.rates_for(model_name, bucket);
+    let split = SessionCostSplit {
+        input_cost: tokens_to_cost(deltas.non_cached_input_tokens, rates.non_cached)
+            + tokens_to_cost(deltas.cached_input_tokens, rates.cached)
+            + tokens_to_cost(deltas.cache_write_tokens, rates.cache_write),
+        output_cost: tokens_to_cost(deltas.output_tokens, rates.output),
+        reasoning_cost: tokens_to_cost(deltas.reasoning_output_tokens, rates.reasoning),
+    };
+    deltas.cost_usd = split.total();
+    (bucket, split)
+}
 
-    if let Some(ts) = timestamp.and_then(parse_timestamp) {
-        events.push(UsageEvent {
-            timestamp: ts,
-            deltas: deltas.clone(),
-        });
+/// Locates the token counters inside a `token_count` info payload. Current logs
+/// nest them under `total_token_usage`; older/newer writers use
+/// `last_token_usage` or place the fields directly on `info`.
+fn token_usage_fields(info: &Value) -> Option<&Value> {
+    if let Some(usage) = info.get("total_token_usage").filter(|v| v.is_object()) {
+        return Some(usage);
     }
-
-    Some(deltas)
+    if let Some(usage) = info.get("last_token_usage").filter(|v| v.is_object()) {
+        return Some(usage);
+    }
+    if TOKEN_FIELDS.iter().any(|field| info.get(*field).is_some()) {
+        return Some(info);
+    }
+    None
 }
 
 fn parse_timestamp(raw: &str) -> Option<DateTime<Utc>> {
@@ -702,36 +1674,53 @@ fn compute_time_buckets(
     bucket_count: usize,
     bucket_size: Duration,
     now: DateTime<Utc>,
+    by_model: bool,
 ) -> Vec<UsageBucket> {
-    if bucket_count == 0 {
-        return Vec::new();
-    }
-
-    let end = now;
-    let start = end - bucket_size * (bucket_count as i32);
-    let mut buckets = Vec::with_capacity(bucket_count);
-    for idx in 0..bucket_count {
-        let bucket_start = start + bucket_size * (idx as i32);
-        let bucket_end = bucket_start + bucket_size;
-        buckets.push(UsageBucket {
-            start: bucket_start,
-            end: bucket_end,
-            totals: UsageTotals::default(),
-        });
+    let mut buckets = empty_time_buckets(bucket_count, bucket_size, now, by_model);
+    for event in events {
+        add_to_time_buckets(&mut buckets, bucket_size, event);
     }
+    buckets
+}
 
-    for event in events {
-        if event.timestamp < start || event.timestamp >= end {
-            continue;
-        }
-        let offset = event.timestamp - start;
-        let idx = (offset.num_seconds() / bucket_size.num_seconds()).clamp(0, bucket_count as i64 - 1);
-        if let Some(bucket) = buckets.get_mut(idx as usize) {
-            bucket.totals.add(&event.deltas);
+/// `bucket_count` consecutive zeroed buckets ending at `now`.
+fn empty_time_buckets(
+    bucket_count: usize,
+    bucket_size: Duration,
+    now: DateTime<Utc>,
+    by_model: bool,
+) -> Vec<UsageBucket> {
+    let start = now - bucket_size * (bucket_count as i32);
+    (0..bucket_count)
+        .map(|idx| {
+            let bucket_start = start + bucket_size * (idx as i32);
+            UsageBucket {
+                start: bucket_start,
+                end: bucket_start + bucket_size,
+                totals: UsageTotals::default(),
+                by_model: by_model.then(BTreeMap::new),
+            }
+        })
+        .collect()
+}
+
+fn add_to_time_buckets(buckets: &mut [UsageBucket], bucket_size: Duration, event: &UsageEvent) {
+    let (Some(first), Some(last)) = (buckets.first(), buckets.last()) else {
+        return;
+    };
+    let (start, end) = (first.start, last.end);
+    // Events at exactly `now` (including clamped skew) fall in the last bucket.
+    if event.timestamp < start || event.timestamp > end {
+        return;
+    }
+    let offset = event.timestamp - start;
+    let idx = (offset.num_seconds() / bucket_size.num_seconds()).clamp(0, buckets.len() as i64 - 1);
+    if let Some(bucket) = buckets.get_mut(idx as usize) {
+        bucket.totals.add(&event.deltas);
+        if let Some(by_model) = bucket.by_model.as_mut() {
+            by_model.entry(event.bucket).or_default().add(&event.deltas);
         }
     }
-
-    buckets
 }
 
 fn compute_rolling_usage(
@@ -739,16 +1728,19 @@ fn compute_rolling_usage(
     duration: Duration,
     now: DateTime<Utc>,
 ) -> UsageTotals {
-    let window_start = now - duration;
     let mut totals = UsageTotals::default();
     for event in events {
-        if event.timestamp >= window_start && event.timestamp <= now {
+        if in_rolling_window(event.timestamp, duration, now) {
             totals.add(&event.deltas);
         }
     }
     totals
 }
 
+fn in_rolling_window(timestamp: DateTime<Utc>, duration: Duration, now: DateTime<Utc>) -> bool {
+    timestamp >= now - duration && timestamp <= now
+}
+
 impl ModelBucket {
     pub fn from_model_name(model: &str) -> Self {
         let normalized = model.to_lowercase();
@@ -807,13 +1799,40 @@ fn load_snapshot_model(path: &Path) -> Option<String> {
     None
 }
 
-fn estimate_cost(
-    bucket: ModelBucket,
-    non_cached: u64,
-    cached: u64,
-    output: u64,
-) -> f64 {
-    let (non_cached_rate, cached_rate, output_rate) = match bucket {
+/// USD per million tokens for each token class.
+#[derive(Debug, Clone, Copy)]
+struct BucketRates {
+    non_cached: f64,
+    cached: f64,
+    /// Rate for input tokens written to the prompt cache.
+    cache_write: f64,
+    output: f64,
+    /// Reasoning tokens are currently billed as output for every bucket.
+    reasoning: f64,
+}
+
+/// Providers that bill cache writes separately charge a premium over the
+/// regular input rate.
+const CACHE_WRITE_RATE_MULTIPLIER: f64 = 1.25;
+
+impl BucketRates {
+    fn new(non_cached: f64, cached: f64, output: f64) -> Self {
+        Self {
+            non_cached,
+            cached,
+            cache_write: non_cached * CACHE_WRITE_RATE_MULTIPLIER,
+            output,
+            reasoning: output,
+        }
+    }
+
+    fn from_provider(rate: &ProviderRate) -> Self {
+        Self::new(rate.non_cached, rate.cached, rate.output)
+    }
+}
+
+fn bucket_rates(bucket: ModelBucket) -> BucketRates {
+    let (non_cached, cached, output) = match bucket {
         ModelBucket::Gpt5
         | ModelBucket::Gpt5Codex
         | ModelBucket::Gpt51
@@ -827,10 +1846,19 @@ fn estimate_cost(
         | ModelBucket::ChatGpt51CodexMini => (0.25, 0.025, 2.0),
         ModelBucket::Other => (1.25, 0.125, 10.0),
     };
+    BucketRates::new(non_cached, cached, output)
+}
 
-    tokens_to_cost(non_cached, non_cached_rate)
-        + tokens_to_cost(cached, cached_rate)
-        + tokens_to_cost(output, output_rate)
+pub(crate) fn estimate_cost(
+    bucket: ModelBucket,
+    non_cached: u64,
+    cached: u64,
+    output: u64,
+) -> f64 {
+    let rates = bucket_rates(bucket);
+    tokens_to_cost(non_cached, rates.non_cached)
+        + tokens_to_cost(cached, rates.cached)
+        + tokens_to_cost(output, rates.output)
 }
 
 fn tokens_to_cost(tokens: u64, rate: f64) -> f64 {
@@ -841,6 +1869,28 @@ fn tokens_to_cost(tokens: u64, rate: f64) -> f64 {
     }
 }
 
+/// Cost decimals used by usage renderers unless the caller asks otherwise.
+pub const DEFAULT_COST_PRECISION: usize = 2;
+
+/// Most decimals `format_cost` adds to keep a tiny nonzero cost visible.
+const MAX_COST_DECIMALS: usize = 10;
+
+/// Formats a USD cost as `$1.23` with `precision` decimals. Nonzero costs that
+/// would round to zero get extra decimals until one significant figure shows
+/// (`$0.0001` rather than `$0.00`).
+pub fn format_cost(usd: f64, precision: usize) -> String {
+    let magnitude = usd.abs();
+    let mut decimals = precision;
+    while magnitude > 0.0
+        && decimals < MAX_COST_DECIMALS
+        && (magnitude * 10f64.powi(decimals as i32)).round() == 0.0
+    {
+        decimals += 1;
+    }
+    let sign = if usd < 0.0 && magnitude > 0.0 { "-" } else { "" };
+    format!("{sign}${magnitude:.decimals$}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -889,63 +1939,1298 @@ mod tests {
         })
     }
 
+    fn token_event_with_info(timestamp: &str, info: Value) -> Value {
+        json!({
+            "type":"event_msg",
+            "timestamp": timestamp,
+            "payload":{
+                "type":"token_count",
+                "info": info
+            }
+        })
+    }
+
+    fn usage_fields(input: u64, cached: u64, output: u64, reasoning: u64, total: u64) -> Value {
+        json!({
+            "input_tokens":input,
+            "cached_input_tokens":cached,
+            "output_tokens":output,
+            "reasoning_output_tokens":reasoning,
+            "total_tokens":total
+        })
+    }
+
     #[test]
-    fn aggregates_simple_session() {
+    fn reasoning_effort_in_turn_context_splits_usage_by_effort() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        write_session(
+            &sessions,
+            "tagged",
+            &[
+                session_meta("tagged", "gpt-5.1-codex"),
+                token_event("2025-11-19T08:00:00Z", 40, 0, 0, 0, 40),
+                json!({"type":"turn_context","payload":{"model":"gpt-5.1-codex","reasoning_effort":"high"}}),
+                token_event("2025-11-19T09:00:00Z", 540, 0, 0, 0, 540),
+                json!({"type":"turn_context","payload":{"model":"gpt-5.1-codex","effort":"Low"}}),
+                token_event("2025-11-19T10:00:00Z", 600, 0, 0, 0, 600),
+                json!({"type":"turn_context","payload":{"model":"gpt-5.1-codex","reasoning_effort":"high"}}),
+                token_event("2025-11-19T11:00:00Z", 800, 0, 0, 0, 800),
+            ],
+        );
+        write_session(
+            &sessions,
+            "medium",
+            &[
+                session_meta("medium", "gpt-5"),
+                json!({"type":"turn_context","payload":{"model":"gpt-5","reasoning_effort":"medium"}}),
+                token_event("2025-11-19T11:00:00Z", 25, 0, 0, 0, 25),
+            ],
+        );
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let options =
+            GlobalUsageScanOptions::new(temp.path().join(".code")).with_sessions_override(sessions.clone());
+
+        let snapshot = scan_global_usage_at(options.clone(), now).expect("scan");
+        let split: Vec<(&str, u64)> = snapshot
+            .effort_usage
+            .iter()
+            .map(|(effort, totals)| (effort.as_str(), totals.total_tokens))
+            .collect();
+        assert_eq!(split, vec![("high", 700), ("low", 60), ("medium", 25)]);
+
+        fs::remove_file(sessions.join("tagged.jsonl")).expect("remove tagged");
+        fs::remove_file(sessions.join("medium.jsonl")).expect("remove medium");
+        write_session(
+            &sessions,
+            "untagged",
+            &[session_meta("untagged", "gpt-5"), token_event("2025-11-19T11:00:00Z", 20, 0, 0, 0, 20)],
+        );
+        let snapshot = scan_global_usage_at(options, now).expect("rescan");
+        assert!(snapshot.effort_usage.is_empty());
+    }
+
+    #[test]
+    fn account_switch_mid_session_splits_usage_by_account() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        write_session(
+            &sessions,
+            "switched",
+            &[
+                session_meta("switched", "gpt-5.1-codex"),
+                token_event("2025-11-19T08:00:00Z", 50, 0, 0, 0, 50),
+                json!({"type":"turn_context","payload":{"model":"gpt-5.1-codex","account_id":"acct-a"}}),
+                token_event("2025-11-19T09:00:00Z", 350, 0, 0, 0, 350),
+                json!({"type":"turn_context","payload":{"model":"gpt-5.1-codex","account_id":"acct-b"}}),
+                token_event("2025-11-19T10:00:00Z", 1_350, 0, 0, 0, 1_350),
+            ],
+        );
+        write_session(
+            &sessions,
+            "plain",
+            &[session_meta("plain", "gpt-5"), token_event("2025-11-19T11:00:00Z", 20, 0, 0, 0, 20)],
+        );
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let options =
+            GlobalUsageScanOptions::new(temp.path().join(".code")).with_sessions_override(sessions.clone());
+
+        let snapshot = scan_global_usage_at(options.clone(), now).expect("scan");
+        let split: Vec<(&str, u64)> = snapshot
+            .by_account
+            .iter()
+            .map(|entry| (entry.account.as_str(), entry.totals.total_tokens))
+            .collect();
+        assert_eq!(split, vec![("acct-b", 1_000), ("acct-a", 300), (UNATTRIBUTED_ACCOUNT, 70)]);
+        let summed: u64 = snapshot.by_account.iter().map(|entry| entry.totals.total_tokens).sum();
+        assert_eq!(summed, snapshot.totals.total_tokens);
+
+        fs::remove_file(sessions.join("switched.jsonl")).expect("remove session");
+        let snapshot = scan_global_usage_at(options, now).expect("scan");
+        assert!(snapshot.by_account.is_empty());
+    }
+
+    #[test]
+    fn format_cost_honors_precision_and_keeps_tiny_costs_visible() {
+        assert_eq!(format_cost(12.345, 0), "$12");
+        assert_eq!(format_cost(0.4, 0), "$0.4");
+        assert_eq!(format_cost(0.0, 0), "$0");
+        assert_eq!(format_cost(12.346, 2), "$12.35");
+        assert_eq!(format_cost(0.0001, 2), "$0.0001");
+        assert_eq!(format_cost(0.00987, 2), "$0.01");
+        assert_eq!(format_cost(0.0, 2), "$0.00");
+        assert_eq!(format_cost(-1.5, 2), "-$1.50");
+        assert_eq!(format_cost(12.345_67, 4), "$12.3457");
+        assert_eq!(format_cost(0.000_004, 4), "$0.000004");
+    }
+
+    #[test]
+    fn model_buckets_map_to_known_display_groups() {
+        let expected = [
+            (ModelBucket::Gpt5, "gpt-5"),
+            (ModelBucket::Gpt5Codex, "gpt-5-codex"),
+            (ModelBucket::Gpt5Mini, "gpt-5-codex-mini"),
+            (ModelBucket::Gpt51, "gpt-5"),
+            (ModelBucket::Gpt51Codex, "gpt-5-codex"),
+            (ModelBucket::Gpt51CodexMini, "gpt-5-codex-mini"),
+            (ModelBucket::CodeGpt5Codex, "gpt-5-codex"),
+            (ModelBucket::CodeGpt5CodexMini, "gpt-5-codex-mini"),
+            (ModelBucket::CodeGpt5Mini, "gpt-5-codex-mini"),
+            (ModelBucket::ChatGpt51Codex, "gpt-5-codex"),
+            (ModelBucket::ChatGpt51CodexMini, "gpt-5-codex-mini"),
+            (ModelBucket::Other, "other"),
+        ];
+        for (bucket, group) in expected {
+            assert_eq!(bucket.display_group(), group, "{bucket:?}");
+            assert!(ModelBucket::DISPLAY_GROUPS.contains(&bucket.display_group()));
+        }
+        for group in ModelBucket::DISPLAY_GROUPS {
+            assert!(expected.iter().any(|(_, expected)| *expected == group), "empty group {group}");
+        }
+    }
+
+    #[test]
+    fn token_usage_shapes_produce_identical_totals() {
+        let counters = [
+            ("2025-11-19T00:00:00Z", usage_fields(100, 30, 50, 10, 190)),
+            ("2025-11-19T00:05:00Z", usage_fields(110, 35, 60, 15, 230)),
+            ("2025-11-19T00:10:00Z", usage_fields(105, 40, 100, 25, 270)),
+        ];
+        type WrapUsage = fn(Value) -> Value;
+        let shapes: [(&str, WrapUsage); 3] = [
+            ("total", |usage| json!({ "total_token_usage": usage })),
+            ("last", |usage| json!({ "last_token_usage": usage })),
+            ("flat", |usage| usage),
+        ];
+
+        let mut results = Vec::new();
+        for (name, wrap) in shapes {
+            let temp = TempDir::new().expect("tempdir");
+            let code_home = temp.path().join(".code");
+            let sessions = code_home.join(SESSIONS_SUBDIR);
+            fs::create_dir_all(&sessions).expect("session dir");
+
+            let mut lines = vec![session_meta(name, "gpt-5.1-codex")];
+            for (timestamp, usage) in &counters {
+                lines.push(token_event_with_info(timestamp, wrap(usage.clone())));
+            }
+            write_session(&sessions, name, &lines);
+
+            let options = GlobalUsageScanOptions::new(code_home)
+                .with_sessions_override(sessions.clone());
+            let snapshot = scan_global_usage(options).expect("scan");
+            assert_eq!(snapshot.sessions_processed, 1, "shape {name}");
+            results.push((name, snapshot.totals));
+        }
+
+        for (name, totals) in &results {
+            assert_eq!(totals.non_cached_input_tokens, 75, "shape {name}");
+            assert_eq!(totals.cached_input_tokens, 40, "shape {name}");
+            assert_eq!(totals.output_tokens, 100, "shape {name}");
+            assert_eq!(totals.reasoning_output_tokens, 25, "shape {name}");
+            assert_eq!(totals.total_tokens, 270, "shape {name}");
+            assert!((totals.cost_usd - results[0].1.cost_usd).abs() < 1e-9, "shape {name}");
+        }
+    }
+
+    fn nested_token_event(timestamp: &str, info: Value) -> Value {
+        json!({
+            "type":"event",
+            "timestamp": timestamp,
+            "payload":{
+                "id":"sub-1",
+                "event_seq":0,
+                "msg":{
+                    "type":"token_count",
+                    "info": info,
+                    "rate_limits": null
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn nested_event_reads_info_from_msg() {
+        let info = json!({ "total_token_usage": usage_fields(10, 0, 5, 0, 15) });
+        let nested = nested_token_event("2025-11-19T00:00:00Z", info.clone());
+        let flat = token_event_with_info("2025-11-19T00:00:00Z", info.clone());
+
+        for entry in [&nested, &flat] {
+            let payload = extract_event_payload(entry).expect("payload");
+            assert_eq!(payload.kind, "token_count");
+            assert_eq!(payload.info, Some(&info));
+        }
+        let without_msg = json!({"type":"event","payload":{"type":"token_count","info":info}});
+        assert!(extract_event_payload(&without_msg).is_none());
+    }
+
+    #[test]
+    fn event_and_event_msg_envelopes_produce_identical_totals() {
+        let counters = [
+            ("2025-11-19T00:00:00Z", usage_fields(100, 30, 50, 10, 190)),
+            ("2025-11-19T00:05:00Z", usage_fields(110, 35, 60, 15, 230)),
+            ("2025-11-19T01:10:00Z", usage_fields(105, 40, 100, 25, 270)),
+        ];
+        type WrapEvent = fn(&str, Value) -> Value;
+        let envelopes: [(&str, WrapEvent); 2] = [
+            ("event_msg", token_event_with_info),
+            ("event", nested_token_event),
+        ];
+
+        let mut snapshots = Vec::new();
+        for (name, wrap) in envelopes {
+            let temp = TempDir::new().expect("tempdir");
+            let code_home = temp.path().join(".code");
+            let sessions = code_home.join(SESSIONS_SUBDIR);
+            fs::create_dir_all(&sessions).expect("session dir");
+
+            let mut lines = vec![session_meta(name, "gpt-5.1-codex")];
+            for (timestamp, usage) in &counters {
+                lines.push(wrap(timestamp, json!({ "total_token_usage": usage })));
+            }
+            write_session(&sessions, name, &lines);
+
+            let options = GlobalUsageScanOptions::new(code_home)
+                .with_sessions_override(sessions.clone());
+            let snapshot = scan_global_usage(options).expect("scan");
+            assert_eq!(snapshot.sessions_processed, 1, "envelope {name}");
+            snapshots.push((name, snapshot));
+        }
+
+        let summary = |snapshot: &GlobalUsageSnapshot| {
+            let totals = &snapshot.totals;
+            (
+                totals.non_cached_input_tokens,
+                totals.cached_input_tokens,
+                totals.output_tokens,
+                totals.reasoning_output_tokens,
+                totals.total_tokens,
+            )
+        };
+        let (_, expected) = &snapshots[0];
+        assert_eq!(summary(expected), (75, 40, 100, 25, 270));
+        for (name, snapshot) in &snapshots[1..] {
+            assert_eq!(summary(snapshot), summary(expected), "envelope {name}");
+            assert!(
+                (snapshot.totals.cost_usd - expected.totals.cost_usd).abs() < 1e-9,
+                "envelope {name}"
+            );
+        }
+    }
+
+    #[test]
+    fn day_buckets_attribute_events_to_requested_days() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let at = |days: i64| (now - Duration::days(days) - Duration::hours(1)).to_rfc3339();
+        write_session(
+            &sessions,
+            "month",
+            &[
+                session_meta("month", "gpt-5.1-codex"),
+                token_event(&at(31), 100, 0, 0, 0, 100),
+                token_event(&at(29), 300, 0, 0, 0, 300),
+                token_event(&at(5), 700, 0, 0, 0, 700),
+                token_event(&at(0), 1_500, 0, 0, 0, 1_500),
+            ],
+        );
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions)
+            .with_day_buckets(30);
+
+        let snapshot = scan_global_usage_at(options, now).expect("scan");
+        assert_eq!(snapshot.day_buckets.len(), 30);
+        assert_eq!(snapshot.day_buckets[0].start, now - Duration::days(30));
+        assert_eq!(snapshot.day_buckets[29].end, now);
+        let tokens: Vec<u64> = snapshot
+            .day_buckets
+            .iter()
+            .map(|bucket| bucket.totals.total_tokens)
+            .collect();
+        let mut expected = vec![0; 30];
+        expected[0] = 200;
+        expected[24] = 400;
+        expected[29] = 800;
+        assert_eq!(tokens, expected);
+        assert_eq!(snapshot.totals.total_tokens, 1_500);
+        assert_eq!(snapshot.daily_buckets.len(), 7);
+    }
+
+    #[test]
+    fn summary_only_skips_buckets_but_keeps_totals() {
         let temp = TempDir::new().expect("tempdir");
         let code_home = temp.path().join(".code");
         let sessions = code_home.join(SESSIONS_SUBDIR);
         fs::create_dir_all(&sessions).expect("session dir");
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        write_session(
+            &sessions,
+            "recent",
+            &[
+                session_meta("recent", "gpt-5.1-codex"),
+                token_event("2025-11-19T10:30:00Z", 100, 20, 40, 10, 170),
+                token_event("2025-11-19T11:30:00Z", 150, 30, 60, 20, 260),
+            ],
+        );
 
-        let log_path = sessions.join("sess-1.jsonl");
-        fs::write(
-            &log_path,
-            r#"{"type":"session_meta","payload":{"id":"sess-1","model":"gpt-5.1-codex"}}
-{"type":"event_msg","timestamp":"2025-11-19T00:00:00Z","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":10,"cached_input_tokens":2,"output_tokens":5,"reasoning_output_tokens":1,"total_tokens":16}}}}
-{"type":"event_msg","timestamp":"2025-11-19T00:10:00Z","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":30,"cached_input_tokens":6,"output_tokens":25,"reasoning_output_tokens":4,"total_tokens":65}}}}
-"#,
-        )
-        .expect("write log");
+        let scan = |summary_only: bool| {
+            let options = GlobalUsageScanOptions::new(code_home.clone())
+                .with_sessions_override(sessions.clone())
+                .with_summary_only(summary_only);
+            scan_global_usage_at(options, now).expect("scan")
+        };
+        let full = scan(false);
+        let summary = scan(true);
+
+        assert!(!full.hourly_buckets.is_empty());
+        assert_eq!(full.trailing.last_day.total_tokens, 260);
+        for buckets in [
+            &summary.hourly_buckets,
+            &summary.twelve_hour_buckets,
+            &summary.daily_buckets,
+            &summary.weekly_buckets,
+            &summary.monthly_buckets,
+        ] {
+            assert!(buckets.is_empty());
+        }
+        assert_eq!(summary.trailing.last_day.total_tokens, 0);
+
+        assert_eq!(summary.totals.total_tokens, full.totals.total_tokens);
+        assert!((summary.totals.cost_usd - full.totals.cost_usd).abs() < 1e-9);
+        assert_eq!(summary.model_usage.len(), full.model_usage.len());
+        assert_eq!(summary.source_usage.len(), full.source_usage.len());
+        assert_eq!(summary.sessions_processed, full.sessions_processed);
+
+        let settings = ParseSettings {
+            cache_write_rate: None,
+            since: None,
+            until: None,
+            provider_rates: ProviderRates::default(),
+            timeline: None,
+            summary_only: true,
+        };
+        let parsed = parse_session_log(&sessions.join("recent.jsonl"), ".code", &settings)
+            .expect("parse");
+        assert!(parsed.events.is_empty());
+        assert_eq!(parsed.all_time_totals.map(|totals| totals.total_tokens), Some(260));
+    }
+
+    #[test]
+    fn worker_pool_never_exceeds_file_count() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&sessions).expect("session dir");
+        for name in ["first", "second"] {
+            write_session(
+                &sessions,
+                name,
+                &[
+                    session_meta(name, "gpt-5"),
+                    token_event("2025-11-19T00:00:00Z", 10, 0, 5, 0, 15),
+                ],
+            );
+        }
 
         let options = GlobalUsageScanOptions::new(code_home)
-            .with_sessions_override(sessions.clone());
+            .with_sessions_override(sessions)
+            .with_max_workers(16);
         let snapshot = scan_global_usage(options).expect("scan");
-        assert_eq!(snapshot.sessions_processed, 1);
-        assert_eq!(snapshot.sessions_missing_totals, 0);
-        assert_eq!(snapshot.totals.non_cached_input_tokens, 24); // (10-2)+(20-4)
-        assert_eq!(snapshot.totals.output_tokens, 25);
-        assert_eq!(snapshot.totals.reasoning_output_tokens, 4);
-        assert_eq!(snapshot.model_usage.len(), 1);
-        assert_eq!(snapshot.source_usage.len(), 1);
+
+        assert_eq!(snapshot.sessions_processed, 2);
+        let requested = REQUESTED_POOL_SIZE.with(|size| size.get()).expect("pool size observed");
+        assert!(requested <= 2, "requested {requested} workers for 2 files");
     }
 
     #[test]
-    fn monotonic_deltas_never_double_count() {
+    fn future_timestamps_are_dropped_from_recent_windows() {
         let temp = TempDir::new().expect("tempdir");
         let code_home = temp.path().join(".code");
         let sessions = code_home.join(SESSIONS_SUBDIR);
         fs::create_dir_all(&sessions).expect("session dir");
-
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
         write_session(
             &sessions,
-            "sess-rolling",
+            "skewed",
             &[
-                session_meta("sess-rolling", "gpt-5.1-codex"),
-                token_event("2025-11-19T00:00:00Z", 100, 30, 50, 10, 190),
-                token_event("2025-11-19T00:05:00Z", 110, 35, 60, 15, 230),
-                token_event("2025-11-19T00:10:00Z", 105, 40, 100, 25, 270),
+                session_meta("skewed", "gpt-5"),
+                token_event("2025-11-19T11:30:00Z", 100, 0, 20, 0, 120),
+                token_event("2025-11-19T11:59:00Z", 150, 0, 30, 0, 180),
+                // Ten minutes ahead of the scan clock.
+                token_event("2025-11-19T12:10:00Z", 400, 0, 100, 0, 500),
             ],
         );
 
         let options = GlobalUsageScanOptions::new(code_home)
-            .with_sessions_override(sessions.clone());
-        let snapshot = scan_global_usage(options).expect("scan");
+            .with_sessions_override(sessions);
+        let snapshot = scan_global_usage_at(options, now).expect("scan");
 
-        assert_eq!(snapshot.sessions_processed, 1);
-        assert_eq!(snapshot.totals.non_cached_input_tokens, 75);
-        assert_eq!(snapshot.totals.cached_input_tokens, 40);
-        assert_eq!(snapshot.totals.output_tokens, 100);
-        assert_eq!(snapshot.totals.reasoning_output_tokens, 25);
-        assert_eq!(snapshot.totals.total_tokens, 270);
+        assert_eq!(snapshot.future_timestamp_events, 1);
+        assert_eq!(snapshot.trailing.last_hour.total_tokens, 180);
+        let hourly_total: u64 = snapshot
+            .hourly_buckets
+            .iter()
+            .map(|bucket| bucket.totals.total_tokens)
+            .sum();
+        assert_eq!(hourly_total, 180);
+    }
+
+    #[test]
+    fn cache_write_tokens_are_tracked_and_costed_separately() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        let mut usage = usage_fields(1_000_000, 200_000, 0, 0, 1_000_000);
+        usage["cache_creation_input_tokens"] = json!(400_000);
+        write_session(
+            &sessions,
+            "cache-write",
+            &[
+                session_meta("cache-write", "gpt-5.1-codex"),
+                token_event_with_info("2025-11-19T00:00:00Z", json!({ "total_token_usage": usage })),
+            ],
+        );
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions)
+            .with_record_sessions(true);
+
+        let snapshot = scan_global_usage(options.clone()).expect("scan");
+        assert_eq!(snapshot.totals.cache_write_tokens, 400_000);
+        assert_eq!(snapshot.totals.cached_input_tokens, 200_000);
+        assert_eq!(snapshot.totals.non_cached_input_tokens, 400_000);
+        // 0.4M × $1.25 + 0.2M × $0.125 + 0.4M cache writes × $1.5625 (1.25× input).
+        assert!((snapshot.totals.cost_usd - 1.15).abs() < 1e-9, "{}", snapshot.totals.cost_usd);
+
+        let custom = scan_global_usage(options.with_cache_write_rate(3.0)).expect("scan");
+        assert_eq!(custom.totals.cache_write_tokens, 400_000);
+        assert!((custom.totals.cost_usd - 1.725).abs() < 1e-9, "{}", custom.totals.cost_usd);
+        let split = custom.per_session[0].cost_split;
+        assert!((split.input_cost - 1.725).abs() < 1e-9, "{}", split.input_cost);
+        assert!((split.total() - custom.totals.cost_usd).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bucket_model_breakdown_splits_window_by_model() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        write_session(
+            &sessions,
+            "codex",
+            &[
+                session_meta("codex", "gpt-5.1-codex"),
+                token_event("2025-11-19T11:30:00Z", 800, 0, 200, 0, 1_000),
+            ],
+        );
+        write_session(
+            &sessions,
+            "mini",
+            &[
+                session_meta("mini", "gpt-5.1-codex-mini"),
+                token_event("2025-11-19T11:40:00Z", 150, 0, 50, 0, 200),
+            ],
+        );
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions);
+
+        let plain = scan_global_usage_at(options.clone(), now).expect("scan");
+        assert!(plain.hourly_buckets.iter().all(|bucket| bucket.by_model.is_none()));
+
+        let snapshot =
+            scan_global_usage_at(options.with_bucket_model_breakdown(true), now).expect("scan");
+        let last_hour = snapshot.hourly_buckets.last().expect("hourly bucket");
+        assert_eq!(last_hour.totals.total_tokens, 1_200);
+        let by_model = last_hour.by_model.as_ref().expect("breakdown");
+        assert_eq!(by_model.len(), 2);
+        assert_eq!(by_model[&ModelBucket::Gpt51Codex].total_tokens, 1_000);
+        assert_eq!(by_model[&ModelBucket::Gpt51CodexMini].total_tokens, 200);
+        assert_eq!(last_hour.top_model().map(|(bucket, _)| bucket), Some(ModelBucket::Gpt51Codex));
+        assert!(snapshot.hourly_buckets[0].by_model.as_ref().is_some_and(BTreeMap::is_empty));
+    }
+
+    #[test]
+    fn provider_rates_file_overrides_matching_model_costs() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        write_session(
+            &sessions,
+            "sonnet",
+            &[
+                session_meta("sonnet", "claude-sonnet-4"),
+                token_event("2025-11-19T10:00:00Z", 1_000_000, 0, 100_000, 0, 1_100_000),
+            ],
+        );
+        write_session(
+            &sessions,
+            "codex",
+            &[
+                session_meta("codex", "gpt-5.1-codex"),
+                token_event("2025-11-19T10:00:00Z", 1_000_000, 0, 0, 0, 1_000_000),
+            ],
+        );
+        let rates_path = temp.path().join("rates.toml");
+        fs::write(
+            &rates_path,
+            "[claude]\nnon_cached = 1.0\ncached = 0.1\noutput = 5.0\n\n\
+             [claude-sonnet]\nnon_cached = 3.0\ncached = 0.3\noutput = 15.0\n",
+        )
+        .expect("write rates");
+        let rates = ProviderRates::load(&rates_path).expect("load rates");
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions)
+            .with_provider_rates(rates);
+
+        let snapshot = scan_global_usage_at(options, now).expect("scan");
+        // Sonnet: 1M input at $3 + 100k output at $15 = $4.50; codex keeps the
+        // built-in $1.25 per 1M input.
+        assert!((snapshot.totals.cost_usd - 5.75).abs() < 1e-9, "{}", snapshot.totals.cost_usd);
+
+        let json_path = temp.path().join("rates.json");
+        fs::write(&json_path, r#"{"sonnet": {"non_cached": 3.0, "cached": 0.3, "output": 15.0}}"#)
+            .expect("write json rates");
+        let json_rates = ProviderRates::load(&json_path).expect("load json rates");
+        assert_eq!(
+            json_rates.rate_for("Claude-Sonnet-4").map(|rate| rate.output),
+            Some(15.0)
+        );
+        assert!(json_rates.rate_for("gpt-5").is_none());
+    }
+
+    #[test]
+    fn provider_rates_cost_split_sums_to_session_cost() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        write_session(
+            &sessions,
+            "sonnet",
+            &[
+                session_meta("sonnet", "claude-sonnet-4"),
+                token_event("2025-11-19T10:00:00Z", 1_000_000, 200_000, 100_000, 50_000, 1_150_000),
+            ],
+        );
+        let rates_path = temp.path().join("rates.toml");
+        fs::write(&rates_path, "[claude-sonnet]\nnon_cached = 3.0\ncached = 0.3\noutput = 15.0\n")
+            .expect("write rates");
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions)
+            .with_provider_rates(ProviderRates::load(&rates_path).expect("load rates"))
+            .with_record_sessions(true);
+
+        let snapshot = scan_global_usage(options).expect("scan");
+        let session = &snapshot.per_session[0];
+        // 800k input at $3 + 200k cached at $0.30 + 150k output/reasoning at $15.
+        assert!((session.totals.cost_usd - 4.71).abs() < 1e-9, "{}", session.totals.cost_usd);
+        let split = session.cost_split;
+        assert!((split.reasoning_cost - 0.75).abs() < 1e-9, "{}", split.reasoning_cost);
+        assert!((split.total() - session.totals.cost_usd).abs() < 1e-9);
+    }
+
+    #[test]
+    fn date_window_limits_totals_but_not_all_time_totals() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        write_session(
+            &sessions,
+            "spanning",
+            &[
+                session_meta("spanning", "gpt-5"),
+                token_event("2025-10-15T10:00:00Z", 1_000, 0, 0, 0, 1_000),
+                token_event("2025-11-10T10:00:00Z", 1_400, 0, 0, 0, 1_400),
+            ],
+        );
+        write_session(
+            &sessions,
+            "old",
+            &[
+                session_meta("old", "gpt-5"),
+                token_event("2025-09-01T10:00:00Z", 500, 0, 0, 0, 500),
+            ],
+        );
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let since = Utc.with_ymd_and_hms(2025, 11, 1, 0, 0, 0).unwrap();
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions);
+
+        let unfiltered = scan_global_usage_at(options.clone(), now).expect("scan");
+        assert_eq!(unfiltered.totals.total_tokens, 1_900);
+        assert_eq!(unfiltered.all_time_totals.total_tokens, 1_900);
+
+        let windowed =
+            scan_global_usage_at(options.with_date_window(Some(since), None), now).expect("scan");
+        assert_eq!(windowed.totals.total_tokens, 400);
+        assert_eq!(windowed.all_time_totals.total_tokens, 1_900);
+        assert!((windowed.all_time_totals.cost_usd - unfiltered.totals.cost_usd).abs() < 1e-9);
+        assert_eq!(windowed.sessions_processed, 1);
+        assert_eq!(windowed.sessions_missing_totals, 0);
+    }
+
+    #[test]
+    fn session_without_any_model_hint_is_marked_inferred() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        write_session(
+            &sessions,
+            "bare",
+            &[
+                json!({"type":"session_meta","payload":{"id":"bare"}}),
+                token_event("2025-11-19T10:00:00Z", 100, 0, 50, 0, 150),
+            ],
+        );
+        // The model only appears after the first token count.
+        write_session(
+            &sessions,
+            "late",
+            &[
+                json!({"type":"session_meta","payload":{"id":"late"}}),
+                token_event("2025-11-19T10:00:00Z", 100, 0, 50, 0, 150),
+                json!({"type":"turn_context","payload":{"model":"gpt-5.1-codex-mini"}}),
+            ],
+        );
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions)
+            .with_record_sessions(true);
+
+        let snapshot = scan_global_usage_at(options, now).expect("scan");
+        let session = |id: &str| {
+            snapshot
+                .per_session
+                .iter()
+                .find(|session| session.session_id == id)
+                .unwrap_or_else(|| panic!("missing session {id}"))
+        };
+        assert!(session("bare").model_inferred);
+        assert_eq!(session("bare").model_bucket, ModelBucket::Gpt5);
+        assert!(!session("late").model_inferred);
+        assert_eq!(session("late").model_bucket, ModelBucket::Gpt51CodexMini);
+        // Same tokens, but the early event is now priced at mini rates.
+        assert!(session("late").totals.cost_usd < session("bare").totals.cost_usd);
+    }
+
+    #[test]
+    fn timeline_cap_samples_old_events_but_keeps_recent_windows_exact() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let mut lines = vec![session_meta("years", "gpt-5.1-codex")];
+        let mut cumulative = 0;
+        let mut push = |at: DateTime<Utc>, tokens: u64| {
+            cumulative += tokens;
+            lines.push(token_event(&at.to_rfc3339(), cumulative, 0, 0, 0, cumulative));
+        };
+        for day in (10..200).rev() {
+            push(now - Duration::days(day), 1_000 + day as u64);
+        }
+        for hours in [150, 30, 11, 3] {
+            push(now - Duration::hours(hours), 10 * hours as u64);
+        }
+        push(now - Duration::minutes(20), 7);
+        write_session(&sessions, "years", &lines);
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions);
+
+        let exact = scan_global_usage_at(options.clone(), now).expect("scan");
+        let sampled =
+            scan_global_usage_at(options.with_max_timeline_events(20), now).expect("sampled scan");
+
+        assert!(!exact.timeline_sampled);
+        assert!(sampled.timeline_sampled);
+        assert_eq!(sampled.totals.total_tokens, exact.totals.total_tokens);
+        let windows = |snapshot: &GlobalUsageSnapshot| {
+            let trailing = &snapshot.trailing;
+            [
+                trailing.last_hour.total_tokens,
+                trailing.last_twelve_hours.total_tokens,
+                trailing.last_day.total_tokens,
+                trailing.last_seven_days.total_tokens,
+            ]
+        };
+        assert_eq!(windows(&sampled), windows(&exact));
+        assert_eq!(windows(&exact), [7, 147, 147, 1_947]);
+        let bucket_tokens = |buckets: &[UsageBucket]| -> Vec<u64> {
+            buckets.iter().map(|bucket| bucket.totals.total_tokens).collect()
+        };
+        for (a, b) in [
+            (&sampled.hourly_buckets, &exact.hourly_buckets),
+            (&sampled.twelve_hour_buckets, &exact.twelve_hour_buckets),
+            (&sampled.daily_buckets, &exact.daily_buckets),
+        ] {
+            assert_eq!(bucket_tokens(a), bucket_tokens(b));
+        }
+        let year = sampled.trailing.last_year.total_tokens as f64;
+        let exact_year = exact.trailing.last_year.total_tokens as f64;
+        assert!((year - exact_year).abs() / exact_year < 0.5, "{year} vs {exact_year}");
+    }
+
+    #[test]
+    fn low_memory_scan_matches_retained_event_timeline() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        write_session(
+            &sessions,
+            "long",
+            &[
+                session_meta("long", "gpt-5.1-codex"),
+                token_event("2025-10-01T09:00:00Z", 500, 100, 200, 50, 700),
+                token_event("2025-11-12T09:00:00Z", 1_500, 300, 600, 100, 2_100),
+                token_event("2025-11-19T02:00:00Z", 2_000, 400, 900, 150, 2_900),
+                token_event("2025-11-19T11:59:00Z", 3_000, 900, 1_000, 200, 4_000),
+                token_event("2025-11-19T12:30:00Z", 3_500, 900, 1_100, 200, 4_600),
+            ],
+        );
+        write_session(
+            &sessions,
+            "mini",
+            &[
+                session_meta("mini", "gpt-5.1-codex-mini"),
+                token_event("2025-11-18T20:00:00Z", 150, 0, 50, 0, 200),
+                token_event("2025-11-19T11:00:00Z", 450, 100, 150, 0, 600),
+            ],
+        );
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let options = GlobalUsageScanOptions::new(temp.path().join(".code"))
+            .with_sessions_override(sessions)
+            .with_bucket_model_breakdown(true)
+            .with_custom_windows(vec![Duration::hours(6), Duration::days(10)])
+            .with_day_buckets(60);
+
+        let retained = scan_global_usage_at(options.clone(), now).expect("scan");
+        let streamed = scan_global_usage_at(options.with_low_memory(true), now).expect("scan");
+
+        fn assert_totals_match(a: &UsageTotals, b: &UsageTotals) {
+            assert_eq!(a.non_cached_input_tokens, b.non_cached_input_tokens);
+            assert_eq!(a.cached_input_tokens, b.cached_input_tokens);
+            assert_eq!(a.output_tokens, b.output_tokens);
+            assert_eq!(a.reasoning_output_tokens, b.reasoning_output_tokens);
+            assert_eq!(a.total_tokens, b.total_tokens);
+            assert!((a.cost_usd - b.cost_usd).abs() < 1e-9);
+        }
+
+        assert_totals_match(&retained.totals, &streamed.totals);
+        assert_eq!(retained.future_timestamp_events, 1);
+        assert_eq!(streamed.future_timestamp_events, 1);
+        for (a, b) in [
+            (&retained.hourly_buckets, &streamed.hourly_buckets),
+            (&retained.twelve_hour_buckets, &streamed.twelve_hour_buckets),
+            (&retained.daily_buckets, &streamed.daily_buckets),
+            (&retained.weekly_buckets, &streamed.weekly_buckets),
+            (&retained.monthly_buckets, &streamed.monthly_buckets),
+            (&retained.day_buckets, &streamed.day_buckets),
+        ] {
+            assert_eq!(a.len(), b.len());
+            for (a, b) in a.iter().zip(b) {
+                assert_eq!((a.start, a.end), (b.start, b.end));
+                assert_totals_match(&a.totals, &b.totals);
+                let a_models = a.by_model.as_ref().expect("breakdown");
+                let b_models = b.by_model.as_ref().expect("breakdown");
+                assert_eq!(a_models.keys().collect::<Vec<_>>(), b_models.keys().collect::<Vec<_>>());
+                for (model, totals) in a_models {
+                    assert_totals_match(totals, &b_models[model]);
+                }
+            }
+        }
+        assert_totals_match(&retained.trailing.last_hour, &streamed.trailing.last_hour);
+        assert_totals_match(&retained.trailing.last_day, &streamed.trailing.last_day);
+        assert_totals_match(&retained.trailing.last_seven_days, &streamed.trailing.last_seven_days);
+        assert_totals_match(&retained.trailing.last_year, &streamed.trailing.last_year);
+        assert!(streamed.trailing.last_day.total_tokens > 0);
+        assert_eq!(retained.custom_windows.len(), 2);
+        assert_eq!(streamed.custom_windows.len(), 2);
+        for (a, b) in retained.custom_windows.iter().zip(&streamed.custom_windows) {
+            assert_eq!(a.duration, b.duration);
+            assert_totals_match(&a.totals, &b.totals);
+        }
+        assert!(streamed.custom_windows[0].totals.total_tokens > 0);
+    }
+
+    #[test]
+    fn default_source_label_reports_legacy_home_backing_default_auth() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let legacy_home = temp.path().join(".codex");
+        for home in [&code_home, &legacy_home] {
+            let sessions = home.join(SESSIONS_SUBDIR);
+            fs::create_dir_all(&sessions).expect("sessions");
+            write_session(
+                &sessions,
+                "sess",
+                &[
+                    session_meta("sess", "gpt-5"),
+                    token_event("2025-11-19T00:00:00Z", 10, 0, 10, 0, 20),
+                ],
+            );
+        }
+        fs::write(legacy_home.join(AUTH_FILE_NAME), "{}").expect("legacy auth");
+        fs::write(code_home.join(AUTH_FILE_NAME), "{}").expect("code auth");
+
+        let mut options = GlobalUsageScanOptions::new(code_home.clone());
+        options.legacy_code_home = Some(legacy_home.clone());
+        let snapshot = scan_global_usage(options.clone()).expect("scan");
+        let mut labels: Vec<&str> = snapshot
+            .source_usage
+            .iter()
+            .map(|source| source.label.as_str())
+            .collect();
+        labels.sort();
+        assert_eq!(labels, vec![".code", ".codex"]);
+        assert_eq!(snapshot.default_source_label.as_deref(), Some(".codex"));
+
+        fs::remove_file(legacy_home.join(AUTH_FILE_NAME)).expect("remove legacy auth");
+        let snapshot = scan_global_usage(options).expect("rescan");
+        assert_eq!(snapshot.default_source_label.as_deref(), Some(".code"));
+    }
+
+    #[test]
+    fn source_cost_alert_flags_only_sources_over_their_cap() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let root_sessions = code_home.join(SESSIONS_SUBDIR);
+        let slot_sessions = code_home.join(SLOT_DIR_NAME).join("work").join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&root_sessions).expect("root sessions");
+        fs::create_dir_all(&slot_sessions).expect("slot sessions");
+        // 1M gpt-5.1-codex output tokens cost $10; 10k cost $0.10.
+        write_session(
+            &slot_sessions,
+            "runaway",
+            &[
+                session_meta("runaway", "gpt-5.1-codex"),
+                token_event("2025-11-19T00:00:00Z", 0, 0, 1_000_000, 0, 1_000_000),
+            ],
+        );
+        write_session(
+            &root_sessions,
+            "steady",
+            &[
+                session_meta("steady", "gpt-5.1-codex"),
+                token_event("2025-11-19T00:00:00Z", 0, 0, 10_000, 0, 10_000),
+            ],
+        );
+
+        let mut options = GlobalUsageScanOptions::new(code_home);
+        options.legacy_code_home = None;
+        let caps = HashMap::from([
+            (".code".to_string(), 1.0),
+            (".code/slot/work".to_string(), 5.0),
+        ]);
+        let snapshot = scan_global_usage(options.with_source_cost_alert(caps)).expect("scan");
+
+        assert_eq!(
+            snapshot.source_cost_alerts,
+            vec![SourceCostAlert {
+                label: ".code/slot/work".to_string(),
+                cost_usd: 10.0,
+                cap_usd: 5.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn usage_by_account_groups_slot_sessions_by_email() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let mut emails = HashMap::new();
+        for (name, email, input, output) in [
+            ("work", "work@example.com", 1_000, 100),
+            ("personal", "me@example.com", 400, 40),
+        ] {
+            let slot_dir = code_home.join(SLOT_DIR_NAME).join(name);
+            let sessions = slot_dir.join(SESSIONS_SUBDIR);
+            fs::create_dir_all(&sessions).expect("slot sessions");
+            write_session(
+                &sessions,
+                name,
+                &[
+                    session_meta(name, "gpt-5.1-codex"),
+                    token_event("2025-11-19T00:00:00Z", input, 0, output, 0, input + output),
+                ],
+            );
+            emails.insert(slot_dir, email.to_string());
+        }
+        // The default slot has sessions but no email, so it cannot be attributed.
+        let root_sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&root_sessions).expect("root sessions");
+        write_session(
+            &root_sessions,
+            "root",
+            &[
+                session_meta("root", "gpt-5.1-codex"),
+                token_event("2025-11-19T00:00:00Z", 10, 0, 1, 0, 11),
+            ],
+        );
+
+        let mut options = GlobalUsageScanOptions::new(code_home);
+        options.legacy_code_home = None;
+        let snapshot = scan_global_usage(options).expect("scan");
+        let accounts = usage_by_account(&snapshot.source_usage, &emails);
+
+        let totals_for = |account: &str| {
+            accounts
+                .iter()
+                .find(|entry| entry.account == account)
+                .unwrap_or_else(|| panic!("missing {account}: {accounts:?}"))
+                .totals
+                .clone()
+        };
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(totals_for("work@example.com").total_tokens, 1_100);
+        assert_eq!(totals_for("work@example.com").output_tokens, 100);
+        assert_eq!(totals_for("me@example.com").total_tokens, 440);
+        assert_eq!(totals_for(UNATTRIBUTED_ACCOUNT).total_tokens, 11);
+        let summed: f64 = accounts.iter().map(|entry| entry.totals.cost_usd).sum();
+        assert!((summed - snapshot.totals.cost_usd).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_usage_sessions_are_not_counted_as_missing_totals() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&sessions).expect("session dir");
+
+        fs::write(
+            sessions.join("zero.jsonl"),
+            r#"{"type":"session_meta","payload":{"id":"zero","model":"gpt-5.1-codex"}}
+{"type":"event_msg","timestamp":"2025-11-19T00:00:00Z","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":0,"cached_input_tokens":0,"output_tokens":0,"reasoning_output_tokens":0,"total_tokens":0}}}}
+"#,
+        )
+        .expect("write zero log");
+        fs::write(
+            sessions.join("empty.jsonl"),
+            r#"{"type":"session_meta","payload":{"id":"empty","model":"gpt-5.1-codex"}}
+"#,
+        )
+        .expect("write empty log");
+
+        let options = GlobalUsageScanOptions::new(code_home)
+            .with_sessions_override(sessions);
+        let snapshot = scan_global_usage(options).expect("scan");
+
+        assert_eq!(snapshot.sessions_processed, 0);
+        assert_eq!(snapshot.sessions_zero_usage, 1);
+        assert_eq!(snapshot.sessions_missing_totals, 1);
+    }
+
+    #[test]
+    fn aggregates_simple_session() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&sessions).expect("session dir");
+
+        let log_path = sessions.join("sess-1.jsonl");
+        fs::write(
+            &log_path,
+            r#"{"type":"session_meta","payload":{"id":"sess-1","model":"gpt-5.1-codex"}}
+{"type":"event_msg","timestamp":"2025-11-19T00:00:00Z","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":10,"cached_input_tokens":2,"output_tokens":5,"reasoning_output_tokens":1,"total_tokens":16}}}}
+{"type":"event_msg","timestamp":"2025-11-19T00:10:00Z","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":30,"cached_input_tokens":6,"output_tokens":25,"reasoning_output_tokens":4,"total_tokens":65}}}}
+"#,
+        )
+        .expect("write log");
+
+        let options = GlobalUsageScanOptions::new(code_home)
+            .with_sessions_override(sessions.clone());
+        let snapshot = scan_global_usage(options).expect("scan");
+        assert_eq!(snapshot.sessions_processed, 1);
+        assert_eq!(snapshot.sessions_missing_totals, 0);
+        assert_eq!(snapshot.totals.non_cached_input_tokens, 24); // (10-2)+(20-4)
+        assert_eq!(snapshot.totals.output_tokens, 25);
+        assert_eq!(snapshot.totals.reasoning_output_tokens, 4);
+        assert_eq!(snapshot.model_usage.len(), 1);
+        assert_eq!(snapshot.source_usage.len(), 1);
+    }
+
+    #[test]
+    fn monotonic_deltas_never_double_count() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&sessions).expect("session dir");
+
+        write_session(
+            &sessions,
+            "sess-rolling",
+            &[
+                session_meta("sess-rolling", "gpt-5.1-codex"),
+                token_event("2025-11-19T00:00:00Z", 100, 30, 50, 10, 190),
+                token_event("2025-11-19T00:05:00Z", 110, 35, 60, 15, 230),
+                token_event("2025-11-19T00:10:00Z", 105, 40, 100, 25, 270),
+            ],
+        );
+
+        let options = GlobalUsageScanOptions::new(code_home)
+            .with_sessions_override(sessions.clone());
+        let snapshot = scan_global_usage(options).expect("scan");
+
+        assert_eq!(snapshot.sessions_processed, 1);
+        assert_eq!(snapshot.totals.non_cached_input_tokens, 75);
+        assert_eq!(snapshot.totals.cached_input_tokens, 40);
+        assert_eq!(snapshot.totals.output_tokens, 100);
+        assert_eq!(snapshot.totals.reasoning_output_tokens, 25);
+        assert_eq!(snapshot.totals.total_tokens, 270);
+    }
+
+    #[test]
+    fn malformed_lines_are_counted() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&sessions).expect("session dir");
+
+        fs::write(
+            sessions.join("sess-corrupt.jsonl"),
+            r#"{"type":"session_meta","payload":{"id":"sess-corrupt","model":"gpt-5"}}
+{"type":"event_msg","timestamp":"2025-11-19T00:00:00Z","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":10,"total_tokens":10}}}}
+{not json
+{"type":"event_msg",
+"#,
+        )
+        .expect("write log");
+
+        let options = GlobalUsageScanOptions::new(code_home)
+            .with_sessions_override(sessions.clone());
+        let snapshot = scan_global_usage(options).expect("scan");
+        assert_eq!(snapshot.sessions_processed, 1);
+        assert_eq!(snapshot.malformed_lines, 2);
+    }
+
+    #[test]
+    fn present_buckets_lists_distinct_models_in_order() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&sessions).expect("session dir");
+
+        for (id, model) in [
+            ("sess-mini-a", "gpt-5.1-codex-mini"),
+            ("sess-codex", "gpt-5.1-codex"),
+            ("sess-mini-b", "gpt-5.1-codex-mini"),
+        ] {
+            write_session(
+                &sessions,
+                id,
+                &[
+                    session_meta(id, model),
+                    token_event("2025-11-19T00:00:00Z", 100, 0, 10, 0, 110),
+                ],
+            );
+        }
+
+        let options = GlobalUsageScanOptions::new(code_home)
+            .with_sessions_override(sessions.clone());
+        let snapshot = scan_global_usage(options).expect("scan");
+        assert_eq!(
+            snapshot.present_buckets,
+            vec![ModelBucket::Gpt51Codex, ModelBucket::Gpt51CodexMini]
+        );
+    }
+
+    #[test]
+    fn progress_reports_every_parsed_file() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&sessions).expect("session dir");
+        for idx in 0..5 {
+            let id = format!("sess-{idx}");
+            write_session(
+                &sessions,
+                &id,
+                &[
+                    session_meta(&id, "gpt-5.1-codex"),
+                    token_event("2025-11-19T00:00:00Z", 10, 0, 5, 0, 15),
+                ],
+            );
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let options = GlobalUsageScanOptions::new(code_home)
+            .with_sessions_override(sessions.clone())
+            .with_max_workers(2)
+            .with_progress(tx);
+        scan_global_usage(options).expect("scan");
+
+        let events: Vec<ScanProgress> = rx.iter().collect();
+        assert_eq!(events.len(), 6);
+        assert!(events.iter().all(|event| event.files_total == 5));
+        assert_eq!(events.iter().map(|event| event.files_done).max(), Some(5));
+    }
+
+    #[test]
+    fn excluded_sources_do_not_count_toward_totals() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let main_sessions = code_home.join(SESSIONS_SUBDIR);
+        let slot_sessions = code_home.join(SLOT_DIR_NAME).join("work").join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&main_sessions).expect("main dir");
+        fs::create_dir_all(&slot_sessions).expect("slot dir");
+        write_session(
+            &main_sessions,
+            "main",
+            &[
+                session_meta("main", "gpt-5.1-codex"),
+                token_event("2025-11-19T00:00:00Z", 100, 0, 10, 0, 110),
+            ],
+        );
+        write_session(
+            &slot_sessions,
+            "slot",
+            &[
+                session_meta("slot", "gpt-5.1-codex"),
+                token_event("2025-11-19T00:00:00Z", 5_000, 0, 500, 0, 5_500),
+            ],
+        );
+
+        let mut options = GlobalUsageScanOptions::new(code_home.clone());
+        options.legacy_code_home = None;
+        let snapshot = scan_global_usage(options.clone()).expect("scan");
+        assert_eq!(snapshot.totals.total_tokens, 5_610);
+
+        let by_label = scan_global_usage(
+            options
+                .clone()
+                .with_excluded_sources(vec![".code/slot/work".to_string()]),
+        )
+        .expect("scan");
+        assert_eq!(by_label.sessions_processed, 1);
+        assert_eq!(by_label.totals.total_tokens, 110);
+        assert!(by_label
+            .source_usage
+            .iter()
+            .all(|source| source.label != ".code/slot/work"));
+
+        let slot_root = code_home.join(SLOT_DIR_NAME).display().to_string();
+        let by_path = scan_global_usage(options.with_excluded_sources(vec![slot_root]))
+            .expect("scan");
+        assert_eq!(by_path.totals.total_tokens, 110);
+    }
+
+    #[test]
+    fn session_cost_split_sums_to_total_cost() {
+        let temp = TempDir::new().expect("tempdir");
+        write_session(
+            temp.path(),
+            "reasoning",
+            &[
+                session_meta("reasoning", "gpt-5.1-codex-mini"),
+                token_event("2025-11-19T00:00:00Z", 4_000, 1_000, 2_000, 500, 6_500),
+                token_event("2025-11-19T00:05:00Z", 9_000, 3_000, 5_000, 1_500, 15_500),
+            ],
+        );
+
+        let options = GlobalUsageScanOptions::new(temp.path().to_path_buf())
+            .with_sessions_override(temp.path().to_path_buf())
+            .with_record_sessions(true);
+        let snapshot = scan_global_usage(options).expect("scan");
+        let session = &snapshot.per_session[0];
+        assert_eq!(session.totals.reasoning_output_tokens, 1_500);
+
+        let split = session.cost_split;
+        assert!(split.output_cost > 0.0);
+        assert!(split.reasoning_cost > 0.0);
+        let sum = split.input_cost + split.output_cost + split.reasoning_cost;
+        assert!((sum - session.totals.cost_usd).abs() < 1e-9, "{sum} vs {}", session.totals.cost_usd);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loops_terminate_without_duplicates() {
+        let temp = TempDir::new().expect("tempdir");
+        let sessions = temp.path().join("sessions");
+        fs::create_dir_all(&sessions).expect("session dir");
+        write_session(
+            &sessions,
+            "only",
+            &[
+                session_meta("only", "gpt-5.1-codex"),
+                token_event("2025-11-19T00:00:00Z", 10, 0, 5, 0, 15),
+            ],
+        );
+        std::os::unix::fs::symlink(&sessions, sessions.join("loop")).expect("dir symlink");
+        std::os::unix::fs::symlink(sessions.join("only.jsonl"), sessions.join("alias.jsonl"))
+            .expect("file symlink");
+
+        let options = GlobalUsageScanOptions::new(temp.path().to_path_buf())
+            .with_sessions_override(sessions);
+        let snapshot = scan_global_usage(options).expect("scan");
+        assert_eq!(snapshot.sessions_processed, 1);
+        assert_eq!(snapshot.totals.total_tokens, 15);
+    }
+
+    #[test]
+    fn relative_bucket_labels_count_back_from_now() {
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let bucket = |start_offset: Duration, size: Duration| UsageBucket {
+            start: now - start_offset,
+            end: now - start_offset + size,
+            totals: UsageTotals::default(),
+            by_model: None,
+        };
+
+        assert_eq!(
+            bucket(Duration::hours(2), Duration::hours(1)).relative_label(now),
+            "2h ago–1h ago"
+        );
+        assert_eq!(
+            bucket(Duration::hours(1), Duration::hours(1)).relative_label(now),
+            "1h ago–now"
+        );
+        assert_eq!(
+            bucket(Duration::minutes(45), Duration::minutes(15)).relative_label(now),
+            "45m ago–30m ago"
+        );
+        assert_eq!(
+            bucket(Duration::days(7), Duration::days(1)).relative_label(now),
+            "7d ago–6d ago"
+        );
+    }
+
+    #[test]
+    fn scan_duration_is_recorded() {
+        let temp = TempDir::new().expect("tempdir");
+        let code_home = temp.path().join(".code");
+        let sessions = code_home.join(SESSIONS_SUBDIR);
+        fs::create_dir_all(&sessions).expect("session dir");
+
+        let empty = scan_global_usage(
+            GlobalUsageScanOptions::new(code_home.clone()).with_sessions_override(sessions.clone()),
+        )
+        .expect("scan empty");
+        assert_eq!(empty.sessions_processed, 0);
+
+        for session in 0..40 {
+            let id = format!("sess-{session}");
+            let mut lines = vec![session_meta(&id, "gpt-5.1-codex")];
+            for step in 1..=500u64 {
+                lines.push(token_event(
+                    "2025-11-19T00:00:00Z",
+                    step * 10,
+                    step * 2,
+                    step * 5,
+                    step,
+                    step * 15,
+                ));
+            }
+            write_session(&sessions, &id, &lines);
+        }
+
+        let snapshot = scan_global_usage(
+            GlobalUsageScanOptions::new(code_home).with_sessions_override(sessions.clone()),
+        )
+        .expect("scan");
+        assert_eq!(snapshot.sessions_processed, 40);
+        assert!(snapshot.scan_duration_ms > 0);
     }
 
     #[test]
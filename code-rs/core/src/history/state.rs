@@ -1,6 +1,7 @@
 use crate::plan_tool::StepStatus;
 use crate::parse_command::ParsedCommand;
 use crate::protocol::{FileChange, RateLimitSnapshotEvent, TokenUsage};
+use code_protocol::models::ContentItem;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
@@ -20,12 +21,18 @@ pub struct SnapshotRecordPayload {
     pub kind: SnapshotRecordKind,
     pub stream_id: Option<String>,
     pub markdown: Option<String>,
+    /// Non-text content (e.g. images) attached to the record.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content: Vec<ContentItem>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SnapshotCoalesceOutcome {
     pub records: Vec<SnapshotRecordPayload>,
     pub removed_count: usize,
+    /// Records folded into a neighbour by the aggressive pass.
+    #[serde(default)]
+    pub merged_count: usize,
 }
 
 pub fn coalesce_snapshot_records(records: Vec<SnapshotRecordPayload>) -> SnapshotCoalesceOutcome {
@@ -52,9 +59,67 @@ pub fn coalesce_snapshot_records(records: Vec<SnapshotRecordPayload>) -> Snapsho
     SnapshotCoalesceOutcome {
         records: retained,
         removed_count: removed,
+        merged_count: 0,
+    }
+}
+
+/// Like [`coalesce_snapshot_records`], then merges adjacent user records and
+/// adjacent assistant records from the same stream, and collapses runs of
+/// whitespace-only records into one.
+pub fn coalesce_snapshot_records_aggressive(
+    records: Vec<SnapshotRecordPayload>,
+) -> SnapshotCoalesceOutcome {
+    let outcome = coalesce_snapshot_records(records);
+    let mut merged: Vec<SnapshotRecordPayload> = Vec::with_capacity(outcome.records.len());
+    let mut merged_count = 0;
+
+    for record in outcome.records {
+        let Some(previous) = merged.last_mut() else {
+            merged.push(record);
+            continue;
+        };
+
+        let record_blank = is_blank_snapshot_record(&record);
+        if record_blank && is_blank_snapshot_record(previous) {
+            merged_count += 1;
+            continue;
+        }
+
+        let mergeable = match record.kind {
+            SnapshotRecordKind::User => previous.kind == SnapshotRecordKind::User,
+            SnapshotRecordKind::Assistant => {
+                previous.kind == SnapshotRecordKind::Assistant
+                    && previous.stream_id == record.stream_id
+            }
+            _ => false,
+        };
+        if mergeable && !record_blank && !is_blank_snapshot_record(previous) {
+            let previous_markdown = previous.markdown.get_or_insert_with(String::new);
+            previous_markdown.push_str("\n\n");
+            previous_markdown.push_str(record.markdown.as_deref().unwrap_or_default());
+            previous.content.extend(record.content);
+            merged_count += 1;
+            continue;
+        }
+
+        merged.push(record);
+    }
+
+    SnapshotCoalesceOutcome {
+        records: merged,
+        removed_count: outcome.removed_count,
+        merged_count,
     }
 }
 
+fn is_blank_snapshot_record(record: &SnapshotRecordPayload) -> bool {
+    record.content.is_empty()
+        && record
+            .markdown
+            .as_deref()
+            .is_none_or(|markdown| markdown.trim().is_empty())
+}
+
 pub fn snapshot_from_records(records: Vec<SnapshotRecordPayload>) -> HistorySnapshot {
     let history_records = records
         .into_iter()
@@ -141,6 +206,9 @@ pub struct SnapshotSummary {
     pub record_count: usize,
     pub assistant_messages: usize,
     pub user_messages: usize,
+    /// Image content items across all records, for multimodal cost estimates.
+    #[serde(default)]
+    pub image_items: usize,
 }
 
 pub fn summarize_snapshot(records: Vec<SnapshotRecordPayload>) -> SnapshotSummary {
@@ -152,10 +220,16 @@ pub fn summarize_snapshot(records: Vec<SnapshotRecordPayload>) -> SnapshotSummar
         .iter()
         .filter(|record| record.kind == SnapshotRecordKind::User)
         .count();
+    let image_items = records
+        .iter()
+        .flat_map(|record| &record.content)
+        .filter(|item| matches!(item, ContentItem::InputImage { .. }))
+        .count();
     SnapshotSummary {
         record_count: records.len(),
         assistant_messages: assistant,
         user_messages: user,
+        image_items,
     }
 }
 
@@ -3,6 +3,7 @@ use code_kotlin_host as host;
 use code_protocol::models::{ContentItem, ResponseItem};
 use serde::{de::Error as DeError, Deserialize, Deserializer};
 use serde_json::{json, to_value, Value};
+use std::collections::VecDeque;
 
 #[derive(Debug)]
 pub struct KotlinCoreHost {
@@ -46,6 +47,59 @@ struct EngineEventRaw {
     pub payload: serde_json::Value,
 }
 
+/// Poll bookkeeping a caller keeps across `poll_events` calls so each event is
+/// surfaced once. Events below the cursor are dropped; with
+/// [`PollState::with_recent_ids`] the last few sequence numbers are also
+/// remembered, which keeps replays out even after [`PollState::reset_cursor`].
+#[derive(Debug, Default)]
+pub struct PollState {
+    cursor: u64,
+    recent_ids: VecDeque<u64>,
+    recent_capacity: usize,
+}
+
+impl PollState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember up to `capacity` recent event ids for duplicate suppression.
+    pub fn with_recent_ids(mut self, capacity: usize) -> Self {
+        self.recent_capacity = capacity;
+        self
+    }
+
+    /// Next sequence number this state expects, suitable as the poll cursor.
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    /// Move the cursor, e.g. to a host-reported `next_cursor` or back to 0
+    /// after the host session restarts.
+    pub fn reset_cursor(&mut self, cursor: u64) {
+        self.cursor = cursor;
+    }
+
+    /// Returns only the events not seen before, in their original order.
+    pub fn advance(&mut self, raw_events: Vec<EngineEvent>) -> Vec<EngineEvent> {
+        let mut fresh = Vec::new();
+        for event in raw_events {
+            if event.seq < self.cursor || self.recent_ids.contains(&event.seq) {
+                continue;
+            }
+            self.cursor = event.seq.saturating_add(1);
+            if self.recent_capacity > 0 {
+                if self.recent_ids.len() == self.recent_capacity {
+                    self.recent_ids.pop_front();
+                }
+                self.recent_ids.push_back(event.seq);
+            }
+            fresh.push(event);
+        }
+        fresh
+    }
+}
+
 fn deserialize_session_id<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
 where
     D: Deserializer<'de>,
@@ -126,6 +180,32 @@ mod tests {
         assert_eq!(payload.get("history").and_then(|v| v.as_array()).map(|a| a.len()), Some(1));
         assert_eq!(payload.get("turn_input").and_then(|v| v.as_array()).map(|a| a.len()), Some(1));
     }
+
+    fn engine_events(seqs: &[u64]) -> Vec<EngineEvent> {
+        seqs.iter()
+            .map(|seq| EngineEvent {
+                seq: *seq,
+                kind: "agent_message".to_string(),
+                payload: json!({ "message": format!("event {seq}") }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn poll_state_suppresses_repeated_batches() {
+        let mut state = PollState::new().with_recent_ids(8);
+        let first = state.advance(engine_events(&[0, 1, 2]));
+        assert_eq!(first.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(state.cursor(), 3);
+
+        assert!(state.advance(engine_events(&[0, 1, 2])).is_empty());
+
+        // After a cursor reset the ring buffer still recognises the replay,
+        // while a genuinely new event comes through.
+        state.reset_cursor(0);
+        let replay = state.advance(engine_events(&[1, 2, 3]));
+        assert_eq!(replay.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![3]);
+    }
 }
 
 pub fn chat_turn_payload(history: &[ResponseItem], turn_input: &[ResponseItem]) -> Result<Value> {
@@ -84,8 +84,10 @@ pub use code_protocol::protocol::InitialHistory;
 pub use conversation_manager::ConversationForkOutcome;
 pub use conversation_manager::ConversationManager;
 pub use conversation_manager::ConversationPruneOutcome;
+pub use conversation_manager::ConversationForkPreview;
 pub use conversation_manager::NewConversation;
 pub use conversation_manager::fork_history_from_response_items;
+pub use conversation_manager::preview_fork_from_response_items;
 pub use conversation_manager::prune_history_after_dropping_last_user_turns;
 // Re-export common auth types for workspace consumers
 pub use auth::AuthManager;
@@ -143,6 +145,7 @@ pub use history::state::SnapshotCoalesceOutcome;
 pub use history::state::SnapshotRecordKind;
 pub use history::state::SnapshotRecordPayload;
 pub use history::state::coalesce_snapshot_records;
+pub use history::state::coalesce_snapshot_records_aggressive;
 pub use history::state::snapshot_from_records;
 pub use history::state::summarize_snapshot;
 
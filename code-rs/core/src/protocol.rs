@@ -222,7 +222,12 @@ pub enum Op {
     GetHistoryEntryRequest { offset: usize, log_id: u64 },
 
     /// Request the current set of running tasks for task manager overlays.
-    ListRunningTasks,
+    ListRunningTasks {
+        /// Only include tasks that can currently be cancelled, keeping the
+        /// snapshot small in busy sessions.
+        #[serde(default)]
+        cancellable_only: bool,
+    },
 
     /// Request termination of a running task (foreground exec, background exec, or agent).
     TerminateTask {
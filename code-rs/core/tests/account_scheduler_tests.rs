@@ -1,12 +1,14 @@
 use base64::Engine;
 use chrono::{DateTime, Duration, Utc};
-use code_core::account_scheduler::{compute_weight, slot_identity as scheduler_slot_identity, AccountScheduler, SchedulerOutcome};
-use code_core::account_usage::{self, record_rate_limit_snapshot};
+use code_app_server_protocol::AuthMode;
+use code_core::account_scheduler::{account_status_overview_at, compute_weight, projected_runout, slot_identity as scheduler_slot_identity, AccountScheduler, SchedulerOutcome, SelectionError};
+use code_core::account_usage::{self, record_rate_limit_snapshot, record_token_usage};
 use code_core::auth_accounts::{self, upsert_api_key_account, upsert_chatgpt_account, StoredAccount};
-use code_core::protocol::RateLimitSnapshotEvent;
+use code_core::protocol::{RateLimitSnapshotEvent, TokenUsage};
 use code_core::token_data::{parse_id_token, TokenData};
 use std::collections::HashMap;
 use tempfile::tempdir;
+use tracing_test::traced_test;
 
 struct CodeHomeGuard {
     saved: Vec<(&'static str, Option<String>)>,
@@ -70,7 +72,11 @@ fn record_snapshot_with_reset(
 }
 
 fn make_chatgpt_tokens(account_id: &str) -> TokenData {
-    let jwt = fake_jwt(account_id);
+    make_chatgpt_tokens_with_plan(account_id, "pro")
+}
+
+fn make_chatgpt_tokens_with_plan(account_id: &str, plan: &str) -> TokenData {
+    let jwt = fake_jwt(account_id, plan);
     TokenData {
         id_token: parse_id_token(&jwt).expect("id token"),
         access_token: "access".into(),
@@ -97,6 +103,15 @@ fn collect_identity_weights(
     let mut weights = HashMap::new();
     for account in accounts {
         let Some(snapshot) = snapshot_map.get(&account.id) else { continue; };
+        // The scheduler skips fully used accounts until their window resets.
+        let exhausted = snapshot
+            .snapshot
+            .as_ref()
+            .is_some_and(|event| event.secondary_used_percent >= 100.0)
+            && snapshot.secondary_next_reset_at.is_some_and(|reset| reset > now);
+        if exhausted {
+            continue;
+        }
         let Some(weight) = snapshot.snapshot.as_ref().map(|_| compute_weight(snapshot, now)) else {
             continue;
         };
@@ -143,7 +158,7 @@ fn reference_weighted_order(weights: &[(String, f64)], iterations: usize) -> Vec
     order
 }
 
-fn fake_jwt(account_id: &str) -> String {
+fn fake_jwt(account_id: &str, plan: &str) -> String {
     use serde::Serialize;
 
     #[derive(Serialize)]
@@ -159,7 +174,7 @@ fn fake_jwt(account_id: &str) -> String {
     let payload = serde_json::json!({
         "email": format!("{account_id}@example.com"),
         "https://api.openai.com/auth": {
-            "chatgpt_plan_type": "pro"
+            "chatgpt_plan_type": plan
         }
     });
 
@@ -281,6 +296,32 @@ fn cooldown_expires_and_account_returns() {
     assert_eq!(after.account_id, first.account_id);
 }
 
+#[test]
+fn exhausted_account_is_skipped_until_reset_without_cooldown() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let acc_a = upsert_api_key_account(home.path(), "sk-a".into(), None, false).unwrap();
+    let acc_b = upsert_api_key_account(home.path(), "sk-b".into(), None, false).unwrap();
+
+    record_snapshot_with_reset(home.path(), &acc_a.id, 100.0, Some(600));
+    record_snapshot(home.path(), &acc_b.id, 50.0);
+
+    let mut scheduler = AccountScheduler::new(home.path().to_path_buf());
+    let now = Utc::now();
+
+    for _ in 0..4 {
+        let pick = scheduler.next_account(None, now).unwrap();
+        assert_eq!(pick.account_id, acc_b.id, "exhausted account must not be chosen");
+    }
+    let preview = scheduler.preview_candidates(now);
+    assert!(preview.iter().any(|(id, _, eligible)| *id == acc_a.id && !eligible));
+
+    // Once the window resets the stale 100% snapshot no longer excludes it.
+    let after_reset = now + Duration::seconds(660);
+    let preview = scheduler.preview_candidates(after_reset);
+    assert!(preview.iter().any(|(id, _, eligible)| *id == acc_a.id && *eligible));
+}
+
 #[test]
 fn scheduler_handles_duplicate_slots_and_cooldowns() {
     let home = tempdir().unwrap();
@@ -447,3 +488,291 @@ fn rate_limit_releases_context_binding() {
 
     assert_ne!(first.account_id, retry.account_id, "context should move to a different account after TPM limit");
 }
+
+#[test]
+fn daily_cap_skips_account_over_budget() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let acc_a = upsert_api_key_account(home.path(), "sk-a".into(), None, false).unwrap();
+    let acc_b = upsert_api_key_account(home.path(), "sk-b".into(), None, false).unwrap();
+
+    record_snapshot(home.path(), &acc_a.id, 10.0);
+    record_snapshot(home.path(), &acc_b.id, 90.0);
+
+    let now = Utc::now();
+    // 2M non-cached input tokens at premium rates is $2.50 of spend today.
+    let usage = TokenUsage {
+        input_tokens: 2_000_000,
+        cached_input_tokens: 0,
+        output_tokens: 0,
+        reasoning_output_tokens: 0,
+        total_tokens: 2_000_000,
+    };
+    record_token_usage(home.path(), &acc_a.id, None, &usage, now).unwrap();
+
+    let caps = HashMap::from([(acc_a.id.clone(), 1.0), (acc_b.id.clone(), 100.0)]);
+    let mut scheduler = AccountScheduler::new(home.path().to_path_buf()).with_daily_cap(caps);
+    for _ in 0..4 {
+        let selection = scheduler.next_account(None, now).unwrap();
+        assert_eq!(selection.account_id, acc_b.id);
+    }
+
+    let caps = HashMap::from([(acc_a.id.clone(), 1.0), (acc_b.id.clone(), 0.0)]);
+    record_token_usage(home.path(), &acc_b.id, None, &usage, now).unwrap();
+    let mut scheduler = AccountScheduler::new(home.path().to_path_buf()).with_daily_cap(caps);
+    match scheduler.select_account(None, now) {
+        Err(SelectionError::AllCapped { mut account_ids }) => {
+            account_ids.sort();
+            let mut expected = vec![acc_a.id.clone(), acc_b.id.clone()];
+            expected.sort();
+            assert_eq!(account_ids, expected);
+        }
+        other => panic!("expected all accounts capped, got {other:?}"),
+    }
+}
+
+#[test]
+#[traced_test]
+fn selection_emits_debug_decision_fields() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let acc_a = upsert_api_key_account(home.path(), "sk-a".into(), None, false).unwrap();
+    let acc_b = upsert_api_key_account(home.path(), "sk-b".into(), None, false).unwrap();
+
+    record_snapshot(home.path(), &acc_a.id, 50.0);
+    record_snapshot(home.path(), &acc_b.id, 50.0);
+
+    let mut scheduler = AccountScheduler::new(home.path().to_path_buf());
+    let now = Utc::now();
+    let first = scheduler.next_account(None, now).unwrap();
+
+    assert!(logs_contain("selected account"));
+    assert!(logs_contain(&format!("account_id={}", first.account_id)));
+    assert!(logs_contain(&format!("identity={}", first.account_id)));
+    assert!(logs_contain("total_weight="));
+    assert!(logs_contain("eligible_slots=2"));
+
+    scheduler.record_outcome(
+        &first.account_id,
+        SchedulerOutcome::RateLimited {
+            resume_at: Some(now + Duration::seconds(60)),
+        },
+    );
+    scheduler.next_account(None, now).unwrap();
+    assert!(logs_contain("reason=\"cooldown\""));
+}
+
+#[test]
+fn blank_api_key_accounts_are_never_selected() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let blank = upsert_api_key_account(home.path(), "   ".into(), None, false).unwrap();
+    let valid = upsert_api_key_account(home.path(), "sk-valid".into(), None, false).unwrap();
+
+    record_snapshot(home.path(), &blank.id, 10.0);
+    record_snapshot(home.path(), &valid.id, 50.0);
+
+    let mut scheduler = AccountScheduler::new(home.path().to_path_buf());
+    let now = Utc::now();
+    for _ in 0..5 {
+        let selection = scheduler.next_account(None, now).unwrap();
+        assert_eq!(selection.account_id, valid.id);
+    }
+}
+
+#[test]
+fn saved_state_continues_rotation_across_instances() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let heavy = upsert_api_key_account(home.path(), "sk-heavy".into(), None, false).unwrap();
+    let light = upsert_api_key_account(home.path(), "sk-light".into(), None, false).unwrap();
+
+    record_snapshot(home.path(), &heavy.id, 10.0);
+    record_snapshot(home.path(), &light.id, 50.0);
+
+    let now = Utc::now();
+    let mut continuous = AccountScheduler::new(home.path().to_path_buf());
+    let expected: Vec<String> = (0..8)
+        .map(|_| continuous.next_account(None, now).unwrap().account_id)
+        .collect();
+
+    let state_path = home.path().join("scheduler").join("state.json");
+    let mut picks = Vec::new();
+    let mut first = AccountScheduler::new(home.path().to_path_buf());
+    for _ in 0..3 {
+        picks.push(first.next_account(None, now).unwrap().account_id);
+    }
+    first.record_outcome(
+        &light.id,
+        SchedulerOutcome::RateLimited {
+            resume_at: Some(Utc::now() - Duration::seconds(5)),
+        },
+    );
+    first.save_state(&state_path).unwrap();
+
+    let mut second = AccountScheduler::new(home.path().to_path_buf());
+    second.load_state(&state_path).unwrap();
+    for _ in 0..5 {
+        picks.push(second.next_account(None, now).unwrap().account_id);
+    }
+    assert_eq!(picks, expected);
+
+    let resaved = home.path().join("resaved.json");
+    let mut reloaded = AccountScheduler::new(home.path().to_path_buf());
+    reloaded.load_state(&state_path).unwrap();
+    reloaded.save_state(&resaved).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&resaved).unwrap()).unwrap();
+    assert!(json["cooldowns"].as_object().unwrap().is_empty(), "expired cooldowns are dropped");
+    assert_eq!(json["weights"].as_object().unwrap().len(), 2);
+}
+
+#[test]
+fn preview_candidates_is_read_only() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let acc_a = upsert_api_key_account(home.path(), "sk-a".into(), None, false).unwrap();
+    let acc_b = upsert_api_key_account(home.path(), "sk-b".into(), None, false).unwrap();
+    let acc_c = upsert_api_key_account(home.path(), "sk-c".into(), None, false).unwrap();
+    record_snapshot(home.path(), &acc_a.id, 20.0);
+    record_snapshot(home.path(), &acc_b.id, 60.0);
+    record_snapshot(home.path(), &acc_c.id, 40.0);
+
+    let now = Utc::now();
+    let mut scheduler = AccountScheduler::new(home.path().to_path_buf());
+    scheduler.record_outcome(
+        &acc_c.id,
+        SchedulerOutcome::RateLimited {
+            resume_at: Some(now + Duration::seconds(60)),
+        },
+    );
+
+    let first = scheduler.preview_candidates(now);
+    let second = scheduler.preview_candidates(now);
+    assert_eq!(first, second);
+    assert_eq!(first.len(), 3);
+    let eligibility: HashMap<String, bool> = first
+        .iter()
+        .map(|(id, _, eligible)| (id.clone(), *eligible))
+        .collect();
+    assert!(eligibility[&acc_a.id]);
+    assert!(eligibility[&acc_b.id]);
+    assert!(!eligibility[&acc_c.id]);
+
+    scheduler.next_account(None, now).unwrap();
+    let after = scheduler.preview_candidates(now);
+    assert_ne!(after, first);
+    assert_eq!(after, scheduler.preview_candidates(now));
+}
+
+#[test]
+fn status_overview_joins_snapshot_and_spend() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let tracked = upsert_api_key_account(home.path(), "sk-a".into(), Some("Tracked".into()), false).unwrap();
+    let idle = upsert_api_key_account(home.path(), "sk-b".into(), None, false).unwrap();
+    record_snapshot(home.path(), &tracked.id, 35.0);
+
+    let now = Utc::now();
+    // 2M non-cached input tokens at premium rates is $2.50 of spend today.
+    let usage = TokenUsage {
+        input_tokens: 2_000_000,
+        cached_input_tokens: 0,
+        output_tokens: 0,
+        reasoning_output_tokens: 0,
+        total_tokens: 2_000_000,
+    };
+    record_token_usage(home.path(), &tracked.id, None, &usage, now).unwrap();
+
+    let overview = account_status_overview_at(home.path(), now);
+    assert_eq!(overview.len(), 2);
+
+    let status = overview.iter().find(|row| row.id == tracked.id).unwrap();
+    assert_eq!(status.label.as_deref(), Some("Tracked"));
+    assert_eq!(status.mode, AuthMode::ApiKey);
+    assert_eq!(status.secondary_used_percent, Some(35.0));
+    assert!(status.resume_at.is_some_and(|at| at > now));
+    assert!((status.today_cost - 2.5).abs() < 1e-9);
+
+    let idle_status = overview.iter().find(|row| row.id == idle.id).unwrap();
+    assert_eq!(idle_status.secondary_used_percent, None);
+    assert_eq!(idle_status.resume_at, None);
+    assert_eq!(idle_status.today_cost, 0.0);
+}
+
+#[test]
+fn half_used_account_halfway_through_window_runs_out_near_reset() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let halfway = upsert_api_key_account(home.path(), "sk-a".into(), None, false).unwrap();
+    let idle = upsert_api_key_account(home.path(), "sk-b".into(), None, false).unwrap();
+    // 60-minute window resetting in 30 minutes with 50% already used.
+    record_snapshot_with_reset(home.path(), &halfway.id, 50.0, Some(1800));
+    record_snapshot_with_reset(home.path(), &idle.id, 0.0, Some(1800));
+
+    let now = Utc::now();
+    let overview = account_status_overview_at(home.path(), now);
+    let status = overview.iter().find(|row| row.id == halfway.id).unwrap();
+    let reset = status.resume_at.expect("reset time");
+    let runout = status.projected_runout.expect("projection");
+    assert!((runout - reset).num_seconds().abs() <= 5, "runout {runout} vs reset {reset}");
+
+    let idle_status = overview.iter().find(|row| row.id == idle.id).unwrap();
+    assert_eq!(idle_status.projected_runout, None);
+
+    let snapshots = account_usage::list_rate_limit_snapshots(home.path()).unwrap();
+    let snapshot = snapshots.iter().find(|entry| entry.account_id == halfway.id).unwrap();
+    // The same percent used later in the window implies a slower pace.
+    let later = reset - Duration::minutes(15);
+    let slower = projected_runout(snapshot, later).expect("projection");
+    assert!(slower > reset);
+}
+
+#[test]
+fn tiny_usage_percent_projects_no_runout_instead_of_overflowing() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let account = upsert_api_key_account(home.path(), "sk-a".into(), None, false).unwrap();
+    record_snapshot_with_reset(home.path(), &account.id, 1e-300, Some(1800));
+
+    let now = Utc::now();
+    let overview = account_status_overview_at(home.path(), now);
+    let status = overview.iter().find(|row| row.id == account.id).unwrap();
+    assert_eq!(status.projected_runout, None);
+}
+
+#[test]
+fn plan_weights_scale_selection_share() {
+    let home = tempdir().unwrap();
+    let _guard = CodeHomeGuard::new(home.path());
+    let now = Utc::now();
+    let pro = upsert_chatgpt_account(
+        home.path(),
+        make_chatgpt_tokens_with_plan("acct-pro", "pro"),
+        now,
+        Some("pro".into()),
+        false,
+    )
+    .unwrap();
+    let plus = upsert_chatgpt_account(
+        home.path(),
+        make_chatgpt_tokens_with_plan("acct-plus", "plus"),
+        now,
+        Some("plus".into()),
+        false,
+    )
+    .unwrap();
+    record_snapshot(home.path(), &pro.id, 30.0);
+    record_snapshot(home.path(), &plus.id, 30.0);
+
+    let multipliers = HashMap::from([("Pro".to_string(), 3.0), ("plus".to_string(), 1.0)]);
+    let mut scheduler = AccountScheduler::new(home.path().to_path_buf()).with_plan_weights(multipliers);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for _ in 0..8 {
+        let selection = scheduler.next_account(None, now).unwrap();
+        *counts.entry(selection.account_id).or_insert(0) += 1;
+    }
+
+    assert_eq!(counts.get(&pro.id).copied(), Some(6));
+    assert_eq!(counts.get(&plus.id).copied(), Some(2));
+}
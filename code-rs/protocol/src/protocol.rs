@@ -235,7 +235,11 @@ pub enum Op {
     GetHistoryEntryRequest { offset: usize, log_id: u64 },
 
     /// Request the list of running tasks for the task manager overlay.
-    ListRunningTasks,
+    ListRunningTasks {
+        /// Only include tasks that can currently be cancelled.
+        #[serde(default)]
+        cancellable_only: bool,
+    },
 
     /// Terminate an individual running task (foreground exec, background exec, or agent).
     TerminateTask {
@@ -1,6 +1,9 @@
 use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -9,15 +12,20 @@ use chrono::{DateTime, Utc};
 use clap::Parser;
 use code_core::config::find_code_home;
 use code_core::global_usage_tracker::{
+    format_cost,
     scan_global_usage,
+    scan_global_usage_at,
     GlobalUsageScanOptions,
     GlobalUsageSnapshot,
     ModelBucket,
+    ScanProgress,
     SourceUsage,
+    TrailingUsageTotals,
     UsageBucket,
     UsageTotals,
+    DEFAULT_COST_PRECISION,
 };
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use crossterm::{execute, terminal};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
@@ -25,33 +33,13 @@ use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::Frame;
 
-const MODEL_DISPLAY_GROUPS: &[(&str, &[ModelBucket])] = &[
-    (
-        "gpt-5-codex",
-        &[
-            ModelBucket::Gpt5Codex,
-            ModelBucket::Gpt51Codex,
-            ModelBucket::CodeGpt5Codex,
-            ModelBucket::ChatGpt51Codex,
-        ],
-    ),
-    ("gpt-5", &[ModelBucket::Gpt5, ModelBucket::Gpt51]),
-    (
-        "gpt-5-codex-mini",
-        &[
-            ModelBucket::Gpt5Mini,
-            ModelBucket::Gpt51CodexMini,
-            ModelBucket::CodeGpt5CodexMini,
-            ModelBucket::CodeGpt5Mini,
-            ModelBucket::ChatGpt51CodexMini,
-        ],
-    ),
-    ("other", &[ModelBucket::Other]),
-];
-
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about = "Rust global token usage viewer", long_about = None)]
 struct Args {
+    /// Use this directory as CODE_HOME instead of resolving it from the environment
+    #[arg(long = "code-home", value_name = "DIR")]
+    code_home: Option<PathBuf>,
+
     /// Override the session logs directory (default scans ~/.code + ~/.codex + slots)
     #[arg(long = "sessions-dir", value_name = "DIR")]
     sessions_dir: Option<PathBuf>,
@@ -65,9 +53,62 @@ struct Args {
     )]
     workers: Option<usize>,
 
+    /// Skip a session source by label (e.g. `.code/slot/work`) or directory prefix; repeatable
+    #[arg(long = "exclude-source", value_name = "LABEL_OR_PATH")]
+    exclude_sources: Vec<String>,
+
     /// Display per-session totals in the detailed panel
     #[arg(long = "verbose")]
     verbose: bool,
+
+    /// Label buckets relative to the scan time (e.g. "2h ago–1h ago")
+    #[arg(long = "relative-time")]
+    relative_time: bool,
+
+    /// Rescan every N seconds instead of waiting for `r` (for mounts without reliable file events)
+    #[arg(
+        long = "watch-interval",
+        value_name = "SECS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    watch_interval: Option<u64>,
+
+    /// Pin the scan clock to a fixed RFC 3339 timestamp (debugging/golden output)
+    #[arg(long = "now", value_name = "RFC3339", hide = true, value_parser = parse_fixed_now)]
+    now: Option<DateTime<Utc>>,
+}
+
+fn parse_fixed_now(value: &str) -> std::result::Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|ts| ts.with_timezone(&Utc))
+        .map_err(|err| format!("invalid RFC 3339 timestamp: {err}"))
+}
+
+/// Fires a refresh every `interval` for `--watch-interval`. Ticks missed
+/// while the UI was busy collapse into a single refresh rather than a burst.
+#[derive(Debug, Clone)]
+struct RefreshTimer {
+    interval: Duration,
+    next_due: Instant,
+}
+
+impl RefreshTimer {
+    fn new(interval: Duration, start: Instant) -> Self {
+        Self {
+            interval,
+            next_due: start + interval,
+        }
+    }
+
+    fn poll(&mut self, now: Instant) -> bool {
+        if now < self.next_due {
+            return false;
+        }
+        while self.next_due <= now {
+            self.next_due += self.interval;
+        }
+        true
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -83,11 +124,14 @@ struct ScanConfig {
     code_home: PathBuf,
     sessions_dir: Option<PathBuf>,
     workers: Option<usize>,
+    exclude_sources: Vec<String>,
     verbose_sessions: bool,
+    now: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug)]
 enum ScanResult {
+    Progress(ScanProgress),
     Snapshot(GlobalUsageSnapshot, DateTime<Utc>),
     Error(String),
 }
@@ -105,31 +149,41 @@ struct App {
     last_updated: Option<DateTime<Utc>>,
     last_error: Option<String>,
     verbose_sessions: bool,
+    relative_time: bool,
     request_in_flight: bool,
+    progress: Option<ScanProgress>,
 }
 
 impl App {
-    fn new(verbose: bool) -> Self {
+    fn new(verbose: bool, relative_time: bool) -> Self {
         Self {
             status: AppStatus::Idle,
             last_snapshot: None,
             last_updated: None,
             last_error: None,
             verbose_sessions: verbose,
+            relative_time,
             request_in_flight: false,
+            progress: None,
         }
     }
 
     fn apply_result(&mut self, result: ScanResult) {
-        self.request_in_flight = false;
         match result {
+            ScanResult::Progress(progress) => {
+                self.progress = Some(progress);
+            }
             ScanResult::Snapshot(snapshot, ts) => {
+                self.request_in_flight = false;
+                self.progress = None;
                 self.last_snapshot = Some(snapshot);
                 self.last_updated = Some(ts);
                 self.last_error = None;
                 self.status = AppStatus::Ready;
             }
             ScanResult::Error(err) => {
+                self.request_in_flight = false;
+                self.progress = None;
                 self.last_error = Some(err);
                 self.status = AppStatus::Error;
             }
@@ -139,6 +193,7 @@ impl App {
     fn mark_scanning(&mut self) {
         self.status = AppStatus::Scanning;
         self.request_in_flight = true;
+        self.progress = None;
     }
 
     fn toggle_verbose(&mut self) {
@@ -148,37 +203,93 @@ impl App {
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let code_home = find_code_home().context("failed to locate CODE_HOME")?;
+    let code_home = resolve_code_home(args.code_home.clone(), find_code_home)?;
     let scan_cfg = ScanConfig {
         code_home,
         sessions_dir: args.sessions_dir,
         workers: args.workers.filter(|w| *w > 0),
+        exclude_sources: args.exclude_sources,
         verbose_sessions: args.verbose,
+        now: args.now,
     };
 
     let (scan_tx, scan_rx) = mpsc::channel::<AppCommand>();
     let (result_tx, result_rx) = mpsc::channel::<ScanResult>();
     start_scan_worker(scan_cfg.clone(), scan_rx, result_tx)?;
 
+    let interrupted = install_interrupt_handlers()?;
+    install_panic_hook();
+
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
     execute!(stdout, terminal::EnterAlternateScreen, event::EnableMouseCapture)?;
     let backend = ratatui::backend::CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(scan_cfg.verbose_sessions);
+    let mut app = App::new(scan_cfg.verbose_sessions, args.relative_time);
     request_refresh(&scan_tx, &mut app)?;
 
-    let res = run_app(&mut terminal, &mut app, &scan_tx, &result_rx);
+    let mut watch = args
+        .watch_interval
+        .map(|secs| RefreshTimer::new(Duration::from_secs(secs), Instant::now()));
+    let res = run_app(&mut terminal, &mut app, &scan_tx, &result_rx, &interrupted, watch.as_mut());
 
+    restore_terminal(terminal.backend_mut())?;
+    res
+}
+
+/// Returns the `--code-home` override when given, only falling back to
+/// `find_code_home` (which needs a resolvable home directory) without one.
+fn resolve_code_home(
+    override_dir: Option<PathBuf>,
+    find: impl FnOnce() -> std::io::Result<PathBuf>,
+) -> Result<PathBuf> {
+    match override_dir {
+        Some(dir) if dir.is_dir() => Ok(dir),
+        Some(dir) => anyhow::bail!("--code-home {} is not a directory", dir.display()),
+        None => find().context("failed to locate CODE_HOME (pass --code-home to override)"),
+    }
+}
+
+/// Leave raw mode and the alternate screen. Safe to call more than once, so
+/// the panic hook and the normal exit path can both run it.
+fn restore_terminal<W: Write>(out: &mut W) -> Result<()> {
     disable_raw_mode()?;
     execute!(
-        terminal.backend_mut(),
+        out,
         terminal::LeaveAlternateScreen,
-        event::DisableMouseCapture
+        event::DisableMouseCapture,
+        crossterm::cursor::Show
     )?;
-    terminal.show_cursor()?;
-    res
+    Ok(())
+}
+
+fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal(&mut std::io::stdout());
+        previous(info);
+    }));
+}
+
+/// Raw mode swallows Ctrl-C as a key event, but SIGINT/SIGTERM can still
+/// arrive from outside; flag them so `run_app` exits through the normal
+/// cleanup path.
+#[cfg(unix)]
+fn install_interrupt_handlers() -> Result<Arc<AtomicBool>> {
+    use signal_hook::consts::signal::{SIGINT, SIGTERM};
+
+    let flag = Arc::new(AtomicBool::new(false));
+    for signal in [SIGINT, SIGTERM] {
+        signal_hook::flag::register(signal, Arc::clone(&flag))
+            .with_context(|| format!("failed to register handler for signal {signal}"))?;
+    }
+    Ok(flag)
+}
+
+#[cfg(not(unix))]
+fn install_interrupt_handlers() -> Result<Arc<AtomicBool>> {
+    Ok(Arc::new(AtomicBool::new(false)))
 }
 
 fn start_scan_worker(
@@ -191,8 +302,17 @@ fn start_scan_worker(
         for cmd in rx {
             match cmd {
                 AppCommand::Refresh => {
-                    let request = build_scan_options(&cfg, verbose);
-                    let result = scan_once(request);
+                    let (progress_tx, progress_rx) = mpsc::channel::<ScanProgress>();
+                    let request = build_scan_options(&cfg, verbose).with_progress(progress_tx);
+                    let result = thread::scope(|scope| {
+                        let forward_tx = tx.clone();
+                        scope.spawn(move || {
+                            for progress in progress_rx {
+                                let _ = forward_tx.send(ScanResult::Progress(progress));
+                            }
+                        });
+                        scan_once(request, cfg.now)
+                    });
                     let _ = tx.send(result);
                 }
                 AppCommand::ToggleVerbose => {
@@ -213,11 +333,17 @@ fn build_scan_options(cfg: &ScanConfig, verbose: bool) -> GlobalUsageScanOptions
     if let Some(workers) = cfg.workers {
         options = options.with_max_workers(workers);
     }
-    options.with_record_sessions(verbose)
+    options
+        .with_record_sessions(verbose)
+        .with_excluded_sources(cfg.exclude_sources.clone())
 }
 
-fn scan_once(options: GlobalUsageScanOptions) -> ScanResult {
-    match scan_global_usage(options) {
+fn scan_once(options: GlobalUsageScanOptions, now: Option<DateTime<Utc>>) -> ScanResult {
+    let scanned = match now {
+        Some(now) => scan_global_usage_at(options, now),
+        None => scan_global_usage(options),
+    };
+    match scanned {
         Ok(snapshot) => {
             let generated = snapshot.generated_at;
             ScanResult::Snapshot(snapshot, generated)
@@ -231,13 +357,27 @@ fn run_app(
     app: &mut App,
     scan_tx: &Sender<AppCommand>,
     result_rx: &Receiver<ScanResult>,
+    interrupted: &AtomicBool,
+    mut watch: Option<&mut RefreshTimer>,
 ) -> Result<()> {
     let mut last_draw = Instant::now();
     loop {
+        if interrupted.load(Ordering::Relaxed) {
+            let _ = scan_tx.send(AppCommand::Quit);
+            break;
+        }
+
         while let Ok(result) = result_rx.try_recv() {
             app.apply_result(result);
         }
 
+        let watch_due = watch
+            .as_deref_mut()
+            .is_some_and(|timer| timer.poll(Instant::now()));
+        if watch_due && !app.request_in_flight {
+            request_refresh(scan_tx, app)?;
+        }
+
         if last_draw.elapsed() >= Duration::from_millis(16) {
             terminal.draw(|frame| draw_ui(frame, app))?;
             last_draw = Instant::now();
@@ -256,6 +396,10 @@ fn run_app(
 
 fn handle_key_event(key: KeyEvent, app: &mut App, scan_tx: &Sender<AppCommand>) -> Result<bool> {
     match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let _ = scan_tx.send(AppCommand::Quit);
+            return Ok(true);
+        }
         KeyCode::Char('q') | KeyCode::Esc => {
             let _ = scan_tx.send(AppCommand::Quit);
             return Ok(true);
@@ -315,10 +459,15 @@ fn draw_header(frame: &mut Frame<'_>, area: Rect, app: &App) {
         "Status: {status}    Last updated: {timestamp}    {help}"
     );
     let mut lines = vec![Line::from(text)];
+    if let (AppStatus::Scanning, Some(progress)) = (app.status, app.progress) {
+        lines.push(Line::from(format_progress_line(progress)));
+    }
     if let Some(snapshot) = &app.last_snapshot {
         lines.push(Line::from(format!(
-            "Sessions processed: {}  missing totals: {}",
-            snapshot.sessions_processed, snapshot.sessions_missing_totals
+            "Sessions processed: {}  missing totals: {}  scan time: {} ms",
+            snapshot.sessions_processed,
+            snapshot.sessions_missing_totals,
+            snapshot.scan_duration_ms
         )));
     }
     if let Some(err) = app.last_error.as_ref() {
@@ -335,6 +484,20 @@ fn draw_header(frame: &mut Frame<'_>, area: Rect, app: &App) {
     );
 }
 
+fn format_progress_line(progress: ScanProgress) -> String {
+    const BAR_WIDTH: usize = 30;
+    let filled = (progress.files_done.min(progress.files_total) * BAR_WIDTH)
+        .checked_div(progress.files_total)
+        .unwrap_or(BAR_WIDTH);
+    format!(
+        "[{}{}] {}/{} files",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        progress.files_done,
+        progress.files_total
+    )
+}
+
 fn draw_totals(frame: &mut Frame<'_>, area: Rect, app: &App) {
     let snapshot = match &app.last_snapshot {
         Some(s) => s,
@@ -356,7 +519,7 @@ fn draw_totals(frame: &mut Frame<'_>, area: Rect, app: &App) {
             snapshot.totals.output_tokens + snapshot.totals.reasoning_output_tokens,
         ),
         format_total_line("Total", snapshot.totals.total_tokens),
-        format!("Cost: ${:.2}", snapshot.totals.cost_usd),
+        format!("Cost: {}", format_cost(snapshot.totals.cost_usd, DEFAULT_COST_PRECISION)),
     ];
     let totals_para = Paragraph::new(join_lines(&totals_lines)).wrap(Wrap { trim: false });
     frame.render_widget(
@@ -364,14 +527,15 @@ fn draw_totals(frame: &mut Frame<'_>, area: Rect, app: &App) {
         layout[0],
     );
 
-    let trailing_lines = vec![
-        format_window_line("Last hour", &snapshot.trailing.last_hour),
-        format_window_line("Last 12h", &snapshot.trailing.last_twelve_hours),
-        format_window_line("Last day", &snapshot.trailing.last_day),
-        format_window_line("Last 7d", &snapshot.trailing.last_seven_days),
-        format_window_line("Last 30d", &snapshot.trailing.last_thirty_days),
-        format_window_line("Last year", &snapshot.trailing.last_year),
-    ];
+    let windows = trailing_windows(&snapshot.trailing, layout[1].width);
+    let mut trailing_lines: Vec<String> = windows
+        .iter()
+        .map(|(label, totals)| format_window_line(label, totals))
+        .collect();
+    let hidden = TRAILING_WINDOW_COUNT - windows.len();
+    if hidden > 0 {
+        trailing_lines.push(format!("({hidden} more windows hidden; widen terminal)"));
+    }
     let trailing_para = Paragraph::new(join_lines(&trailing_lines)).wrap(Wrap { trim: true });
     frame.render_widget(
         trailing_para
@@ -380,6 +544,33 @@ fn draw_totals(frame: &mut Frame<'_>, area: Rect, app: &App) {
     );
 }
 
+/// Number of trailing windows shown when the column is wide enough.
+const TRAILING_WINDOW_COUNT: usize = 6;
+
+/// Below this column width only the hour/day/30d windows are shown.
+const NARROW_WINDOW_COLUMN_WIDTH: u16 = 56;
+
+fn trailing_windows(
+    trailing: &TrailingUsageTotals,
+    width: u16,
+) -> Vec<(&'static str, &UsageTotals)> {
+    if width < NARROW_WINDOW_COLUMN_WIDTH {
+        return vec![
+            ("Last hour", &trailing.last_hour),
+            ("Last day", &trailing.last_day),
+            ("Last 30d", &trailing.last_thirty_days),
+        ];
+    }
+    vec![
+        ("Last hour", &trailing.last_hour),
+        ("Last 12h", &trailing.last_twelve_hours),
+        ("Last day", &trailing.last_day),
+        ("Last 7d", &trailing.last_seven_days),
+        ("Last 30d", &trailing.last_thirty_days),
+        ("Last year", &trailing.last_year),
+    ]
+}
+
 fn draw_detail(frame: &mut Frame<'_>, area: Rect, app: &App) {
     let snapshot = match &app.last_snapshot {
         Some(s) => s,
@@ -396,7 +587,8 @@ fn draw_detail(frame: &mut Frame<'_>, area: Rect, app: &App) {
 
     draw_model_groups(frame, rows[0], snapshot);
     draw_source_panel(frame, rows[1], &snapshot.source_usage);
-    draw_bucket_panel(frame, rows[2], snapshot, app.verbose_sessions);
+    let relative_to = app.relative_time.then_some(snapshot.generated_at);
+    draw_bucket_panel(frame, rows[2], snapshot, app.verbose_sessions, relative_to);
 }
 
 fn draw_bucket_panel(
@@ -404,6 +596,7 @@ fn draw_bucket_panel(
     area: Rect,
     snapshot: &GlobalUsageSnapshot,
     show_sessions: bool,
+    relative_to: Option<DateTime<Utc>>,
 ) {
     let rows = Layout::default()
         .direction(Direction::Vertical)
@@ -424,6 +617,7 @@ fn draw_bucket_panel(
         "Hourly (last 12)",
         &snapshot.hourly_buckets,
         12,
+        relative_to,
     );
     render_bucket_section(
         frame,
@@ -431,6 +625,7 @@ fn draw_bucket_panel(
         "12-hour (last 14)",
         &snapshot.twelve_hour_buckets,
         14,
+        relative_to,
     );
 
     let mid_cols = Layout::default()
@@ -443,6 +638,7 @@ fn draw_bucket_panel(
         "Daily (last 7)",
         &snapshot.daily_buckets,
         7,
+        relative_to,
     );
     render_bucket_section(
         frame,
@@ -450,6 +646,7 @@ fn draw_bucket_panel(
         "Weekly (last 8)",
         &snapshot.weekly_buckets,
         8,
+        relative_to,
     );
 
     let bottom_cols = Layout::default()
@@ -462,6 +659,7 @@ fn draw_bucket_panel(
         "Monthly (last 6)",
         &snapshot.monthly_buckets,
         6,
+        relative_to,
     );
 
     let session_lines = session_summary_lines(snapshot, show_sessions);
@@ -473,23 +671,31 @@ fn draw_bucket_panel(
     );
 }
 
-fn bucket_lines(_title: &str, buckets: &[UsageBucket], limit: usize) -> Vec<String> {
+fn bucket_lines(
+    _title: &str,
+    buckets: &[UsageBucket],
+    limit: usize,
+    relative_to: Option<DateTime<Utc>>,
+) -> Vec<String> {
     let mut lines = Vec::new();
     if buckets.is_empty() {
         lines.push("  (no data)".to_string());
         return lines;
     }
     for bucket in buckets.iter().take(limit) {
-        let label = format!(
-            "{}-{}",
-            bucket.start.format("%m-%d %H:%M"),
-            bucket.end.format("%H:%M")
-        );
+        let label = match relative_to {
+            Some(now) => bucket.relative_label(now),
+            None => format!(
+                "{}-{}",
+                bucket.start.format("%m-%d %H:%M"),
+                bucket.end.format("%H:%M")
+            ),
+        };
         lines.push(format!(
-            "  {}  {}  ${:.2}",
+            "  {}  {}  {}",
             label,
             format_token_number(bucket.totals.total_tokens),
-            bucket.totals.cost_usd
+            format_cost(bucket.totals.cost_usd, DEFAULT_COST_PRECISION)
         ));
     }
     lines
@@ -535,8 +741,9 @@ fn render_bucket_section(
     title: &str,
     buckets: &[UsageBucket],
     limit: usize,
+    relative_to: Option<DateTime<Utc>>,
 ) {
-    let lines = bucket_lines(title, buckets, limit);
+    let lines = bucket_lines(title, buckets, limit, relative_to);
     frame.render_widget(
         Paragraph::new(join_lines(&lines))
             .wrap(Wrap { trim: true })
@@ -552,28 +759,29 @@ fn draw_model_groups(frame: &mut Frame<'_>, area: Rect, snapshot: &GlobalUsageSn
     }
 
     let mut lines = Vec::new();
-    for (group_label, members) in MODEL_DISPLAY_GROUPS {
+    for group_label in ModelBucket::DISPLAY_GROUPS {
         let mut group_total = UsageTotals::default();
         let mut member_lines = Vec::new();
-        for bucket in *members {
-            if let Some(value) = usage_by_bucket.get(bucket) {
-                accumulate_totals(&mut group_total, value);
-                member_lines.push(format!(
-                    "    {:<18} tokens={} cost=${:.2}",
-                    bucket.as_str(),
-                    format_token_number(value.total_tokens),
-                    value.cost_usd
-                ));
+        for (bucket, value) in &usage_by_bucket {
+            if bucket.display_group() != group_label {
+                continue;
             }
+            accumulate_totals(&mut group_total, value);
+            member_lines.push(format!(
+                "    {:<18} tokens={} cost={}",
+                bucket.as_str(),
+                format_token_number(value.total_tokens),
+                format_cost(value.cost_usd, DEFAULT_COST_PRECISION)
+            ));
         }
         if group_total.total_tokens == 0 && member_lines.is_empty() {
             continue;
         }
         lines.push(format!(
-            "{:<16} tokens={} cost=${:.2}",
+            "{:<16} tokens={} cost={}",
             group_label,
             format_token_number(group_total.total_tokens),
-            group_total.cost_usd
+            format_cost(group_total.cost_usd, DEFAULT_COST_PRECISION)
         ));
         lines.extend(member_lines);
     }
@@ -596,10 +804,10 @@ fn draw_source_panel(frame: &mut Frame<'_>, area: Rect, sources: &[SourceUsage])
     let mut lines = Vec::new();
     for entry in sources.iter().take(8) {
         lines.push(format!(
-            "{:24} tokens={} cost=${:.2}",
+            "{:24} tokens={} cost={}",
             entry.label,
             format_token_number(entry.totals.total_tokens),
-            entry.totals.cost_usd
+            format_cost(entry.totals.cost_usd, DEFAULT_COST_PRECISION)
         ));
     }
     if lines.is_empty() {
@@ -633,6 +841,9 @@ fn accumulate_totals(target: &mut UsageTotals, value: &UsageTotals) {
     target.cached_input_tokens = target
         .cached_input_tokens
         .saturating_add(value.cached_input_tokens);
+    target.cache_write_tokens = target
+        .cache_write_tokens
+        .saturating_add(value.cache_write_tokens);
     target.output_tokens = target.output_tokens.saturating_add(value.output_tokens);
     target.reasoning_output_tokens = target
         .reasoning_output_tokens
@@ -653,8 +864,11 @@ fn format_window_line(label: &str, totals: &UsageTotals) -> String {
     let cached = format_token_number(totals.cached_input_tokens);
     let output = format_token_number(totals.output_tokens + totals.reasoning_output_tokens);
     format!(
-        "{label:<10} nc={} cached={} out={} cost=${:.2}",
-        non_cached, cached, output, totals.cost_usd
+        "{label:<10} nc={} cached={} out={} cost={}",
+        non_cached,
+        cached,
+        output,
+        format_cost(totals.cost_usd, DEFAULT_COST_PRECISION)
     )
 }
 
@@ -673,3 +887,90 @@ fn format_token_number(value: u64) -> String {
     }
     format!("{value}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn narrow_columns_collapse_trailing_windows() {
+        let trailing = TrailingUsageTotals::default();
+        let labels = |width: u16| {
+            trailing_windows(&trailing, width)
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(labels(30), vec!["Last hour", "Last day", "Last 30d"]);
+        assert_eq!(labels(80).len(), TRAILING_WINDOW_COUNT);
+        assert_eq!(labels(80)[5], "Last year");
+    }
+
+    #[test]
+    fn fixed_now_produces_stable_bucket_labels() {
+        let missing = std::env::temp_dir().join("code-token-usage-fixed-now-missing");
+        let cfg = ScanConfig {
+            code_home: missing.clone(),
+            sessions_dir: Some(missing),
+            workers: Some(1),
+            exclude_sources: Vec::new(),
+            verbose_sessions: false,
+            now: Some(parse_fixed_now("2025-11-19T12:00:00Z").expect("timestamp")),
+        };
+        let ScanResult::Snapshot(snapshot, _) = scan_once(build_scan_options(&cfg, false), cfg.now)
+        else {
+            panic!("scan failed");
+        };
+
+        let hourly = bucket_lines("Hourly", &snapshot.hourly_buckets, 12, None);
+        assert_eq!(hourly.len(), 12);
+        assert_eq!(hourly[0], "  11-19 00:00-01:00  0  $0.00");
+        assert_eq!(hourly[11], "  11-19 11:00-12:00  0  $0.00");
+        let daily = bucket_lines("Daily", &snapshot.daily_buckets, 7, None);
+        assert_eq!(daily[6], "  11-18 12:00-12:00  0  $0.00");
+    }
+
+    #[test]
+    fn restore_terminal_is_idempotent() {
+        let mut out = Vec::new();
+        restore_terminal(&mut out).expect("first restore");
+        restore_terminal(&mut out).expect("second restore");
+        assert!(!out.is_empty());
+    }
+
+    #[test]
+    fn code_home_override_skips_home_resolution() {
+        let dir = std::env::temp_dir();
+        let resolved = resolve_code_home(Some(dir.clone()), || {
+            panic!("find_code_home must not be consulted when --code-home is set")
+        })
+        .expect("override");
+        assert_eq!(resolved, dir);
+
+        let missing = dir.join("code-token-usage-code-home-missing");
+        let err = resolve_code_home(Some(missing), || panic!("not consulted")).expect_err("missing dir");
+        assert!(err.to_string().contains("not a directory"), "{err}");
+
+        let fallback = resolve_code_home(None, || Ok(dir.join("found"))).expect("fallback");
+        assert_eq!(fallback, dir.join("found"));
+    }
+
+    #[test]
+    fn refresh_timer_fires_once_per_interval() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut timer = RefreshTimer::new(Duration::from_secs(5), start);
+
+        assert!(!timer.poll(at(4)));
+        assert!(timer.poll(at(5)));
+        assert!(!timer.poll(at(6)));
+        assert!(!timer.poll(at(9)));
+        assert!(timer.poll(at(10)));
+
+        // A long stall yields one refresh, then the cadence resumes.
+        assert!(timer.poll(at(31)));
+        assert!(!timer.poll(at(34)));
+        assert!(timer.poll(at(35)));
+    }
+}
@@ -1,3 +1,5 @@
+use chrono::{DateTime, Utc};
+use code_common::elapsed::format_duration;
 use code_core::auth_accounts::StoredAccount;
 use code_protocol::mcp_protocol::AuthMode;
 
@@ -5,7 +7,16 @@ const KEY_SUFFIX_LEN: usize = 8;
 
 /// Returns a user-facing label for the given account.
 /// Prefers stored labels when present, otherwise formats a sensible default.
+/// ChatGPT accounts get their current plan appended, e.g. `Work (Pro)`.
 pub(crate) fn account_display_label(account: &StoredAccount) -> String {
+    let label = base_display_label(account);
+    match (account.mode, account.chatgpt_plan()) {
+        (AuthMode::ChatGPT, Some(plan)) => format!("{label} ({plan})"),
+        _ => label,
+    }
+}
+
+fn base_display_label(account: &StoredAccount) -> String {
     if let Some(label) = account.label.as_ref() {
         let trimmed = label.trim();
         if !trimmed.is_empty() {
@@ -69,3 +80,51 @@ pub(crate) fn account_mode_priority(mode: AuthMode) -> u8 {
         AuthMode::ApiKey => 1,
     }
 }
+
+/// Status text for an account the scheduler has put in cooldown, e.g.
+/// "rate-limited · resumes in 3m 00s". Expired cooldowns read "available".
+#[allow(dead_code)]
+pub(crate) fn format_cooldown(resume_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let remaining_ms = resume_at.signed_duration_since(now).num_milliseconds();
+    if remaining_ms <= 0 {
+        return "available".to_string();
+    }
+    // Round up so a cooldown never reads as over before it is.
+    let remaining_secs = (remaining_ms as u64).div_ceil(1_000);
+    let remaining = format_duration(std::time::Duration::from_secs(remaining_secs));
+    format!("rate-limited · resumes in {remaining}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn cooldown_reports_remaining_time() {
+        let now = Utc::now();
+        assert_eq!(
+            format_cooldown(now + Duration::seconds(42), now),
+            "rate-limited · resumes in 42s"
+        );
+        assert_eq!(
+            format_cooldown(now + Duration::milliseconds(300), now),
+            "rate-limited · resumes in 1s"
+        );
+        assert_eq!(
+            format_cooldown(now + Duration::seconds(185), now),
+            "rate-limited · resumes in 3m 05s"
+        );
+        assert_eq!(
+            format_cooldown(now + Duration::minutes(130), now),
+            "rate-limited · resumes in 2h 10m"
+        );
+    }
+
+    #[test]
+    fn expired_cooldown_is_available() {
+        let now = Utc::now();
+        assert_eq!(format_cooldown(now, now), "available");
+        assert_eq!(format_cooldown(now - Duration::minutes(5), now), "available");
+    }
+}
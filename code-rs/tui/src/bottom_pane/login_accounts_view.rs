@@ -1032,16 +1032,6 @@ impl AccountRow {
         let mode = account.mode;
         let mut detail_parts: Vec<String> = Vec::new();
 
-        if let AuthMode::ChatGPT = mode {
-            if let Some(plan) = account
-                .tokens
-                .as_ref()
-                .and_then(|t| t.id_token.get_chatgpt_plan_type())
-            {
-                detail_parts.push(format!("{plan} Plan"));
-            }
-        }
-
         if let Some(created_at) = account.created_at {
             detail_parts.push(format!("connected {}", format_timestamp(created_at)));
         }
@@ -100,7 +100,16 @@ pub(crate) struct ModelSelectionView {
     target: ModelSelectionTarget,
     available_targets: Vec<ModelSelectionTarget>,
     target_state: HashMap<ModelSelectionTarget, TargetContext>,
+    /// Last cursor position per target, restored when tabbing back.
+    remembered_selection: HashMap<ModelSelectionTarget, usize>,
+    /// Inherit-vs-override row choice per target, so an override picked this
+    /// session is not reset to the entry's `inherits_from_session` on return.
+    remembered_inherit: HashMap<ModelSelectionTarget, bool>,
     auto_inherit_selected: bool,
+    auto_custom_selected: bool,
+    /// Free-form model name being typed for the Auto target, if editing.
+    custom_model_input: Option<String>,
+    custom_model_invalid: bool,
 }
 
 impl ModelSelectionView {
@@ -143,8 +152,13 @@ impl ModelSelectionView {
             target: initial_target,
             available_targets,
             target_state,
+            remembered_selection: HashMap::new(),
+            remembered_inherit: HashMap::new(),
             auto_inherit_selected: matches!(initial_target, ModelSelectionTarget::Auto)
                 && inherits_flag,
+            auto_custom_selected: false,
+            custom_model_input: None,
+            custom_model_invalid: false,
         }
     }
 
@@ -174,13 +188,28 @@ impl ModelSelectionView {
 
     fn apply_target(&mut self, target: ModelSelectionTarget) {
         if let Some(ctx) = self.target_state.get(&target) {
+            self.remembered_selection.insert(self.target, self.selected_index);
+            self.remembered_inherit.insert(self.target, self.auto_inherit_selected);
             self.target = target;
             self.current_model = ctx.model.clone();
             self.current_effort = ctx.effort;
-            self.selected_index =
-                Self::initial_selection(&self.presets, &self.current_model, self.current_effort);
+            self.selected_index = match self.remembered_selection.get(&target) {
+                Some(&idx) if idx < self.presets.len() => idx,
+                _ => Self::initial_selection(
+                    &self.presets,
+                    &self.current_model,
+                    self.current_effort,
+                ),
+            };
             self.auto_inherit_selected = matches!(target, ModelSelectionTarget::Auto)
-                && ctx.inherits_from_session;
+                && self
+                    .remembered_inherit
+                    .get(&target)
+                    .copied()
+                    .unwrap_or(ctx.inherits_from_session);
+            self.auto_custom_selected = false;
+            self.custom_model_input = None;
+            self.custom_model_invalid = false;
         }
     }
 
@@ -244,17 +273,20 @@ impl ModelSelectionView {
     }
 
     fn move_selection_up(&mut self) {
-        if self.presets.is_empty() {
-            return;
-        }
-        let sorted = self.sorted_indices();
+        let sorted = self.selectable_indices();
         if sorted.is_empty() {
+            self.toggle_auto_special_rows();
             return;
         }
 
         if matches!(self.target, ModelSelectionTarget::Auto) {
             if self.auto_inherit_selected {
                 self.auto_inherit_selected = false;
+                self.auto_custom_selected = true;
+                return;
+            }
+            if self.auto_custom_selected {
+                self.auto_custom_selected = false;
                 self.selected_index = *sorted.last().unwrap_or(&0);
                 return;
             }
@@ -283,11 +315,9 @@ impl ModelSelectionView {
     }
 
     fn move_selection_down(&mut self) {
-        if self.presets.is_empty() {
-            return;
-        }
-        let sorted = self.sorted_indices();
+        let sorted = self.selectable_indices();
         if sorted.is_empty() {
+            self.toggle_auto_special_rows();
             return;
         }
 
@@ -297,12 +327,17 @@ impl ModelSelectionView {
                 self.selected_index = sorted[0];
                 return;
             }
+            if self.auto_custom_selected {
+                self.auto_custom_selected = false;
+                self.auto_inherit_selected = true;
+                return;
+            }
             let current_pos = sorted
                 .iter()
                 .position(|&idx| idx == self.selected_index)
                 .unwrap_or(0);
             if current_pos + 1 >= sorted.len() {
-                self.auto_inherit_selected = true;
+                self.auto_custom_selected = true;
                 return;
             }
             self.selected_index = sorted[current_pos + 1];
@@ -317,41 +352,117 @@ impl ModelSelectionView {
         self.selected_index = sorted[new_pos];
     }
 
-    fn confirm_selection(&mut self) {
+    /// With no presets the Auto target only has its inherit and "Other model…" rows.
+    fn toggle_auto_special_rows(&mut self) {
+        if !matches!(self.target, ModelSelectionTarget::Auto) {
+            return;
+        }
+        let inherit = !self.auto_inherit_selected;
+        self.auto_inherit_selected = inherit;
+        self.auto_custom_selected = !inherit;
+    }
+
+    /// Applies the highlighted row. With `keep_open` (Shift+Enter) the panel
+    /// stays up and adopts the applied model as current so another can be tried.
+    fn confirm_selection(&mut self, keep_open: bool) {
         if matches!(self.target, ModelSelectionTarget::Auto) && self.auto_inherit_selected {
             if let Some(session_ctx) = self.target_state.get(&ModelSelectionTarget::Session) {
                 let _ = self.app_event_tx.send(AppEvent::UpdateAutoModelSelection {
                     model: session_ctx.model.clone(),
                 });
             }
-            self.is_complete = true;
+            self.is_complete = !keep_open;
             return;
         }
-        if let Some(preset) = self.presets.get(self.selected_index) {
-            let effort = Self::preset_effort(preset);
-            match self.target {
-                ModelSelectionTarget::Session => {
-                    let _ = self.app_event_tx.send(AppEvent::UpdateModelSelection {
-                        model: preset.model.to_string(),
-                        effort: Some(effort),
-                    });
-                }
-                ModelSelectionTarget::Auto => {
-                    let _ = self
-                        .app_event_tx
-                        .send(AppEvent::UpdateAutoModelSelection { model: preset.model.to_string() });
-                }
-                ModelSelectionTarget::Review => {
-                    let _ = self.app_event_tx.send(AppEvent::UpdateReviewModelSelection {
-                        model: preset.model.to_string(),
-                        effort,
-                    });
-                }
+        if matches!(self.target, ModelSelectionTarget::Auto) && self.auto_custom_selected {
+            self.custom_model_input = Some(String::new());
+            self.custom_model_invalid = false;
+            return;
+        }
+        // Nothing to confirm without presets; keep the panel open.
+        let Some(preset) = self.presets.get(self.selected_index) else {
+            return;
+        };
+        if !preset.supports_effort() {
+            return;
+        }
+        let effort = Self::preset_effort(preset);
+        match self.target {
+            ModelSelectionTarget::Session => {
+                let _ = self.app_event_tx.send(AppEvent::UpdateModelSelection {
+                    model: preset.model.to_string(),
+                    effort: Some(effort),
+                });
+            }
+            ModelSelectionTarget::Auto => {
+                let _ = self
+                    .app_event_tx
+                    .send(AppEvent::UpdateAutoModelSelection { model: preset.model.to_string() });
             }
+            ModelSelectionTarget::Review => {
+                let _ = self.app_event_tx.send(AppEvent::UpdateReviewModelSelection {
+                    model: preset.model.to_string(),
+                    effort,
+                });
+            }
+        }
+        if !keep_open {
+            self.is_complete = true;
+            return;
+        }
+        let model = preset.model.to_string();
+        self.current_model = model.clone();
+        self.current_effort = effort;
+        if let Some(ctx) = self.target_state.get_mut(&self.target) {
+            ctx.model = model;
+            ctx.effort = effort;
+            ctx.inherits_from_session = false;
         }
+    }
+
+    fn confirm_custom_model(&mut self) {
+        let Some(input) = self.custom_model_input.as_ref() else {
+            return;
+        };
+        let model = input.trim();
+        if model.is_empty() {
+            self.custom_model_invalid = true;
+            return;
+        }
+        let _ = self.app_event_tx.send(AppEvent::UpdateAutoModelSelection {
+            model: model.to_string(),
+        });
+        self.custom_model_input = None;
         self.is_complete = true;
     }
 
+    fn handle_custom_model_key(&mut self, key_event: KeyEvent) -> bool {
+        let Some(input) = self.custom_model_input.as_mut() else {
+            return false;
+        };
+        match key_event.code {
+            KeyCode::Enter => self.confirm_custom_model(),
+            KeyCode::Esc => {
+                self.custom_model_input = None;
+                self.custom_model_invalid = false;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+                self.custom_model_invalid = false;
+            }
+            KeyCode::Char(c)
+                if !key_event
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                input.push(c);
+                self.custom_model_invalid = false;
+            }
+            _ => {}
+        }
+        true
+    }
+
     fn content_line_count(&self) -> u16 {
         // Current model + reasoning effort + optional target/note rows.
         let mut lines: u16 = 2;
@@ -368,18 +479,33 @@ impl ModelSelectionView {
         lines = lines.saturating_add(1);
 
         if matches!(self.target, ModelSelectionTarget::Auto) {
-            lines = lines.saturating_add(1);
+            // Inherit row above the presets, spacer + "Other model…" row below.
+            lines = lines.saturating_add(3);
         }
 
+        let group_by_provider = self.groups_by_provider();
+        let mut previous_provider: Option<&str> = None;
         let mut previous_model: Option<&str> = None;
         for idx in self.sorted_indices() {
             let preset = &self.presets[idx];
+            let provider = Self::preset_provider(preset.model);
+            let is_new_provider = group_by_provider && previous_provider != Some(provider);
+            if is_new_provider {
+                if previous_model.is_some() {
+                    // Spacer between provider groups.
+                    lines = lines.saturating_add(1);
+                }
+                // Provider header.
+                lines = lines.saturating_add(1);
+                previous_provider = Some(provider);
+            }
+
             let is_new_model = previous_model
                 .map(|prev| !prev.eq_ignore_ascii_case(&preset.model))
                 .unwrap_or(true);
 
             if is_new_model {
-                if previous_model.is_some() {
+                if previous_model.is_some() && !is_new_provider {
                     // Spacer between model groups.
                     lines = lines.saturating_add(1);
                 }
@@ -395,6 +521,11 @@ impl ModelSelectionView {
             lines = lines.saturating_add(1);
         }
 
+        if self.presets.is_empty() {
+            // "No models available" row.
+            lines = lines.saturating_add(1);
+        }
+
         // Spacer before footer plus footer hint row.
         lines.saturating_add(2)
     }
@@ -405,7 +536,60 @@ impl ModelSelectionView {
         indices
     }
 
+    /// Sorted presets the cursor can land on; efforts the model does not
+    /// support are still rendered but skipped.
+    fn selectable_indices(&self) -> Vec<usize> {
+        self.sorted_indices()
+            .into_iter()
+            .filter(|&idx| self.presets[idx].supports_effort())
+            .collect()
+    }
+
+    /// Provider headers are only shown once presets span more than one provider.
+    fn groups_by_provider(&self) -> bool {
+        let mut providers = self
+            .presets
+            .iter()
+            .map(|preset| Self::preset_provider(preset.model));
+        match providers.next() {
+            Some(first) => providers.any(|provider| provider != first),
+            None => false,
+        }
+    }
+
+    /// `ModelPreset` carries no provider, so infer one from the model slug.
+    fn preset_provider(model: &str) -> &'static str {
+        let model = model.to_ascii_lowercase();
+        if model.starts_with("gpt-")
+            || model.starts_with("codex")
+            || ["o1", "o3", "o4"].iter().any(|prefix| model.starts_with(prefix))
+        {
+            "OpenAI"
+        } else if model.starts_with("claude") {
+            "Anthropic"
+        } else if model.starts_with("gemini") {
+            "Google"
+        } else {
+            "Other"
+        }
+    }
+
+    fn provider_rank(provider: &str) -> u8 {
+        match provider {
+            "OpenAI" => 0,
+            "Anthropic" => 1,
+            "Google" => 2,
+            _ => 3,
+        }
+    }
+
     fn compare_presets(a: &ModelPreset, b: &ModelPreset) -> Ordering {
+        let provider_rank = Self::provider_rank(Self::preset_provider(a.model))
+            .cmp(&Self::provider_rank(Self::preset_provider(b.model)));
+        if provider_rank != Ordering::Equal {
+            return provider_rank;
+        }
+
         let model_rank = Self::model_rank(a.model).cmp(&Self::model_rank(b.model));
         if model_rank != Ordering::Equal {
             return model_rank;
@@ -489,6 +673,9 @@ impl ModelSelectionView {
 
 impl ModelSelectionView {
     pub(crate) fn handle_key_event_direct(&mut self, key_event: KeyEvent) -> bool {
+        if self.custom_model_input.is_some() {
+            return self.handle_custom_model_key(key_event);
+        }
         match key_event {
             KeyEvent {
                 code: KeyCode::Up,
@@ -512,7 +699,15 @@ impl ModelSelectionView {
                 modifiers: KeyModifiers::NONE,
                 ..
             } => {
-                self.confirm_selection();
+                self.confirm_selection(false);
+                true
+            }
+            KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            } => {
+                self.confirm_selection(true);
                 true
             }
             KeyEvent {
@@ -625,16 +820,33 @@ impl ModelSelectionView {
             lines.push(self.render_auto_inherit_row());
         }
 
+        let group_by_provider = self.groups_by_provider();
+        let mut previous_provider: Option<&str> = None;
         let mut previous_model: Option<&str> = None;
         let sorted_indices = self.sorted_indices();
 
         for preset_index in sorted_indices {
             let preset = &self.presets[preset_index];
+            let provider = Self::preset_provider(preset.model);
+            let is_new_provider = group_by_provider && previous_provider != Some(provider);
+            if is_new_provider {
+                if previous_model.is_some() {
+                    lines.push(Line::from(""));
+                }
+                lines.push(Line::from(vec![Span::styled(
+                    provider,
+                    Style::default()
+                        .fg(crate::colors::primary())
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                )]));
+                previous_provider = Some(provider);
+            }
+
             if previous_model
                 .map(|m| !m.eq_ignore_ascii_case(&preset.model))
                 .unwrap_or(true)
             {
-                if previous_model.is_some() {
+                if previous_model.is_some() && !is_new_provider {
                     lines.push(Line::from(""));
                 }
                 lines.push(Line::from(vec![Span::styled(
@@ -652,46 +864,33 @@ impl ModelSelectionView {
                 previous_model = Some(preset.model);
             }
 
-            let is_selected = preset_index == self.selected_index;
+            let is_selected = preset_index == self.selected_index
+                && !self.auto_inherit_selected
+                && !self.auto_custom_selected;
             let preset_effort = Self::preset_effort(preset);
             let is_current = preset.model.eq_ignore_ascii_case(&self.current_model)
                 && preset_effort == self.current_effort;
+            let supported = preset.supports_effort();
             let label = Self::effort_label(preset_effort);
             let mut row_text = label.to_string();
             if is_current {
                 row_text.push_str(" (current)");
             }
-
-            let mut indent_style = Style::default();
-            if is_selected {
-                indent_style = indent_style
-                    .bg(crate::colors::selection())
-                    .add_modifier(Modifier::BOLD);
-            }
-
-            let mut label_style = Style::default().fg(crate::colors::text());
-            if is_selected {
-                label_style = label_style
-                    .bg(crate::colors::selection())
-                    .add_modifier(Modifier::BOLD);
-            }
-            if is_current {
-                label_style = label_style.fg(crate::colors::success());
-            }
-
-            let mut divider_style = Style::default().fg(crate::colors::text_dim());
-            if is_selected {
-                divider_style = divider_style
-                    .bg(crate::colors::selection())
-                    .add_modifier(Modifier::BOLD);
+            if !supported {
+                row_text.push_str(" (unsupported)");
             }
 
-            let mut description_style = Style::default().fg(crate::colors::dim());
-            if is_selected {
-                description_style = description_style
-                    .bg(crate::colors::selection())
-                    .add_modifier(Modifier::BOLD);
-            }
+            let row_style = |style: Style| {
+                if is_selected {
+                    style.patch(Self::highlight_style())
+                } else {
+                    style
+                }
+            };
+            let indent_style = row_style(Style::default());
+            let label_style = Self::preset_label_style(is_selected, is_current, supported);
+            let divider_style = row_style(Style::default().fg(crate::colors::text_dim()));
+            let description_style = row_style(Style::default().fg(crate::colors::dim()));
 
             let description = Self::effort_description(preset_effort);
 
@@ -703,12 +902,38 @@ impl ModelSelectionView {
             ]));
         }
 
+        if self.presets.is_empty() {
+            lines.push(Line::from(vec![Span::styled(
+                "   No models available",
+                Style::default()
+                    .fg(crate::colors::text_dim())
+                    .add_modifier(Modifier::ITALIC),
+            )]));
+        }
+
+        if matches!(self.target, ModelSelectionTarget::Auto) {
+            lines.push(Line::from(""));
+            lines.push(self.render_auto_custom_row());
+        }
+
         lines.push(Line::from(""));
+        if self.custom_model_input.is_some() {
+            lines.push(Line::from(vec![
+                Span::styled("Enter", Style::default().fg(crate::colors::success())),
+                Span::raw(" Use model  "),
+                Span::styled("Esc", Style::default().fg(crate::colors::error())),
+                Span::raw(" Back"),
+            ]));
+            Self::render_lines(lines, area, buf);
+            return;
+        }
         let mut footer = vec![
             Span::styled("↑↓", Style::default().fg(crate::colors::light_blue())),
             Span::raw(" Navigate  "),
             Span::styled("Enter", Style::default().fg(crate::colors::success())),
             Span::raw(" Select  "),
+            Span::styled("Shift+Enter", Style::default().fg(crate::colors::success())),
+            Span::raw(" Apply & stay  "),
             Span::styled("Esc", Style::default().fg(crate::colors::error())),
             Span::raw(" Cancel"),
         ];
@@ -726,7 +951,10 @@ impl ModelSelectionView {
             footer.push(Span::raw(" Back"));
         }
         lines.push(Line::from(footer));
+        Self::render_lines(lines, area, buf);
+    }
 
+    fn render_lines(lines: Vec<Line<'_>>, area: Rect, buf: &mut Buffer) {
         let padded = Rect {
             x: area.x.saturating_add(1),
             y: area.y,
@@ -797,13 +1025,37 @@ impl ModelSelectionView {
             .eq_ignore_ascii_case(&session_ctx.model)
     }
 
+    /// Style layered over every span of the highlighted row.
+    fn highlight_style() -> Style {
+        Style::default()
+            .bg(crate::colors::selection())
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// Label style for a preset row. The current preset keeps its success
+    /// color unless that color would wash out against the selection background.
+    fn preset_label_style(is_selected: bool, is_current: bool, supported: bool) -> Style {
+        let fg = if !supported {
+            crate::colors::text_dim()
+        } else if is_current && is_selected {
+            crate::colors::on_selection(crate::colors::success())
+        } else if is_current {
+            crate::colors::success()
+        } else {
+            crate::colors::text()
+        };
+        if is_selected {
+            Style::default().fg(fg).patch(Self::highlight_style())
+        } else {
+            Style::default().fg(fg)
+        }
+    }
+
     fn render_auto_inherit_row(&self) -> Line<'static> {
         let mut label_style = Style::default().fg(crate::colors::text());
         let mut description_style = Style::default().fg(crate::colors::dim());
         if self.auto_inherit_selected {
-            let highlight = Style::default()
-                .bg(crate::colors::selection())
-                .add_modifier(Modifier::BOLD);
+            let highlight = Self::highlight_style();
             label_style = label_style.patch(highlight);
             description_style = description_style.patch(highlight);
         }
@@ -817,4 +1069,317 @@ impl ModelSelectionView {
             ),
         ])
     }
+
+    fn render_auto_custom_row(&self) -> Line<'static> {
+        let mut label_style = Style::default().fg(crate::colors::text());
+        let mut description_style = Style::default().fg(crate::colors::dim());
+        if self.auto_custom_selected {
+            let highlight = Self::highlight_style();
+            label_style = label_style.patch(highlight);
+            description_style = description_style.patch(highlight);
+        }
+        match self.custom_model_input.as_ref() {
+            Some(input) => {
+                let mut spans = vec![
+                    Span::styled("   ", label_style),
+                    Span::styled("Other model: ", label_style),
+                    Span::styled(format!("{input}▏"), label_style.fg(crate::colors::warning())),
+                ];
+                if self.custom_model_invalid {
+                    spans.push(Span::styled(
+                        "  Model name cannot be empty",
+                        Style::default().fg(crate::colors::error()),
+                    ));
+                }
+                Line::from(spans)
+            }
+            None => Line::from(vec![
+                Span::styled("   ", label_style),
+                Span::styled("Other model…", label_style),
+                Span::styled(" - ", description_style),
+                Span::styled("Type any model name for Auto Drive", description_style),
+            ]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use code_common::model_presets::builtin_model_presets;
+    use std::sync::mpsc::Receiver;
+
+    fn auto_view() -> (ModelSelectionView, Receiver<AppEvent>) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let entries = vec![ModelSelectionEntry::new(
+            ModelSelectionTarget::Auto,
+            "gpt-5.1-codex".to_string(),
+            ReasoningEffort::Medium,
+            true,
+        )];
+        let view = ModelSelectionView::new(
+            builtin_model_presets(None),
+            entries,
+            AppEventSender::new(tx),
+        );
+        (view, rx)
+    }
+
+    fn press(view: &mut ModelSelectionView, code: KeyCode) {
+        view.handle_key_event_direct(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn rendered_text(view: &ModelSelectionView) -> String {
+        let area = Rect::new(0, 0, 80, view.content_line_count());
+        let mut buf = Buffer::empty(area);
+        view.render_without_frame(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn empty_presets_render_placeholder_and_ignore_enter() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let entries = vec![ModelSelectionEntry::new(
+            ModelSelectionTarget::Session,
+            "gpt-5.1-codex".to_string(),
+            ReasoningEffort::Medium,
+            false,
+        )];
+        let mut view = ModelSelectionView::new(Vec::new(), entries, AppEventSender::new(tx));
+
+        assert!(rendered_text(&view).contains("No models available"));
+
+        press(&mut view, KeyCode::Down);
+        press(&mut view, KeyCode::Enter);
+        assert!(!view.is_complete);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn presets_from_multiple_providers_render_under_headers() {
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let preset = |id: &'static str, model: &'static str| ModelPreset {
+            id,
+            label: id,
+            description: "",
+            model,
+            effort: None,
+            supported_efforts: &[],
+        };
+        let presets = vec![
+            preset("claude-sonnet", "claude-sonnet-4"),
+            preset("gpt-codex", "gpt-5.1-codex"),
+            preset("claude-opus", "claude-opus-4"),
+            preset("gpt-general", "gpt-5.1"),
+        ];
+        let entries = vec![ModelSelectionEntry::new(
+            ModelSelectionTarget::Session,
+            "gpt-5.1-codex".to_string(),
+            ReasoningEffort::Medium,
+            false,
+        )];
+        let view = ModelSelectionView::new(presets, entries, AppEventSender::new(tx));
+
+        let text = rendered_text(&view);
+        let lines: Vec<&str> = text.lines().map(str::trim).collect();
+        let position = |needle: &str| {
+            lines
+                .iter()
+                .position(|line| *line == needle)
+                .unwrap_or_else(|| panic!("missing {needle} in:\n{text}"))
+        };
+        let order = [
+            position("OpenAI"),
+            position("GPT-5.1-Codex"),
+            position("GPT-5.1"),
+            position("Anthropic"),
+            position("CLAUDE-Opus-4"),
+            position("CLAUDE-Sonnet-4"),
+        ];
+        assert!(order.windows(2).all(|pair| pair[0] < pair[1]), "{text}");
+    }
+
+    #[test]
+    fn shift_enter_applies_selection_and_keeps_panel_open() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let entries = vec![ModelSelectionEntry::new(
+            ModelSelectionTarget::Session,
+            "gpt-5.1-codex".to_string(),
+            ReasoningEffort::Medium,
+            false,
+        )];
+        let mut view =
+            ModelSelectionView::new(builtin_model_presets(None), entries, AppEventSender::new(tx));
+        assert!(rendered_text(&view).contains("Shift+Enter"));
+
+        press(&mut view, KeyCode::Down);
+        let expected = view.presets[view.selected_index].model.to_string();
+        view.handle_key_event_direct(KeyEvent::new(KeyCode::Enter, KeyModifiers::SHIFT));
+
+        assert!(!view.is_complete);
+        match rx.try_recv() {
+            Ok(AppEvent::UpdateModelSelection { model, .. }) => assert_eq!(model, expected),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert_eq!(view.current_model, expected);
+    }
+
+    #[test]
+    fn auto_other_model_emits_free_form_model() {
+        let (mut view, rx) = auto_view();
+        // Inherit row starts selected; Up wraps to the "Other model…" row.
+        press(&mut view, KeyCode::Up);
+        press(&mut view, KeyCode::Enter);
+
+        press(&mut view, KeyCode::Enter);
+        assert!(!view.is_complete, "empty model must be rejected");
+        assert!(rx.try_recv().is_err());
+
+        for ch in "my-cheap-model".chars() {
+            press(&mut view, KeyCode::Char(ch));
+        }
+        press(&mut view, KeyCode::Enter);
+
+        assert!(view.is_complete);
+        match rx.try_recv() {
+            Ok(AppEvent::UpdateAutoModelSelection { model }) => {
+                assert_eq!(model, "my-cheap-model");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn switching_targets_restores_previous_selection() {
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let entries = vec![
+            ModelSelectionEntry::new(
+                ModelSelectionTarget::Session,
+                "gpt-5.1-codex".to_string(),
+                ReasoningEffort::Medium,
+                false,
+            ),
+            ModelSelectionEntry::new(
+                ModelSelectionTarget::Auto,
+                "gpt-5.1-codex".to_string(),
+                ReasoningEffort::Medium,
+                true,
+            ),
+        ];
+        let mut view = ModelSelectionView::new(
+            builtin_model_presets(None),
+            entries,
+            AppEventSender::new(tx),
+        );
+        let default_index = view.selected_index;
+
+        press(&mut view, KeyCode::Down);
+        let chosen = view.selected_index;
+        assert_ne!(chosen, default_index, "Down should pick a non-default preset");
+
+        press(&mut view, KeyCode::Tab);
+        assert_eq!(view.target, ModelSelectionTarget::Auto);
+        press(&mut view, KeyCode::Tab);
+        assert_eq!(view.target, ModelSelectionTarget::Session);
+
+        assert_eq!(view.selected_index, chosen);
+    }
+
+    #[test]
+    fn auto_override_survives_cycling_targets() {
+        let (tx, _rx) = std::sync::mpsc::channel();
+        let entries = vec![
+            ModelSelectionEntry::new(
+                ModelSelectionTarget::Auto,
+                "gpt-5.1-codex".to_string(),
+                ReasoningEffort::Medium,
+                true,
+            ),
+            ModelSelectionEntry::new(
+                ModelSelectionTarget::Session,
+                "gpt-5.1-codex".to_string(),
+                ReasoningEffort::Medium,
+                false,
+            ),
+        ];
+        let mut view = ModelSelectionView::new(
+            builtin_model_presets(None),
+            entries,
+            AppEventSender::new(tx),
+        );
+        assert!(view.auto_inherit_selected);
+
+        // Down leaves the inherit row for an explicit preset override.
+        press(&mut view, KeyCode::Down);
+        assert!(!view.auto_inherit_selected);
+        let chosen = view.selected_index;
+
+        press(&mut view, KeyCode::Tab);
+        assert_eq!(view.target, ModelSelectionTarget::Session);
+        assert!(!view.auto_inherit_selected);
+        press(&mut view, KeyCode::Tab);
+        assert_eq!(view.target, ModelSelectionTarget::Auto);
+
+        assert!(!view.auto_inherit_selected, "override must not reset to inherit");
+        assert_eq!(view.selected_index, chosen);
+    }
+
+    #[test]
+    fn unsupported_effort_rows_are_skipped() {
+        let (tx, _rx) = std::sync::mpsc::channel();
+        use code_core::protocol_config_types::ReasoningEffort as PresetEffort;
+        const SUPPORTED: &[PresetEffort] = &[PresetEffort::High, PresetEffort::Low];
+        let preset = |id: &'static str, effort: PresetEffort| ModelPreset {
+            id,
+            label: id,
+            description: "",
+            model: "gpt-5.1",
+            effort: Some(effort),
+            supported_efforts: SUPPORTED,
+        };
+        let presets = vec![
+            preset("gpt-5.1-high", PresetEffort::High),
+            preset("gpt-5.1-medium", PresetEffort::Medium),
+            preset("gpt-5.1-low", PresetEffort::Low),
+        ];
+        let entries = vec![ModelSelectionEntry::new(
+            ModelSelectionTarget::Session,
+            "gpt-5.1".to_string(),
+            ReasoningEffort::High,
+            false,
+        )];
+        let mut view = ModelSelectionView::new(presets, entries, AppEventSender::new(tx));
+        assert_eq!(view.selected_index, 0);
+        assert!(rendered_text(&view).contains("Medium (unsupported)"));
+
+        press(&mut view, KeyCode::Down);
+        assert_eq!(view.selected_index, 2, "Down must skip the unsupported Medium row");
+        press(&mut view, KeyCode::Up);
+        assert_eq!(view.selected_index, 0);
+    }
+
+    #[test]
+    fn selected_current_preset_label_composes_highlight_and_legible_fg() {
+        let style = ModelSelectionView::preset_label_style(true, true, true);
+        assert_eq!(style.bg, Some(crate::colors::selection()));
+        assert!(style.add_modifier.contains(Modifier::BOLD));
+        let fg = style.fg.expect("selected label keeps a foreground");
+        assert_eq!(fg, crate::colors::on_selection(crate::colors::success()));
+        assert!(
+            fg == crate::colors::text_bright()
+                || crate::theme::contrast_ratio(fg, crate::colors::selection()) >= 3.0
+        );
+
+        let unselected = ModelSelectionView::preset_label_style(false, true, true);
+        assert_eq!(unselected.fg, Some(crate::colors::success()));
+        assert_eq!(unselected.bg, None);
+        assert!(!unselected.add_modifier.contains(Modifier::BOLD));
+    }
 }
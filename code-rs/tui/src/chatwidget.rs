@@ -42,7 +42,7 @@ use code_core::config_types::AutoDriveContinueMode;
 use code_core::config_types::Notifications;
 use code_core::config_types::ReasoningEffort;
 use code_core::config_types::TextVerbosity;
-use code_core::global_usage_tracker::GlobalUsageSnapshot;
+use code_core::global_usage_tracker::{format_cost, GlobalUsageSnapshot, DEFAULT_COST_PRECISION};
 use code_core::plan_tool::{PlanItemArg, StepStatus, UpdatePlanArgs};
 use code_core::model_family::derive_default_model_family;
 use code_core::model_family::find_family_for_model;
@@ -21178,9 +21178,9 @@ Have we met every part of this goal and is there no further work to do?"#
         let total = Self::format_compact_tokens(snapshot.totals.total_tokens);
         let last_hour = Self::format_compact_tokens(snapshot.trailing.last_hour.total_tokens);
         format!(
-            "{} · ${:.2} · {} last hour",
+            "{} · {} · {} last hour",
             total,
-            snapshot.totals.cost_usd,
+            format_cost(snapshot.totals.cost_usd, DEFAULT_COST_PRECISION),
             last_hour
         )
     }
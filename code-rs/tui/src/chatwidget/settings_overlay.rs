@@ -29,7 +29,13 @@ use crate::chrome_launch::{ChromeLaunchOption, CHROME_LAUNCH_CHOICES};
 use super::limits_overlay::{LimitsOverlay, LimitsOverlayContent};
 use super::{GlobalUsageState, GlobalUsageStatus, AUTO_INACTIVITY_TIMEOUT_OPTIONS};
 use code_core::config::EngineMode;
-use code_core::global_usage_tracker::{GlobalUsageSnapshot, UsageBucket, UsageTotals};
+use code_core::global_usage_tracker::{
+    format_cost,
+    GlobalUsageSnapshot,
+    UsageBucket,
+    UsageTotals,
+    DEFAULT_COST_PRECISION,
+};
 use code_protocol::num_format::format_with_separators;
 use std::cell::Cell;
 use crate::live_wrap::take_prefix_by_width;
@@ -1577,8 +1583,8 @@ impl GlobalUsageSettingsContent {
             )
         )));
         lines.push(Line::from(format!(
-            "  Cost            : ${:.2}",
-            snapshot.totals.cost_usd
+            "  Cost            : {}",
+            format_cost(snapshot.totals.cost_usd, DEFAULT_COST_PRECISION)
         )));
         lines.push(Line::default());
 
@@ -1601,10 +1607,10 @@ impl GlobalUsageSettingsContent {
             )));
             for entry in snapshot.model_usage.iter().take(5) {
                 lines.push(Line::from(format!(
-                    "  {:<18} tokens={} cost=${:.2}",
+                    "  {:<18} tokens={} cost={}",
                     entry.bucket.as_str(),
                     self.display_tokens(entry.totals.total_tokens),
-                    entry.totals.cost_usd,
+                    format_cost(entry.totals.cost_usd, DEFAULT_COST_PRECISION),
                 )));
             }
             lines.push(Line::default());
@@ -1617,10 +1623,10 @@ impl GlobalUsageSettingsContent {
             )));
             for entry in snapshot.source_usage.iter().take(5) {
                 lines.push(Line::from(format!(
-                    "  {:<22} tokens={} cost=${:.2}",
+                    "  {:<22} tokens={} cost={}",
                     entry.label,
                     self.display_tokens(entry.totals.total_tokens),
-                    entry.totals.cost_usd,
+                    format_cost(entry.totals.cost_usd, DEFAULT_COST_PRECISION),
                 )));
             }
             lines.push(Line::default());
@@ -1638,11 +1644,11 @@ impl GlobalUsageSettingsContent {
                 Style::default().fg(crate::colors::text_dim()),
             )));
             lines.push(Line::from(format!(
-                "  {} [{}] tokens={} cost=${:.2}",
+                "  {} [{}] tokens={} cost={}",
                 session.session_id,
                 session.model_bucket.as_str(),
                 self.display_tokens(session.totals.total_tokens),
-                session.totals.cost_usd,
+                format_cost(session.totals.cost_usd, DEFAULT_COST_PRECISION),
             )));
         }
 
@@ -1680,11 +1686,11 @@ impl GlobalUsageSettingsContent {
         )));
         for bucket in buckets.iter().rev().take(limit).rev() {
             lines.push(Line::from(format!(
-                "  {}-{} {} (cost ${:.2})",
+                "  {}-{} {} (cost {})",
                 bucket.start.format("%m-%d %H:%M"),
                 bucket.end.format("%H:%M"),
                 self.display_tokens(bucket.totals.total_tokens),
-                bucket.totals.cost_usd,
+                format_cost(bucket.totals.cost_usd, DEFAULT_COST_PRECISION),
             )));
         }
         lines.push(Line::default());
@@ -1692,9 +1698,9 @@ impl GlobalUsageSettingsContent {
 
     fn summary_line(&self, snapshot: &GlobalUsageSnapshot) -> String {
         format!(
-            "{} tokens · ${:.2} · {} last hour",
+            "{} tokens · {} · {} last hour",
             self.display_tokens(snapshot.totals.total_tokens),
-            snapshot.totals.cost_usd,
+            format_cost(snapshot.totals.cost_usd, DEFAULT_COST_PRECISION),
             self.display_tokens(snapshot.trailing.last_hour.total_tokens)
         )
     }
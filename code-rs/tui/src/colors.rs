@@ -1,5 +1,5 @@
 use ratatui::style::Color;
-use crate::theme::{current_theme, palette_mode, quantize_color_for_palette, PaletteMode};
+use crate::theme::{contrast_ratio, current_theme, palette_mode, quantize_color_for_palette, PaletteMode};
 
 // Legacy color constants - now redirect to theme
 pub(crate) fn light_blue() -> Color {
@@ -119,11 +119,21 @@ pub(crate) fn background() -> Color {
     current_theme().background
 }
 
-#[allow(dead_code)]
 pub(crate) fn selection() -> Color {
     current_theme().selection
 }
 
+/// Foreground for text drawn over `selection()`: keeps `preferred` when it
+/// stays readable, otherwise falls back to the theme's bright text color.
+pub(crate) fn on_selection(preferred: Color) -> Color {
+    const MIN_SELECTION_CONTRAST: f32 = 3.0;
+    if contrast_ratio(preferred, selection()) >= MIN_SELECTION_CONTRAST {
+        preferred
+    } else {
+        text_bright()
+    }
+}
+
 // Syntax/special helpers
 pub(crate) fn function() -> Color {
     current_theme().function
@@ -13,6 +13,37 @@ struct GlyphPixel {
     style: Style,
 }
 
+/// Phase breakpoints for the intro reveal, as `(start, end)` pairs over `t` in
+/// `0.0..=1.0`. Each phase eases between its breakpoints with `smoothstep`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct IntroTiming {
+    /// Outline draws left to right.
+    pub outline: (f32, f32),
+    /// Interior fills left to right.
+    pub fill: (f32, f32),
+    /// Soft fade toward the final light color.
+    pub fade: (f32, f32),
+    /// Scanline sweep.
+    pub scan: (f32, f32),
+}
+
+impl Default for IntroTiming {
+    fn default() -> Self {
+        Self {
+            outline: (0.00, 0.60),
+            fill: (0.35, 0.95),
+            fade: (0.90, 1.00),
+            scan: (0.55, 0.85),
+        }
+    }
+}
+
+impl IntroTiming {
+    fn progress(phase: (f32, f32), t: f32) -> f32 {
+        smoothstep(phase.0, phase.1, t)
+    }
+}
+
 pub(crate) const SPARKSI_LIGHT_BLUE: Color = Color::Rgb(132, 188, 255);
 pub(crate) const SPARKSI_LIME_GREEN: Color = Color::Rgb(181, 255, 92);
 
@@ -93,6 +124,32 @@ pub(crate) fn render_intro_word_with_options(
     color_mode: IntroColorMode,
     offset: i32,
     clear_background: bool,
+) {
+    render_intro_word_with_timing(
+        area,
+        buf,
+        t,
+        alpha,
+        word,
+        color_mode,
+        offset,
+        clear_background,
+        IntroTiming::default(),
+    )
+}
+
+// Same as `render_intro_word_with_options`, with caller-provided phase pacing.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn render_intro_word_with_timing(
+    area: Rect,
+    buf: &mut Buffer,
+    t: f32,
+    alpha: Option<f32>,
+    word: &str,
+    color_mode: IntroColorMode,
+    offset: i32,
+    clear_background: bool,
+    timing: IntroTiming,
 ) {
     // Compute the final render rect first (including our 1‑col right shift)
     let mut r = area;
@@ -107,11 +164,10 @@ pub(crate) fn render_intro_word_with_options(
     }
 
     let t = t.clamp(0.0, 1.0);
-    let outline_p = smoothstep(0.00, 0.60, t); // outline draws L->R
-    let fill_p = smoothstep(0.35, 0.95, t); // interior fills L->R
-    // Original fade profile: begin soft fade near the end.
-    let fade = smoothstep(0.90, 1.00, t);
-    let scan_p = smoothstep(0.55, 0.85, t); // scanline sweep
+    let outline_p = IntroTiming::progress(timing.outline, t);
+    let fill_p = IntroTiming::progress(timing.fill, t);
+    let fade = IntroTiming::progress(timing.fade, t);
+    let scan_p = IntroTiming::progress(timing.scan, t);
     let frame = (t * 60.0) as u32;
 
     // Build scaled mask + border map using the actual render rect size
@@ -373,6 +429,13 @@ fn bump_rgb(c: Color, amt: f32) -> Color {
     }
 }
 
+// Public helper exposing the scaled pixel mask (rows of columns) for static rendering or golden tests.
+#[allow(dead_code)]
+pub fn word_mask(word: &str, max_w: u16, max_h: u16) -> (usize, Vec<Vec<bool>>) {
+    let (scale, mask, _, _) = scaled_mask(word, max_w, max_h);
+    (scale, mask)
+}
+
 // Scale a 5×7 word bitmap (e.g., "CODE") to fill `max_w` x `max_h`, returning (scale, grid, w, h)
 fn scaled_mask(word: &str, max_w: u16, max_h: u16) -> (usize, Vec<Vec<bool>>, usize, usize) {
     let rows = 7usize;
@@ -524,3 +587,52 @@ fn glyph_5x7(ch: char) -> [&'static str; 7] {
         ],
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline_cells(timing: IntroTiming, t: f32) -> usize {
+        let area = Rect::new(0, 0, 80, 12);
+        let mut buf = Buffer::empty(area);
+        render_intro_word_with_timing(
+            area,
+            &mut buf,
+            t,
+            None,
+            "CODE",
+            IntroColorMode::Rainbow,
+            0,
+            false,
+            timing,
+        );
+        buf.content().iter().filter(|cell| cell.symbol() == "▓").count()
+    }
+
+    #[test]
+    fn word_mask_scales_glyphs_to_fit() {
+        // "CODE" is 4 glyphs of 5 columns plus 3 gap columns, 7 rows tall, with 66 set pixels.
+        let (scale, mask) = word_mask("CODE", 200, 40);
+        assert_eq!(scale, 3);
+        assert_eq!(mask.len(), 7 * 3);
+        assert!(mask.iter().all(|row| row.len() == 23 * 3));
+        let set = mask.iter().flatten().filter(|&&pixel| pixel).count();
+        assert_eq!(set, 66 * 3 * 3);
+
+        let (scale, mask) = word_mask("CODE", 80, 12);
+        assert_eq!(scale, 1);
+        assert_eq!((mask.len(), mask[0].len()), (7, 23));
+    }
+
+    #[test]
+    fn faster_timing_fills_mask_earlier() {
+        let fast = IntroTiming {
+            outline: (0.00, 0.30),
+            fill: (0.15, 0.50),
+            ..IntroTiming::default()
+        };
+        let t = 0.6;
+        assert!(outline_cells(IntroTiming::default(), t) > 0);
+        assert_eq!(outline_cells(fast, t), 0);
+    }
+}
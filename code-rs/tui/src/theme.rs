@@ -896,7 +896,7 @@ fn color_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
     dr * dr + dg * dg + db * db
 }
 
-fn contrast_ratio(foreground: Color, background: Color) -> f32 {
+pub(crate) fn contrast_ratio(foreground: Color, background: Color) -> f32 {
     let lf = relative_luminance_color(foreground);
     let lb = relative_luminance_color(background);
     if lf >= lb {